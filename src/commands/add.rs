@@ -0,0 +1,42 @@
+#![forbid(unsafe_code)]
+
+use crate::library::error::VcsError;
+use crate::library::vcs_repository::VcsRepository;
+use crate::utils::work_with_index::{normalize_path, read_index, write_index};
+use crate::utils::work_with_merge_state::{read_merge_state, write_merge_state};
+use crate::utils::work_with_trees::write_flat_tree_from_path;
+use std::path::PathBuf;
+
+/// Stages the current content of the files along the given paths relative to the repository root.
+/// Directories are staged with all their files, files deleted from the directory are unstaged.
+/// Only the files along the given paths are read and put to the object store.
+/// If a merge is in progress, the conflicts along the given paths are marked as resolved.
+pub fn add(vcs: &VcsRepository, paths: &[PathBuf]) -> Result<(), VcsError> {
+    let root_dir = vcs.get_dir();
+    let mut index = read_index(vcs)?;
+    let mut merge_state = read_merge_state(root_dir)?;
+
+    for path in paths.iter() {
+        let path = normalize_path(path)?;
+        let mut found = index.remove_entries(&path);
+        for (entry, (mode, hash)) in write_flat_tree_from_path(root_dir, &path)?.iter() {
+            index.add_entry(entry, *mode, hash);
+            found = true;
+        }
+        if !found && !path.as_os_str().is_empty() {
+            if root_dir.join(&path).exists() {
+                return Err(VcsError::IgnoredPath(path));
+            }
+            return Err(VcsError::PathNotFound(path));
+        }
+        if let Some(state) = merge_state.as_mut() {
+            state.resolve(&path);
+        }
+    }
+
+    write_index(root_dir, &index)?;
+    match merge_state {
+        Some(state) => write_merge_state(root_dir, &state),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,40 @@
+#![forbid(unsafe_code)]
+
+use crate::library::config::Config;
+use crate::library::error::VcsError;
+use crate::library::report::ConfigReport;
+use crate::utils::work_with_config::write_config_value;
+use std::path::Path;
+
+/// Returns the value of the key from the configuration.
+pub fn get_config(config: &Config, key: &str) -> Result<String, VcsError> {
+    config
+        .get(key)
+        .map(str::to_string)
+        .ok_or_else(|| VcsError::NoConfigValue(key.to_lowercase()))
+}
+
+/// Sets the key to the value in the configuration file along the given path.
+/// The values of the known keys are checked.
+pub fn set_config(path: &Path, key: &str, value: &str) -> Result<(), VcsError> {
+    let key = key.to_lowercase();
+    Config::check_key(&key)?;
+    Config::check_value(&key, value)?;
+    write_config_value(path, &key, Some(value))?;
+    Ok(())
+}
+
+/// Removes the key from the configuration file along the given path.
+pub fn unset_config(path: &Path, key: &str) -> Result<(), VcsError> {
+    let key = key.to_lowercase();
+    Config::check_key(&key)?;
+    match write_config_value(path, &key, None)? {
+        true => Ok(()),
+        false => Err(VcsError::NoConfigValue(key)),
+    }
+}
+
+/// Returns all settings of the configuration, they are printed as "key = value" lines.
+pub fn list_config(config: &Config) -> ConfigReport {
+    ConfigReport::list(config.get_entries())
+}
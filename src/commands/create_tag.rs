@@ -0,0 +1,24 @@
+#![forbid(unsafe_code)]
+
+use crate::library::error::VcsError;
+use crate::library::tag::{Annotation, Tag};
+use crate::library::vcs_repository::VcsRepository;
+use crate::utils::operation_hash::calculate_hash;
+
+/// Creates a tag with the given name for the commit of the given revision,
+/// the current commit by default. The tag is annotated if the annotation is given.
+/// Returns the hash of the tagged commit.
+pub fn create_tag(
+    vcs: &mut VcsRepository,
+    tag_name: &str,
+    commit: Option<&str>,
+    annotation: Option<Annotation>,
+) -> Result<String, VcsError> {
+    let commit = match commit {
+        Some(name) => vcs.resolve_commit(name)?,
+        None => vcs.get_current_commit(),
+    };
+    let hash = calculate_hash(commit);
+    vcs.add_tag(Tag::init(tag_name, &hash, annotation))?;
+    Ok(hash)
+}
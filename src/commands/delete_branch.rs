@@ -0,0 +1,29 @@
+#![forbid(unsafe_code)]
+
+use crate::library::error::VcsError;
+use crate::library::head::Head;
+use crate::library::vcs_repository::VcsRepository;
+
+/// Deletes the branch with the given name and returns the hash of its last commit.
+/// Only the branch is deleted, its commits are removed by the garbage collection
+/// once they can't be reached. Unmerged branches are deleted only if `force` is set,
+/// the default branch is never deleted.
+pub fn delete_branch(
+    vcs: &mut VcsRepository,
+    branch_name: &str,
+    force: bool,
+) -> Result<String, VcsError> {
+    let branch = vcs.get_branch_by_name(branch_name)?.clone();
+    if vcs.get_head() == &Head::Branch(branch_name.to_string()) {
+        return Err(VcsError::CurrentBranch(branch_name.to_string()));
+    }
+    if vcs.get_default_branch() == branch_name {
+        return Err(VcsError::DefaultBranch(branch_name.to_string()));
+    }
+    if !force && !vcs.is_merged(&branch)? {
+        return Err(VcsError::UnmergedBranch(branch_name.to_string()));
+    }
+
+    vcs.delete_branch(branch_name)?;
+    Ok(branch.get_commit_hash().to_string())
+}
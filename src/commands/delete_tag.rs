@@ -0,0 +1,11 @@
+#![forbid(unsafe_code)]
+
+use crate::library::error::VcsError;
+use crate::library::vcs_repository::VcsRepository;
+
+/// Deletes the tag with the given name and returns the hash of the tagged commit.
+/// The commit is removed by the garbage collection once it can't be reached.
+pub fn delete_tag(vcs: &mut VcsRepository, tag_name: &str) -> Result<String, VcsError> {
+    let tag = vcs.delete_tag(tag_name)?;
+    Ok(tag.get_commit_hash().to_string())
+}
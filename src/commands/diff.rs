@@ -0,0 +1,80 @@
+#![forbid(unsafe_code)]
+
+use crate::library::commit::Commit;
+use crate::library::error::VcsError;
+use crate::library::files::File;
+use crate::library::vcs_repository::VcsRepository;
+use crate::utils::diff::unified_diff;
+use crate::utils::extract_files::{files_from_commit, files_from_dir};
+use crate::utils::work_with_commit_files::{added_files, deleted_files, modified_files};
+use std::path::PathBuf;
+
+/// Returns the data of the file along the given path.
+fn find_data<'a>(files: &'a [File], path: &PathBuf) -> Option<&'a [u8]> {
+    files
+        .iter()
+        .find(|file| file.get_path() == path)
+        .map(|file| file.get_data().as_slice())
+}
+
+/// Returns the unified diff between two sets of files.
+pub fn diff_files(old_files: &[File], new_files: &[File], context: usize) -> String {
+    let mut paths = added_files(new_files, old_files);
+    paths.append(&mut modified_files(new_files, old_files));
+    paths.append(&mut deleted_files(new_files, old_files));
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            unified_diff(
+                path,
+                find_data(old_files, path),
+                find_data(new_files, path),
+                context,
+            )
+        })
+        .collect()
+}
+
+fn diff_two_commits(
+    vcs: &VcsRepository,
+    old: &Commit,
+    new: &Commit,
+    context: usize,
+) -> Result<String, VcsError> {
+    let old_files = files_from_commit(vcs.get_dir(), old)?;
+    let new_files = files_from_commit(vcs.get_dir(), new)?;
+    Ok(diff_files(&old_files, &new_files, context))
+}
+
+/// Returns the diff between the current commit and the working tree.
+pub fn diff_working_tree(vcs: &VcsRepository, context: usize) -> Result<String, VcsError> {
+    let commit_files = files_from_commit(vcs.get_dir(), vcs.get_current_commit())?;
+    let repo_files = files_from_dir(vcs.get_dir())?;
+    Ok(diff_files(&commit_files, &repo_files, context))
+}
+
+/// Returns the diff between the commits of two revisions, such as "HEAD~1", tag names or hash prefixes.
+pub fn diff_commits(
+    vcs: &VcsRepository,
+    old_hash: &str,
+    new_hash: &str,
+    context: usize,
+) -> Result<String, VcsError> {
+    let old = vcs.resolve_commit(old_hash)?;
+    let new = vcs.resolve_commit(new_hash)?;
+    diff_two_commits(vcs, old, new, context)
+}
+
+/// Returns the diff between the last commits of two branches with the given names.
+pub fn diff_branches(
+    vcs: &VcsRepository,
+    old_branch: &str,
+    new_branch: &str,
+    context: usize,
+) -> Result<String, VcsError> {
+    let old = vcs.get_branch_commit(vcs.get_branch_by_name(old_branch)?)?;
+    let new = vcs.get_branch_commit(vcs.get_branch_by_name(new_branch)?)?;
+    diff_two_commits(vcs, old, new, context)
+}
@@ -0,0 +1,14 @@
+#![forbid(unsafe_code)]
+
+use crate::library::error::VcsError;
+use crate::library::vcs_repository::VcsRepository;
+use crate::utils::pack_files::pack_vcs;
+
+/// Deletes the commits which can't be reached from the branches and the objects used by nobody.
+/// Returns the number of deleted commits.
+pub fn gc(vcs: &mut VcsRepository) -> Result<usize, VcsError> {
+    let count = vcs.prune_commits()?;
+    pack_vcs(vcs.get_dir(), vcs)?;
+    vcs.collect_garbage()?;
+    Ok(count)
+}
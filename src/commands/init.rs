@@ -1,20 +1,29 @@
 #![forbid(unsafe_code)]
 
+use crate::library::branch::Branch;
+use crate::library::config::Config;
+use crate::library::error::VcsError;
 use crate::library::vcs_repository::VcsRepository;
 use std::fs;
 use std::path::Path;
 
-/// Creates a directory.vcs in the given path, which will contain meta information on the repository, 
-/// including the entire subtree of the directory folder. 
-/// Creates a commit with the message "Initial commit".
-pub fn init(path: &Path) -> Result<VcsRepository, &'static str> {
-    fs::create_dir_all(path).unwrap();
+/// Creates a directory.vcs in the given path, which will contain meta information on the repository,
+/// including the entire subtree of the directory folder.
+/// Creates a commit with the message "Initial commit" in the initial branch,
+/// which is "init.defaultbranch" of the configuration if no name is given.
+pub fn init(path: &Path, initial_branch: Option<&str>) -> Result<VcsRepository, VcsError> {
+    let branch_name = match initial_branch {
+        Some(name) => name.to_string(),
+        None => Config::read(path)?.get_default_branch()?.to_string(),
+    };
+    Branch::check_name(&branch_name)?;
+    fs::create_dir_all(path).map_err(|e| VcsError::io(path, e))?;
     if !path.is_dir() {
-        Err("is not a dir")
+        Err(VcsError::NotADirectory(path.to_path_buf()))
     } else if fs::create_dir(path.join(".vcs")).is_err() {
-        Err("already exists")
+        Err(VcsError::AlreadyExists(path.to_path_buf()))
     } else {
-        let vcs = VcsRepository::init(path)?;
+        let vcs = VcsRepository::init(path, &branch_name)?;
         Ok(vcs)
     }
 }
@@ -1,12 +1,32 @@
 #![forbid(unsafe_code)]
 
-use super::jump_to_commit::jump_to_commit;
+use crate::library::error::VcsError;
+use crate::library::head::Head;
 use crate::library::vcs_repository::VcsRepository;
-use crate::utils::operation_hash::calculate_hash;
+use crate::utils::journal::{begin_operation, finish_operation};
+use crate::utils::update_repo::update_repo;
+use crate::utils::work_with_merge_state::check_no_merge;
 
 /// Moves the repository to the last commit of the branch with given branch.
-pub fn jump_to_branch(vcs: &mut VcsRepository, branch_name: &str) -> Result<(), &'static str> {
+/// Commits created on the detached head are left only if `force` is set.
+/// The jump is journaled: if it is interrupted, it is rolled back by the next command changing the repository.
+pub fn jump_to_branch(
+    vcs: &mut VcsRepository,
+    branch_name: &str,
+    force: bool,
+) -> Result<(), VcsError> {
+    check_no_merge(vcs.get_dir())?;
+    vcs.check_no_uncommited()?;
+
     let branch = vcs.get_branch_by_name(branch_name)?;
-    let commit = branch.get_last_commit();
-    jump_to_commit(vcs, calculate_hash(&commit))
+    if !force {
+        vcs.check_no_detached_commits(vcs.get_branch_commit(branch)?)?;
+    }
+    begin_operation(vcs, "jump")?;
+    vcs.change_head(Head::Branch(branch.get_name().to_string()));
+
+    update_repo(vcs)?;
+    finish_operation(vcs)?;
+
+    Ok(())
 }
@@ -1,19 +1,41 @@
 #![forbid(unsafe_code)]
 
+use crate::library::error::VcsError;
+use crate::library::head::Head;
 use crate::library::vcs_repository::VcsRepository;
+use crate::utils::journal::{begin_operation, finish_operation};
+use crate::utils::operation_hash::calculate_hash;
 use crate::utils::update_repo::update_repo;
+use crate::utils::work_with_merge_state::check_no_merge;
 
-/// Moves the repository to a commit with the given hash.
-pub fn jump_to_commit(vcs: &mut VcsRepository, commit_hash: u64) -> Result<(), &'static str> {
+/// Moves the repository to the commit of the given revision, such as "HEAD~1", a tag name or a hash prefix.
+/// The head follows the branch of the commit if it is the last one, otherwise it is detached.
+/// Commits created on the detached head are left only if `force` is set.
+/// The jump is journaled: if it is interrupted, it is rolled back by the next command changing the repository.
+pub fn jump_to_commit(
+    vcs: &mut VcsRepository,
+    commit_hash: &str,
+    force: bool,
+) -> Result<(), VcsError> {
+    check_no_merge(vcs.get_dir())?;
     vcs.check_no_uncommited()?;
 
-    let commit = vcs.get_commit_by_hash(commit_hash)?.clone();
-
-    vcs.change_current_commit(&commit);
-    let branch = vcs.get_branch_by_commit(&commit)?.clone();
-    vcs.change_current_branch(&branch)?;
+    let commit = vcs.resolve_commit(commit_hash)?;
+    if !force {
+        vcs.check_no_detached_commits(commit)?;
+    }
+    let hash = calculate_hash(commit);
+    let head = match vcs.get_branch_by_commit(commit) {
+        Ok(branch) if branch.get_commit_hash() == hash => {
+            Head::Branch(branch.get_name().to_string())
+        }
+        _ => Head::Detached(hash),
+    };
+    begin_operation(vcs, "jump")?;
+    vcs.change_head(head);
 
     update_repo(vcs)?;
+    finish_operation(vcs)?;
 
     Ok(())
 }
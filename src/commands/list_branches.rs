@@ -0,0 +1,25 @@
+#![forbid(unsafe_code)]
+
+use crate::library::error::VcsError;
+use crate::library::head::Head;
+use crate::library::report::{BranchEntry, BranchListReport};
+use crate::library::vcs_repository::VcsRepository;
+
+/// Returns the branches sorted by name with their last commits,
+/// the current branch or the detached head is marked with "*" in the text.
+pub fn list_branches(vcs: &VcsRepository) -> Result<BranchListReport, VcsError> {
+    let detached = match vcs.get_head() {
+        Head::Detached(hash) => Some(hash.clone()),
+        Head::Branch(_) => None,
+    };
+    let mut branches = vec![];
+    for branch in vcs.get_branches().iter() {
+        branches.push(BranchEntry::init(
+            branch.get_name(),
+            branch.get_commit_hash(),
+            vcs.get_branch_commit(branch)?.get_msg(),
+            vcs.get_head() == &Head::Branch(branch.get_name().to_string()),
+        ));
+    }
+    Ok(BranchListReport::init(detached, branches))
+}
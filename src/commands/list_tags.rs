@@ -0,0 +1,10 @@
+#![forbid(unsafe_code)]
+
+use crate::library::report::TagListReport;
+use crate::library::vcs_repository::VcsRepository;
+
+/// Returns the tags sorted by name with the tagged commits,
+/// annotated tags are followed by their tagger, date and message in the text.
+pub fn list_tags(vcs: &VcsRepository) -> TagListReport {
+    TagListReport::init(vcs.get_tags().to_vec())
+}
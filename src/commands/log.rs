@@ -1,42 +1,459 @@
 #![forbid(unsafe_code)]
 
+use crate::library::commit::Commit;
+use crate::library::error::VcsError;
+use crate::library::head::Head;
+use crate::library::log_options::{LogFormat, LogOptions};
+use crate::library::report::{Changes, LogEntry, LogReport, Report};
+use crate::library::revision::{RevisionRange, HEAD};
+use crate::library::tree::{EntryMode, FlatTree};
 use crate::library::vcs_repository::VcsRepository;
-use crate::utils::extract_files::files_from_commit;
 use crate::utils::operation_hash::calculate_hash;
-use crate::utils::print_files::print_changed_paths;
-use crate::utils::work_with_commit_files::{added_files, deleted_files, modified_files};
+use crate::utils::print_files::format_changed_paths;
+use crate::utils::work_with_trees::flatten_tree;
+use chrono::{DateTime, Local};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
-/// Outputs a list to the terminal from the repository initialization to the current one.
-pub fn log(vcs: &VcsRepository) -> Result<(), &'static str> {
-    let mut commit_to_log = Some(vcs.get_current_commit());
-    while let Some(commit) = commit_to_log {
-        println!("commit {}", calculate_hash(&commit));
-        println!("Date: {}", format!("{}", commit.get_date().format("%c %z")));
-        println!("Message: {}", commit.get_msg());
+/// The length of the commit hashes in the short output.
+pub const SHORT_HASH_LEN: usize = 7;
 
-        if commit.is_initial() {
-            println!(" No changes");
-            commit_to_log = None;
-        } else {
-            let nxt_commit = vcs.get_commit_by_hash(commit.get_parent_hash()?)?;
+/// Returns the beginning of the hash used in the short output.
+fn short_hash(hash: &str) -> &str {
+    &hash[..SHORT_HASH_LEN.min(hash.len())]
+}
+
+/// Returns the names of the references to the commits by their hashes:
+/// "HEAD -> branch" for the current branch, "HEAD" for the detached head, branches and tags.
+fn ref_names(vcs: &VcsRepository) -> HashMap<String, Vec<String>> {
+    let mut ans: HashMap<String, Vec<String>> = HashMap::new();
+    if let Head::Detached(hash) = vcs.get_head() {
+        ans.entry(hash.clone()).or_default().push(HEAD.to_string());
+    }
+    for branch in vcs.get_branches().iter() {
+        let name = match vcs.get_head() {
+            Head::Branch(name) if name == branch.get_name() => format!("{} -> {}", HEAD, name),
+            _ => branch.get_name().to_string(),
+        };
+        ans.entry(branch.get_commit_hash().to_string())
+            .or_default()
+            .push(name);
+    }
+    for tag in vcs.get_tags().iter() {
+        ans.entry(tag.get_commit_hash().to_string())
+            .or_default()
+            .push(format!("tag: {}", tag.get_name()));
+    }
+    ans
+}
+
+/// Returns the references in parentheses after a space, or nothing if there are none.
+fn decoration(refs: &[String]) -> String {
+    match refs.is_empty() {
+        true => String::new(),
+        false => format!(" ({})", refs.join(", ")),
+    }
+}
 
-            let new_files = &files_from_commit(&commit)?;
-            let old_files = &files_from_commit(&nxt_commit)?;
+/// Outputs the commit according to the template, the placeholders are:
+/// `%H` and `%h` for the full and the short hash, `%P` and `%p` for the parent hashes,
+/// `%an`, `%ae`, `%cn` and `%ce` for the names and emails of the author and the committer,
+/// `%ad` and `%as` for the full and the short date, `%s` for the message,
+/// `%d` for the references, `%n` for a new line and `%%` for a percent sign.
+/// Unknown placeholders are kept as they are.
+pub fn format_commit(commit: &Commit, refs: &[String], template: &str) -> String {
+    let hash = calculate_hash(commit);
+    let parents = commit.get_parent_hashes();
+    let identity = |committer: bool, email: bool| {
+        let identity = match committer {
+            true => commit.get_committer(),
+            false => commit.get_author(),
+        };
+        identity
+            .map(|identity| match email {
+                true => identity.get_email().to_string(),
+                false => identity.get_name().to_string(),
+            })
+            .unwrap_or_default()
+    };
 
-            let added = added_files(new_files, old_files);
-            let modified = modified_files(new_files, old_files);
-            let deleted = deleted_files(new_files, old_files);
+    let mut ans = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('%') {
+        ans.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let (value, len) = match rest.get(1..3).unwrap_or_default() {
+            "an" => (identity(false, false), 3),
+            "ae" => (identity(false, true), 3),
+            "cn" => (identity(true, false), 3),
+            "ce" => (identity(true, true), 3),
+            "ad" => (commit.get_date().format("%c %z").to_string(), 3),
+            "as" => (commit.get_date().format("%Y-%m-%d").to_string(), 3),
+            _ => match rest.get(1..2).unwrap_or_default() {
+                "H" => (hash.clone(), 2),
+                "h" => (short_hash(&hash).to_string(), 2),
+                "P" => (parents.join(" "), 2),
+                "p" => (
+                    parents
+                        .iter()
+                        .map(|parent| short_hash(parent))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    2,
+                ),
+                "s" => (commit.get_msg().to_string(), 2),
+                "d" => (decoration(refs), 2),
+                "n" => ("\n".to_string(), 2),
+                "%" => ("%".to_string(), 2),
+                _ => ("%".to_string(), 1),
+            },
+        };
+        ans.push_str(&value);
+        rest = &rest[len..];
+    }
+    ans.push_str(rest);
+    ans
+}
+
+/// Returns the files changed from the old tree to the new one, the contents are compared by their hashes.
+fn tree_changes(new_tree: &FlatTree, old_tree: &FlatTree) -> Changes {
+    let files = |tree: &FlatTree| -> BTreeMap<PathBuf, String> {
+        tree.iter()
+            .filter(|(_, (mode, _))| *mode != EntryMode::Directory)
+            .map(|(path, (_, hash))| (path.clone(), hash.clone()))
+            .collect()
+    };
+    let new_files = files(new_tree);
+    let old_files = files(old_tree);
+
+    let mut added = vec![];
+    let mut modified = vec![];
+    for (path, hash) in new_files.iter() {
+        match old_files.get(path) {
+            None => added.push(path.clone()),
+            Some(old_hash) if old_hash != hash => modified.push(path.clone()),
+            Some(_) => {}
+        }
+    }
+    let deleted = old_files
+        .keys()
+        .filter(|path| !new_files.contains_key(*path))
+        .cloned()
+        .collect();
+    Changes::init(added, modified, deleted)
+}
+
+/// Returns the files changed by the commit since its first parent,
+/// none for the first commit of a branch.
+fn commit_changes(vcs: &VcsRepository, commit: &Commit) -> Result<Option<Changes>, VcsError> {
+    if commit.is_initial() {
+        return Ok(None);
+    }
+    let parent = vcs.get_commit_by_hash(commit.get_parent_hash()?)?;
 
-            if added.is_empty() && modified.is_empty() && deleted.is_empty() {
-                println!("  No changes");
-            } else {
-                println!("Changes: ");
-                print_changed_paths(&added, &modified, &deleted, vcs.get_dir());
+    let tree = flatten_tree(vcs.get_dir(), commit.get_tree_hash())?;
+    let parent_tree = flatten_tree(vcs.get_dir(), parent.get_tree_hash())?;
+    Ok(Some(tree_changes(&tree, &parent_tree)))
+}
+
+/// Returns the full output of the commit: the hash with the references, the identities,
+/// the date, the message and the files changed since the first parent.
+fn full_text(commit: &Commit, refs: &[String], changes: Option<&Changes>) -> String {
+    let mut ans = format!("commit {}{}\n", calculate_hash(commit), decoration(refs));
+    if commit.is_merge() {
+        ans.push_str(&format!(
+            "Merge: {}\n",
+            commit.get_parent_hashes().join(" ")
+        ));
+    }
+    if let Some(author) = commit.get_author() {
+        ans.push_str(&format!("Author: {}\n", author));
+    }
+    if let Some(committer) = commit
+        .get_committer()
+        .filter(|c| Some(*c) != commit.get_author())
+    {
+        ans.push_str(&format!("Committer: {}\n", committer));
+    }
+    ans.push_str(&format!("Date: {}\n", commit.get_date().format("%c %z")));
+    ans.push_str(&format!("Message: {}\n", commit.get_msg()));
+
+    match changes {
+        None => ans.push_str(" No changes\n"),
+        Some(changes) if changes.is_empty() => ans.push_str("  No changes\n"),
+        Some(changes) => {
+            ans.push_str("Changes: \n");
+            ans.push_str(&format_changed_paths(
+                changes.get_added(),
+                changes.get_modified(),
+                changes.get_deleted(),
+            ));
+        }
+    }
+    ans
+}
+
+/// Orders the commits so that every commit goes before its parents, the newest commits go first
+/// otherwise.
+fn sort_topologically(commits: Vec<&Commit>) -> Vec<&Commit> {
+    let hashes: Vec<String> = commits
+        .iter()
+        .map(|commit| calculate_hash(commit))
+        .collect();
+    let positions: HashMap<&str, usize> = hashes
+        .iter()
+        .enumerate()
+        .map(|(ind, hash)| (hash.as_str(), ind))
+        .collect();
+    let mut children = vec![0; commits.len()];
+    for commit in commits.iter() {
+        for parent in commit.get_parent_hashes() {
+            if let Some(&ind) = positions.get(parent.as_str()) {
+                children[ind] += 1;
             }
+        }
+    }
 
-            commit_to_log = Some(nxt_commit);
+    let mut ready: BinaryHeap<(&DateTime<Local>, &str, usize)> = (0..commits.len())
+        .filter(|&ind| children[ind] == 0)
+        .map(|ind| (commits[ind].get_date(), hashes[ind].as_str(), ind))
+        .collect();
+    let mut ans = vec![];
+    while let Some((_, _, ind)) = ready.pop() {
+        let commit = commits[ind];
+        ans.push(commit);
+        for parent in commit.get_parent_hashes() {
+            if let Some(&ind) = positions.get(parent.as_str()) {
+                children[ind] -= 1;
+                if children[ind] == 0 {
+                    ready.push((commits[ind].get_date(), hashes[ind].as_str(), ind));
+                }
+            }
         }
     }
+    ans
+}
+
+/// Returns the entries of the tree along the given paths.
+fn entries_along<'a>(
+    tree: &'a FlatTree,
+    paths: &'a [impl AsRef<Path>],
+) -> Vec<(&'a Path, &'a str)> {
+    tree.iter()
+        .filter(|(path, _)| paths.iter().any(|prefix| path.starts_with(prefix)))
+        .map(|(path, (_, hash))| (path.as_path(), hash.as_str()))
+        .collect()
+}
+
+/// Checks whether the commit changes the files along the given paths since its first parent.
+fn changes_paths(
+    vcs: &VcsRepository,
+    commit: &Commit,
+    paths: &[impl AsRef<Path>],
+) -> Result<bool, VcsError> {
+    let tree = flatten_tree(vcs.get_dir(), commit.get_tree_hash())?;
+    let parent_tree = match commit.get_parent_hashes().first() {
+        Some(parent) => flatten_tree(
+            vcs.get_dir(),
+            vcs.get_commit_by_hash(parent)?.get_tree_hash(),
+        )?,
+        None => FlatTree::new(),
+    };
+    Ok(entries_along(&tree, paths) != entries_along(&parent_tree, paths))
+}
+
+/// Returns the commits of the log: the revision range or all commits reachable from
+/// the branches, the tags and the head, filtered by the options. Every commit goes before
+/// its parents, the newest commits go first otherwise.
+pub fn log_commits<'a>(
+    vcs: &'a VcsRepository,
+    range: &RevisionRange,
+    options: &LogOptions,
+) -> Result<Vec<&'a Commit>, VcsError> {
+    let commits = match options.is_all() {
+        false => vcs.get_range_history(range)?,
+        true => {
+            let mut tips = vec![vcs.get_current_commit()];
+            for branch in vcs.get_branches().iter() {
+                tips.push(vcs.get_branch_commit(branch)?);
+            }
+            for tag in vcs.get_tags().iter() {
+                tips.push(vcs.get_commit_by_hash(tag.get_commit_hash())?);
+            }
+            let mut hashes = HashSet::new();
+            for tip in tips {
+                hashes.extend(vcs.get_ancestors(tip)?);
+            }
+            let mut commits = vec![];
+            for hash in hashes.iter() {
+                commits.push(vcs.get_commit_by_hash(hash)?);
+            }
+            commits
+        }
+    };
+
+    let mut ans = vec![];
+    for commit in sort_topologically(commits) {
+        if options.get_max_count() == Some(ans.len()) {
+            break;
+        }
+        let date = commit.get_date();
+        if options.get_since().is_some_and(|since| date < since)
+            || options.get_until().is_some_and(|until| date > until)
+            || options
+                .get_message()
+                .is_some_and(|message| !commit.get_msg().contains(message))
+            || (!options.get_paths().is_empty()
+                && !changes_paths(vcs, commit, options.get_paths())?)
+        {
+            continue;
+        }
+        ans.push(commit);
+    }
+    Ok(ans)
+}
+
+/// Returns the rows drawing the lines from the columns to the new columns, one step per row.
+fn draw_lanes(mut lanes: Vec<(usize, usize)>) -> String {
+    let mut ans = String::new();
+    while lanes.iter().any(|(from, to)| from != to) {
+        let width = lanes
+            .iter()
+            .map(|(from, _)| 2 * from + 2)
+            .max()
+            .unwrap_or(0);
+        let mut row = vec![' '; width];
+        for (from, to) in lanes.iter_mut() {
+            match (*from).cmp(to) {
+                Ordering::Equal => row[2 * *from] = '|',
+                Ordering::Less => {
+                    row[2 * *from + 1] = '\\';
+                    *from += 1;
+                }
+                Ordering::Greater => {
+                    row[2 * *from - 1] = '/';
+                    *from -= 1;
+                }
+            }
+        }
+        ans.push_str(row.iter().collect::<String>().trim_end());
+        ans.push('\n');
+    }
+    ans
+}
+
+/// Draws the graph of the commits: every column is a line of history, "*" is the commit
+/// and "|", "/" and "\" lead to its parents. The lines of the texts are prefixed with the graph.
+/// The commits go before their parents, the parents which are not given are left out.
+fn draw_graph(commits: &[(&Commit, String)]) -> String {
+    let shown: HashSet<String> = commits
+        .iter()
+        .map(|(commit, _)| calculate_hash(commit))
+        .collect();
+    let mut ans = String::new();
+    let mut columns: Vec<String> = vec![];
+    for (commit, text) in commits.iter() {
+        let hash = calculate_hash(commit);
+        let parents: Vec<&String> = commit
+            .get_parent_hashes()
+            .iter()
+            .filter(|parent| shown.contains(*parent))
+            .collect();
+        let col = match columns.iter().position(|column| *column == hash) {
+            Some(col) => col,
+            None => {
+                columns.push(hash.clone());
+                columns.len() - 1
+            }
+        };
+
+        let row = |commit_cell: &str| {
+            (0..columns.len())
+                .map(|ind| if ind == col { commit_cell } else { "|" })
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let below = if parents.is_empty() { " " } else { "|" };
+        let mut lines: Vec<&str> = text.lines().collect();
+        if lines.is_empty() {
+            lines.push("");
+        }
+        for (ind, line) in lines.into_iter().enumerate() {
+            let graph = row(if ind == 0 { "*" } else { below });
+            ans.push_str(format!("{} {}", graph, line).trim_end());
+            ans.push('\n');
+        }
+
+        let mut next: Vec<String> = vec![];
+        for (ind, column) in columns.iter().enumerate() {
+            if ind != col {
+                if !next.contains(column) {
+                    next.push(column.clone());
+                }
+                continue;
+            }
+            for parent in parents.iter() {
+                let elsewhere = columns
+                    .iter()
+                    .enumerate()
+                    .any(|(other, column)| other != col && column == *parent);
+                if !elsewhere && !next.contains(*parent) {
+                    next.push(parent.to_string());
+                }
+            }
+        }
+        let position = |hash: &String| next.iter().position(|column| column == hash).unwrap_or(0);
+        let mut lanes = vec![];
+        for (ind, column) in columns.iter().enumerate() {
+            match ind == col {
+                true => lanes.extend(parents.iter().map(|parent| (col, position(parent)))),
+                false => lanes.push((ind, position(column))),
+            }
+        }
+        ans.push_str(&draw_lanes(lanes));
+        columns = next;
+    }
+    ans
+}
+
+/// Returns the log of the revision range, for a single revision it is a list from
+/// the repository initialization to the revision. Commits of the merged branches are listed too.
+/// The options choose the format, filter the commits and add the graph.
+pub fn log(
+    vcs: &VcsRepository,
+    range: &RevisionRange,
+    options: &LogOptions,
+) -> Result<String, VcsError> {
+    Ok(log_report(vcs, range, options)?.to_text())
+}
+
+/// Returns the entries of the commits of the log with the log itself,
+/// the entries are in the order of the log and don't depend on the format.
+pub fn log_report(
+    vcs: &VcsRepository,
+    range: &RevisionRange,
+    options: &LogOptions,
+) -> Result<LogReport, VcsError> {
+    let refs = ref_names(vcs);
+    let no_refs = vec![];
+    let mut entries = vec![];
+    let mut texts = vec![];
+    for commit in log_commits(vcs, range, options)? {
+        let refs = refs.get(&calculate_hash(commit)).unwrap_or(&no_refs);
+        let changes = commit_changes(vcs, commit)?;
+        let text = match options.get_format() {
+            LogFormat::Full => full_text(commit, refs, changes.as_ref()),
+            LogFormat::Oneline => format_commit(commit, refs, "%h%d %s\n"),
+            LogFormat::Template(template) => format!("{}\n", format_commit(commit, refs, template)),
+        };
+        entries.push(LogEntry::init(commit, refs.clone(), changes));
+        texts.push((commit, text));
+    }
 
-    Ok(())
+    let text = match options.is_graph() {
+        true => draw_graph(&texts),
+        false => texts.into_iter().map(|(_, text)| text).collect(),
+    };
+    Ok(LogReport::init(entries, text))
 }
@@ -1,72 +1,104 @@
 #![forbid(unsafe_code)]
 
+use crate::library::error::VcsError;
+use crate::library::identity::Identity;
+use crate::library::index::Index;
+use crate::library::merge_state::MergeState;
+use crate::library::report::{AbortReport, Changes, MergeReport};
 use crate::library::vcs_repository::VcsRepository;
 use crate::utils::extract_files::files_from_commit;
+use crate::utils::journal::{begin_operation, finish_operation};
 use crate::utils::operation_hash::calculate_hash;
-use crate::utils::print_files::print_changed_paths;
-use crate::utils::update_repo::update_repo;
-use crate::utils::work_with_commit_files::both_changed;
-use crate::utils::work_with_commit_files::{added_files, modified_files};
+use crate::utils::three_way_merge::merge_trees;
+use crate::utils::update_repo::{restore_tree, update_repo};
+use crate::utils::work_with_commit_files::{added_files, deleted_files, modified_files};
+use crate::utils::work_with_index::write_index;
+use crate::utils::work_with_merge_state::{check_no_merge, read_merge_state, write_merge_state};
+use crate::utils::work_with_trees::{flatten_tree, write_tree};
 
-/// Merge changes from the brunch with the given name into the master and creates commit with the result.
-pub fn merge(vcs: &mut VcsRepository, branch_name: &str) -> Result<(), &'static str> {
-    if vcs.get_current_commit() != vcs.get_last_master_commit() {
-        return Err("Current commit no master last commit");
-    }
+/// Merge changes from the brunch with the given name into the current branch and creates commit with the result,
+/// the merge commit is returned with the changed files.
+/// Files changed on both sides are merged line by line against their newest common ancestor.
+/// If some changes conflict, the merged files with conflict markers are written and staged,
+/// the merge is finished by the next commit or undone by `abort_merge`. The merged branch is kept.
+/// The merge is journaled: if it is interrupted, it is rolled back by the next command changing the repository.
+pub fn merge(vcs: &mut VcsRepository, branch_name: &str) -> Result<MergeReport, VcsError> {
+    check_no_merge(vcs.get_dir())?;
 
-    let branch = vcs.get_branch_by_name(&branch_name)?.clone();
+    let branch_commit = vcs
+        .get_branch_commit(vcs.get_branch_by_name(branch_name)?)?
+        .clone();
+    let current_branch_name = vcs.get_head_name().to_string();
+    let current_history = vcs.get_ancestors(vcs.get_current_commit())?;
+    if current_history.contains(&calculate_hash(&branch_commit)) {
+        return Err(VcsError::AlreadyMerged(branch_name.to_string()));
+    }
 
     vcs.check_no_uncommited()?;
 
-    let branch_first_commit = branch.get_first_commit().clone();
-    let commit_ancestor_hash = branch_first_commit.get_parent_hash()?;
-    let common_ancestor = vcs.get_commit_by_hash(commit_ancestor_hash)?;
+    let common_ancestor = vcs.get_merge_base(vcs.get_current_commit(), &branch_commit)?;
+
+    let root_dir = vcs.get_dir().to_path_buf();
+    let tree_ancestor = flatten_tree(&root_dir, common_ancestor.get_tree_hash())?;
+    let tree_branch = flatten_tree(&root_dir, branch_commit.get_tree_hash())?;
+    let tree_master = flatten_tree(&root_dir, vcs.get_current_commit().get_tree_hash())?;
 
-    let files_ancestor = files_from_commit(common_ancestor)?;
-    let files_branch = files_from_commit(branch.get_last_commit())?;
-    let files_master = files_from_commit(vcs.get_current_commit())?;
+    let (merged, conflicts, unresolved) = merge_trees(
+        &root_dir,
+        &tree_ancestor,
+        &tree_master,
+        &tree_branch,
+        &current_branch_name,
+        branch_name,
+    )?;
+    let merged_tree = write_tree(&root_dir, &merged)?;
 
-    let both_changed_files = both_changed(&files_ancestor, &files_master, &files_branch);
-    if !both_changed_files.is_empty() {
-        println!("Merge confilict: file has been changed both in master and branch");
-        for file in both_changed_files.iter() {
-            println!("  {}", file.display());
-        }
-        return Err("Aborting");
+    if !conflicts.is_empty() {
+        begin_operation(vcs, "merge")?;
+        restore_tree(&root_dir, &merged_tree)?;
+        write_index(&root_dir, &Index::init(merged))?;
+        let state = MergeState::init(
+            branch_name,
+            &calculate_hash(&branch_commit),
+            conflicts.clone(),
+            unresolved,
+        );
+        write_merge_state(&root_dir, &state)?;
+        finish_operation(vcs)?;
+        return Err(VcsError::MergeConflict(conflicts));
     }
 
-    let mut nxt_commit = vcs
-        .get_current_commit()
-        .merge_init(branch.get_last_commit(), &branch_name)?;
+    let nxt_commit = vcs.get_current_commit().merge_init(
+        &merged_tree,
+        &branch_commit,
+        branch_name,
+        &Identity::committer(&root_dir)?,
+    );
 
-    let old_files = files_from_commit(vcs.get_current_commit())?;
-    let new_files = files_from_commit(branch.get_last_commit())?;
+    let old_files = files_from_commit(&root_dir, vcs.get_current_commit())?;
+    let new_files = files_from_commit(&root_dir, &nxt_commit)?;
 
-    let mut added = added_files(&new_files, &old_files);
-    added.append(&mut added_files(&old_files, &new_files));
+    let added = added_files(&new_files, &old_files);
     let modified = modified_files(&new_files, &old_files);
+    let deleted = deleted_files(&new_files, &old_files);
 
-    vcs.get_mut_current_branch().add_commit(&mut nxt_commit);
-    vcs.change_current_commit(&nxt_commit);
-    vcs.add_commit(&nxt_commit);
+    begin_operation(vcs, "merge")?;
+    vcs.update_head(&nxt_commit);
 
-    println!("Successfully created merge commit:");
-    println!(
-        "[master {}] merge branch {}.",
-        calculate_hash(&nxt_commit),
-        branch_name
-    );
+    update_repo(vcs)?;
+    finish_operation(vcs)?;
 
-    if modified.is_empty() && added.is_empty() {
-        println!("No changes to be committed");
-    } else {
-        println!("  {} files modified, {} added", modified.len(), added.len(),);
-        print_changed_paths(&added, &modified, &Vec::new(), vcs.get_dir());
-    }
+    Ok(MergeReport::init(
+        vcs,
+        branch_name,
+        Changes::init(added, modified, deleted),
+    ))
+}
 
-    vcs.delete_branch(&branch_name);
+/// Undoes the merge stopped by conflicts: the files of the current commit are restored.
+pub fn abort_merge(vcs: &VcsRepository) -> Result<AbortReport, VcsError> {
+    let state = read_merge_state(vcs.get_dir())?.ok_or(VcsError::NoMergeInProgress)?;
     update_repo(vcs)?;
-    println!("Deleted {}", branch_name);
-
-    Ok(())
+    vcs.collect_garbage()?;
+    Ok(AbortReport::init(state.get_branch()))
 }
@@ -1,10 +1,22 @@
 #![forbid(unsafe_code)]
 
+pub mod add;
+pub mod config;
+pub mod create_tag;
+pub mod delete_branch;
+pub mod delete_tag;
+pub mod diff;
+pub mod gc;
 pub mod init;
 pub mod jump_to_branch;
 pub mod jump_to_commit;
+pub mod list_branches;
+pub mod list_tags;
 pub mod log;
 pub mod merge;
 pub mod new_branch;
 pub mod new_commit;
+pub mod rename_branch;
+pub mod reset;
+pub mod show_branch;
 pub mod status;
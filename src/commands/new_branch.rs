@@ -1,28 +1,33 @@
 #![forbid(unsafe_code)]
 
-use crate::library::branch::Branch;
 use crate::library::commit::Commit;
+use crate::library::error::VcsError;
+use crate::library::identity::Identity;
 use crate::library::vcs_repository::VcsRepository;
 use crate::utils::operation_hash::calculate_hash;
+use crate::utils::work_with_index::read_index;
+use crate::utils::work_with_merge_state::check_no_merge;
+use crate::utils::work_with_trees::write_tree;
 
-/// Creates a new branch from the current commit in the master.
-pub fn new_branch(vcs: &mut VcsRepository, branch_name: &str) -> Result<u64, &'static str> {
-    if vcs.get_current_branch().get_name() != "master" {
-        return Err("Current branch is not master");
-    }
-
-    if vcs.exists_branch(branch_name) {
-        return Err("Branch exists");
-    }
+/// Creates a new branch from the current commit of any branch, its first commit contains the staged changes.
+pub fn new_branch(vcs: &mut VcsRepository, branch_name: &str) -> Result<String, VcsError> {
+    check_no_merge(vcs.get_dir())?;
+    vcs.check_new_branch_name(branch_name)?;
 
     let mut msg = "Initial commit ".to_string();
     msg.push_str(branch_name);
 
-    let parent = calculate_hash(&vcs.get_current_commit());
-    let commit = Commit::init(vcs.get_dir(), &msg, Some(parent), true)?;
-    let branch = Branch::init(&commit, branch_name);
-
-    vcs.add_branch(&branch);
+    let parent = calculate_hash(vcs.get_current_commit());
+    let tree = write_tree(vcs.get_dir(), read_index(vcs)?.get_entries())?;
+    let commit = Commit::from_tree(
+        &tree,
+        &msg,
+        vec![parent.clone()],
+        true,
+        &Identity::author(vcs.get_dir())?,
+        &Identity::committer(vcs.get_dir())?,
+    );
+    vcs.add_branch(branch_name, &commit);
 
     Ok(parent)
 }
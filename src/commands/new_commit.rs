@@ -1,56 +1,96 @@
 #![forbid(unsafe_code)]
 
 use crate::library::commit::Commit;
+use crate::library::error::VcsError;
+use crate::library::identity::Identity;
+use crate::library::index::Index;
+use crate::library::merge_state::MergeState;
+use crate::library::report::{Changes, CommitReport};
+use crate::library::tree::EntryMode;
 use crate::library::vcs_repository::VcsRepository;
-use crate::utils::extract_files::{files_from_commit, files_from_dir};
+use crate::utils::extract_files::{files_from_commit, files_from_index};
+use crate::utils::journal::{begin_operation, finish_operation, COMMIT_OPERATION};
+use crate::utils::object_store::read_object;
 use crate::utils::operation_hash::calculate_hash;
-use crate::utils::print_files::print_changed_paths;
+use crate::utils::three_way_merge::has_conflict_markers;
 use crate::utils::work_with_commit_files::{added_files, deleted_files, modified_files};
+use crate::utils::work_with_index::read_index;
+use crate::utils::work_with_merge_state::{read_merge_state, remove_merge_state};
+use crate::utils::work_with_trees::write_tree;
+use std::path::{Path, PathBuf};
 
-/// Creates a new commit with the given message from the current changes 
-/// or reports that there are no changes.
-pub fn new_commit(vcs: &mut VcsRepository, msg: &str) -> Result<(), &'static str> {
-    if vcs.get_last_branch_commit() != vcs.get_current_commit() {
-        return Err("Current commit not last");
+/// Returns the conflicting paths of the merge which aren't added since the merge
+/// or whose staged files still contain conflict markers.
+fn unresolved_conflicts(
+    root_dir: &Path,
+    state: &MergeState,
+    index: &Index,
+) -> Result<Vec<PathBuf>, VcsError> {
+    let mut ans = state.get_unresolved().to_vec();
+    for path in state.get_conflicts().iter() {
+        if ans.contains(path) {
+            continue;
+        }
+        if let Some((mode, hash)) = index.get_entries().get(path) {
+            if *mode != EntryMode::Directory && has_conflict_markers(&read_object(root_dir, hash)?)
+            {
+                ans.push(path.clone());
+            }
+        }
     }
-    let repo_files = files_from_dir(vcs.get_dir())?;
-    let commit_files = files_from_commit(&vcs.get_current_commit())?;
-
-    let added = added_files(&repo_files, &commit_files);
-    let modified = modified_files(&repo_files, &commit_files);
-    let deleted = deleted_files(&repo_files, &commit_files);
+    ans.sort();
+    Ok(ans)
+}
 
-    if added.is_empty() && modified.is_empty() && deleted.is_empty() {
-        return Err("No changes");
+/// Creates a new commit with the given message from the staged changes and returns it
+/// with the changed files, or reports that nothing is staged.
+/// If a merge is in progress, the commit finishes it.
+/// If the head is detached, the commit belongs to no branch and the head moves to it.
+/// The commit is journaled: if it is interrupted, it is rolled back by the next command changing the repository.
+pub fn new_commit(vcs: &mut VcsRepository, msg: &str) -> Result<CommitReport, VcsError> {
+    let merge_state = read_merge_state(vcs.get_dir())?;
+    let index = read_index(vcs)?;
+    if let Some(state) = merge_state.as_ref() {
+        let unresolved = unresolved_conflicts(vcs.get_dir(), state, &index)?;
+        if !unresolved.is_empty() {
+            return Err(VcsError::UnresolvedConflicts(unresolved));
+        }
+    }
+    let tree = write_tree(vcs.get_dir(), index.get_entries())?;
+    if merge_state.is_none() && tree == vcs.get_current_commit().get_tree_hash() {
+        return Err(VcsError::NoChanges);
     }
 
-    let mut commit = Commit::init(
-        vcs.get_dir(),
-        msg,
-        Some(calculate_hash(&vcs.get_current_commit())),
-        false,
-    )?;
+    let index_files = files_from_index(vcs.get_dir(), &index)?;
+    let commit_files = files_from_commit(vcs.get_dir(), vcs.get_current_commit())?;
 
-    let commit_hash = calculate_hash(&commit);
-    println!(
-        "[{} {}] Work in progress",
-        vcs.get_current_branch().get_name(),
-        commit_hash
-    );
+    let added = added_files(&index_files, &commit_files);
+    let modified = modified_files(&index_files, &commit_files);
+    let deleted = deleted_files(&index_files, &commit_files);
 
-    let branch = vcs.get_mut_current_branch();
-    branch.add_commit(&mut commit);
-    vcs.change_current_commit(&commit);
-    vcs.add_commit(&commit);
-
-    println!(
-        "{} files changed, {} added, {} deleted",
-        modified.len(),
-        added.len(),
-        deleted.len()
+    let mut parents = vec![calculate_hash(vcs.get_current_commit())];
+    if let Some(state) = merge_state.as_ref() {
+        parents.push(state.get_commit().to_string());
+    }
+    let commit = Commit::from_tree(
+        &tree,
+        msg,
+        parents,
+        false,
+        &Identity::author(vcs.get_dir())?,
+        &Identity::committer(vcs.get_dir())?,
     );
 
-    print_changed_paths(&added, &modified, &deleted, vcs.get_dir());
+    begin_operation(vcs, COMMIT_OPERATION)?;
+    vcs.update_head(&commit);
+    if merge_state.is_some() {
+        remove_merge_state(vcs.get_dir())?;
+    }
+    finish_operation(vcs)?;
 
-    Ok(())
+    Ok(CommitReport::init(
+        vcs,
+        Changes::init(added, modified, deleted),
+        merge_state.map(|state| state.get_branch().to_string()),
+    ))
 }
@@ -0,0 +1,15 @@
+#![forbid(unsafe_code)]
+
+use crate::library::error::VcsError;
+use crate::library::vcs_repository::VcsRepository;
+use crate::utils::work_with_merge_state::check_no_merge;
+
+/// Renames the branch, the current branch stays current under the new name.
+pub fn rename_branch(
+    vcs: &mut VcsRepository,
+    old_name: &str,
+    new_name: &str,
+) -> Result<(), VcsError> {
+    check_no_merge(vcs.get_dir())?;
+    vcs.rename_branch(old_name, new_name)
+}
@@ -0,0 +1,31 @@
+#![forbid(unsafe_code)]
+
+use crate::library::error::VcsError;
+use crate::library::vcs_repository::VcsRepository;
+use crate::utils::work_with_index::{normalize_path, read_index, write_index};
+use crate::utils::work_with_trees::flatten_tree;
+use std::path::PathBuf;
+
+/// Unstages the changes of the files along the given paths relative to the repository root:
+/// their entries in the index are restored from the current commit. The files themselves are not changed.
+pub fn reset(vcs: &VcsRepository, paths: &[PathBuf]) -> Result<(), VcsError> {
+    let root_dir = vcs.get_dir();
+    let mut index = read_index(vcs)?;
+    let commit_tree = flatten_tree(root_dir, vcs.get_current_commit().get_tree_hash())?;
+
+    for path in paths.iter() {
+        let path = normalize_path(path)?;
+        let mut found = index.remove_entries(&path);
+        for (entry, (mode, hash)) in commit_tree.iter() {
+            if entry.starts_with(&path) {
+                index.add_entry(entry, *mode, hash);
+                found = true;
+            }
+        }
+        if !found && !path.as_os_str().is_empty() {
+            return Err(VcsError::PathNotFound(path));
+        }
+    }
+
+    write_index(root_dir, &index)
+}
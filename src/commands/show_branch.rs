@@ -0,0 +1,29 @@
+#![forbid(unsafe_code)]
+
+use crate::library::error::VcsError;
+use crate::library::report::BranchReport;
+use crate::library::vcs_repository::VcsRepository;
+
+/// Returns the branch with the given name: its last and first commits,
+/// the number of its commits and whether it is merged into another branch.
+pub fn show_branch(vcs: &VcsRepository, branch_name: &str) -> Result<BranchReport, VcsError> {
+    let branch = vcs.get_branch_by_name(branch_name)?;
+    let last = vcs.get_branch_commit(branch)?;
+    let first = vcs.get_first_commit(branch)?;
+
+    let mut count = 1;
+    let mut commit = last;
+    while commit != first {
+        commit = vcs.get_commit_by_hash(commit.get_parent_hash()?)?;
+        count += 1;
+    }
+
+    Ok(BranchReport::init(
+        vcs,
+        branch_name,
+        last,
+        first,
+        count,
+        vcs.is_merged(branch)?,
+    ))
+}
@@ -1,27 +1,32 @@
 #![forbid(unsafe_code)]
 
+use crate::library::error::VcsError;
+use crate::library::report::{Changes, HeadReport, StatusReport};
 use crate::library::vcs_repository::VcsRepository;
-use crate::utils::extract_files::{files_from_commit, files_from_dir};
-use crate::utils::print_files::print_changed_paths;
+use crate::utils::extract_files::{files_from_commit, files_from_dir, files_from_index};
 use crate::utils::work_with_commit_files::{added_files, deleted_files, modified_files};
+use crate::utils::work_with_index::read_index;
+use crate::utils::work_with_merge_state::read_merge_state;
 
-/// Displays the current status to the terminal.
-pub fn status(vcs: &VcsRepository) -> Result<(), &str> {
+/// Returns the current status: staged changes, unstaged changes and untracked files.
+pub fn status(vcs: &VcsRepository) -> Result<StatusReport, VcsError> {
     let repo_files = files_from_dir(vcs.get_dir())?;
-    let commit_files = files_from_commit(&vcs.get_current_commit())?;
+    let index_files = files_from_index(vcs.get_dir(), &read_index(vcs)?)?;
+    let commit_files = files_from_commit(vcs.get_dir(), vcs.get_current_commit())?;
 
-    let added = added_files(&repo_files, &commit_files);
-    let modified = modified_files(&repo_files, &commit_files);
-    let deleted = deleted_files(&repo_files, &commit_files);
+    let staged_added = added_files(&index_files, &commit_files);
+    let staged_modified = modified_files(&index_files, &commit_files);
+    let staged_deleted = deleted_files(&index_files, &commit_files);
 
-    println!("On branch {}", vcs.get_current_branch().get_name());
+    let untracked = added_files(&repo_files, &index_files);
+    let modified = modified_files(&repo_files, &index_files);
+    let deleted = deleted_files(&repo_files, &index_files);
 
-    if added.is_empty() && modified.is_empty() && deleted.is_empty() {
-        println!("No changes to be committed");
-    } else {
-        println!("Changes to be commited:");
-        print_changed_paths(&added, &modified, &deleted, vcs.get_dir());
-    }
-
-    Ok(())
+    Ok(StatusReport::init(
+        HeadReport::from_vcs(vcs),
+        read_merge_state(vcs.get_dir())?,
+        Changes::init(staged_added, staged_modified, staged_deleted),
+        Changes::init(vec![], modified, deleted),
+        untracked,
+    ))
 }
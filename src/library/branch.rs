@@ -1,37 +1,31 @@
 #![forbid(unsafe_code)]
 
-use super::commit::Commit;
-use serde::Deserialize;
-use serde::Serialize;
+use super::error::VcsError;
+use crate::utils::work_with_refs::check_ref_name;
 
 ///
-/// This is a struct for working with branches.
-#[derive(Clone, Serialize, Deserialize)]
-#[derive(PartialEq)]
-#[derive(Debug)]
+/// This is a struct for working with branches: a named pointer to the last commit of the branch.
+#[derive(Clone, PartialEq, Debug)]
 pub struct Branch {
-    first_commit: Commit,
-    last_commit: Commit,
     name: String,
+    commit: String,
 }
 
 impl Branch {
-    /// Creates master branch.
-    pub fn init_master(commit: &Commit) -> Self {
+    /// Creates branch with given name pointing to the commit with given hash.
+    pub fn init(name: &str, commit_hash: &str) -> Self {
         Self {
-            first_commit: commit.clone(),
-            last_commit: commit.clone(),
-            name: "master".to_string(),
+            name: name.to_string(),
+            commit: commit_hash.to_string(),
         }
     }
 
-    /// Creates branch with given first commit and name.
-    pub fn init(commit: &Commit, name: &str) -> Self {
-        Self {
-            first_commit: commit.clone(),
-            last_commit: commit.clone(),
+    /// Checks that the given name can be used for a branch and for the path of its reference.
+    pub fn check_name(name: &str) -> Result<(), VcsError> {
+        check_ref_name(name).map_err(|reason| VcsError::InvalidBranchName {
             name: name.to_string(),
-        }
+            reason: reason.to_string(),
+        })
     }
 
     /// Returns the name of the branch.
@@ -39,19 +33,18 @@ impl Branch {
         self.name.as_str()
     }
 
-    /// Returns the reference to the last commit of the branch.
-    pub fn get_last_commit(&self) -> &Commit {
-        &self.last_commit
+    /// Returns the hash of the last commit of the branch.
+    pub fn get_commit_hash(&self) -> &str {
+        self.commit.as_str()
     }
 
-
-    /// Returns the reference to the first commit of the branch.
-    pub fn get_first_commit(&self) -> &Commit {
-        &self.first_commit
+    /// Changes the name of the branch.
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
     }
 
-    /// Adds given commit to the end of the branch.
-    pub fn add_commit(&mut self, commit: &Commit) {
-        self.last_commit = commit.clone();
+    /// Moves the branch to the commit with given hash.
+    pub fn set_commit_hash(&mut self, commit_hash: &str) {
+        self.commit = commit_hash.to_string();
     }
 }
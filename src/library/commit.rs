@@ -2,116 +2,129 @@
 
 extern crate array_tool;
 
-use crate::utils::extract_files::{files_from_commit, files_from_dir};
+use super::error::VcsError;
+use super::identity::Identity;
 use crate::utils::operation_hash::calculate_hash;
-use crate::utils::pack_files;
-use crate::utils::work_with_commit_files::{added_files_with_data, modified_files_with_data};
+use crate::utils::work_with_trees::write_tree_from_dir;
 use chrono::prelude::*;
 use serde::Deserialize;
 use serde::Serialize;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
 ///
 /// This is a struct for working with commits.
-#[derive(Hash, Clone, PartialEq, Serialize, Deserialize)]
-#[derive(Debug)]
+/// Commits created before the identities were recorded have no author and committer.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
 pub struct Commit {
-    parent: Option<u64>,
+    tree: String,
+    parents: Vec<String>,
     message: String,
     is_first_in_branch: bool,
-    root_path: PathBuf,
     date: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    author: Option<Identity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    committer: Option<Identity>,
 }
 
 impl Commit {
     /// Creates new commit.
-    /// 
+    ///
     /// # Arguments
     /// * `message` - The message of new commit
-    /// * `parent` - The hash of parent commit in vcs-tree and None if commit - is the first master commit
+    /// * `parent` - The hash of parent commit in vcs-tree and None if commit - is the first commit of the repository
     /// * `path` - The path to repository files
     /// * `is_first` - The flag indicating that the commit is the first in the his branch.
+    ///
+    /// The author and the committer are taken from the configuration, see `Identity::author`.
     pub fn init(
         path: &Path,
         msg: &str,
-        parent: Option<u64>,
+        parent: Option<String>,
         is_first: bool,
-    ) -> Result<Self, &'static str> {
-        let res = Self {
+    ) -> Result<Self, VcsError> {
+        Ok(Self::from_tree(
+            &write_tree_from_dir(path, path)?,
+            msg,
+            parent.into_iter().collect(),
+            is_first,
+            &Identity::author(path)?,
+            &Identity::committer(path)?,
+        ))
+    }
+
+    /// Creates new commit with the tree which is already in the object store.
+    ///
+    /// # Arguments
+    /// * `tree` - The hash of the tree of new commit
+    /// * `message` - The message of new commit
+    /// * `parents` - The hashes of parent commits, the first one is the previous commit of the branch
+    /// * `is_first` - The flag indicating that the commit is the first in the his branch.
+    /// * `author` - The one who made the changes
+    /// * `committer` - The one who created the commit
+    pub fn from_tree(
+        tree: &str,
+        msg: &str,
+        parents: Vec<String>,
+        is_first: bool,
+        author: &Identity,
+        committer: &Identity,
+    ) -> Self {
+        Self {
+            tree: tree.to_string(),
             message: msg.to_string(),
-            parent,
+            parents,
             is_first_in_branch: is_first,
-            root_path: path.to_path_buf(),
             date: Local::now(),
-        };
-
-        pack_files::put_to_dir(
-            &res.root_path,
-            calculate_hash(&res).to_string().as_str(),
-            &files_from_dir(path)?,
-        )?;
-
-        Ok(res)
+            author: Some(author.clone()),
+            committer: Some(committer.clone()),
+        }
     }
 
     /// Create a commit-merge of two commits.
-    /// 
+    ///
     /// # Arguments
-    /// * `self` - The last commit of the master
+    /// * `self` - The last commit of the current branch
+    /// * `tree` - The hash of the merged tree
     /// * `branch_commit` - The last commit of the branch
-    /// * `branch_name` - The name of the branch that merges the master
+    /// * `branch_name` - The name of the branch merged into the current one
+    /// * `committer` - The one who merges, they are the author of the merge commit too
     pub fn merge_init(
         &self,
+        tree: &str,
         branch_commit: &Self,
         branch_name: &str,
-    ) -> Result<Self, &'static str> {
+        committer: &Identity,
+    ) -> Self {
         let mut msg = "Merge branch ".to_string();
         msg.push_str(branch_name);
 
-        let res = Self {
-            message: msg.clone(),
-            parent: Some(calculate_hash(&self)),
+        Self {
+            tree: tree.to_string(),
+            message: msg,
+            parents: vec![calculate_hash(self), calculate_hash(branch_commit)],
             is_first_in_branch: false,
-            root_path: self.root_path.clone(),
             date: Local::now(),
-        };
-
-        let mut old_files = files_from_commit(&self)?;
-        let new_files = files_from_commit(branch_commit)?;
-
-        let mut added = added_files_with_data(&new_files, &old_files);
-        let modified = modified_files_with_data(&new_files, &old_files);
-
-        old_files.append(&mut added);
-
-        for mod_file in modified.iter() {
-            for file in old_files.iter_mut() {
-                if file.get_path() == mod_file.get_path() {
-                    file.change_data(&mod_file.get_data());
-                    break;
-                }
-            }
+            author: Some(committer.clone()),
+            committer: Some(committer.clone()),
         }
-
-        pack_files::put_to_dir(
-            &res.root_path,
-            calculate_hash(&res).to_string().as_str(),
-            &old_files,
-        )?;
-
-        Ok(res)
     }
 
     //get
 
-    /// Returns parent hash of the commit.
-    pub fn get_parent_hash(&self) -> Result<u64, &'static str> {
-        match self.parent {
-            None => Err("No parent in branch_commit"),
-            Some(x) => Ok(x),
+    /// Returns the hash of the first parent of the commit: the previous commit of its branch.
+    pub fn get_parent_hash(&self) -> Result<&str, VcsError> {
+        match self.parents.first() {
+            None => Err(VcsError::NoParent(calculate_hash(self))),
+            Some(x) => Ok(x.as_str()),
         }
     }
 
+    /// Returns the hashes of all parents of the commit, merge commits have several ones.
+    pub fn get_parent_hashes(&self) -> &[String] {
+        &self.parents
+    }
+
     /// Returns message of the commit.
     pub fn get_msg(&self) -> &str {
         self.message.as_str()
@@ -122,14 +135,47 @@ impl Commit {
         &self.date
     }
 
-    /// Returns the directory where the commit files are located.
-    pub fn get_dir_commit(&self) -> PathBuf {
-        let dir = self.root_path.clone();
-        let dir = dir
-            .join(".vcs")
-            .join("objects")
-            .join(calculate_hash(&self).to_string().as_str());
-        dir
+    /// Returns the one who made the changes of the commit.
+    pub fn get_author(&self) -> Option<&Identity> {
+        self.author.as_ref()
+    }
+
+    /// Returns the one who created the commit.
+    pub fn get_committer(&self) -> Option<&Identity> {
+        self.committer.as_ref()
+    }
+
+    /// Returns the hash of the tree of the repository files at the time of the commit.
+    pub fn get_tree_hash(&self) -> &str {
+        self.tree.as_str()
+    }
+
+    /// Returns the canonical form of the commit which its hash is calculated from.
+    /// It contains the tree hash, the parent hashes in order, the mark of the first commit of a branch,
+    /// the author and the committer if they are known, the date in UTC and the message.
+    pub fn get_canonical_form(&self) -> String {
+        let mut res = format!("tree {}\n", self.tree);
+        for parent in self.parents.iter() {
+            res.push_str(&format!("parent {}\n", parent));
+        }
+        if self.is_first_in_branch {
+            res.push_str("first-in-branch\n");
+        }
+        if let Some(author) = self.author.as_ref() {
+            res.push_str(&format!("author {}\n", author));
+        }
+        if let Some(committer) = self.committer.as_ref() {
+            res.push_str(&format!("committer {}\n", committer));
+        }
+        res.push_str(&format!(
+            "date {}\n",
+            self.date
+                .with_timezone(&Utc)
+                .to_rfc3339_opts(SecondsFormat::Nanos, true)
+        ));
+        res.push('\n');
+        res.push_str(&self.message);
+        res
     }
 
     //check
@@ -138,8 +184,13 @@ impl Commit {
         self.is_first_in_branch
     }
 
-    /// Checks whether the given commit is the first in master.
+    /// Checks whether the given commit is the first commit of the repository.
     pub fn is_initial(&self) -> bool {
-        self.parent.is_none()
+        self.parents.is_empty()
+    }
+
+    /// Checks whether the given commit merges several commits.
+    pub fn is_merge(&self) -> bool {
+        self.parents.len() > 1
     }
 }
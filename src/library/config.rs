@@ -0,0 +1,194 @@
+#![forbid(unsafe_code)]
+
+use super::branch::Branch;
+use super::error::VcsError;
+use crate::utils::diff::DEFAULT_CONTEXT_LINES;
+use crate::utils::work_with_config::{read_config_file, repo_config_path, user_config_path};
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+
+/// The name of the author and the committer.
+pub const USER_NAME_KEY: &str = "user.name";
+
+/// The email of the author and the committer.
+pub const USER_EMAIL_KEY: &str = "user.email";
+
+/// The name of the first branch of the new repositories.
+pub const DEFAULT_BRANCH_KEY: &str = "init.defaultbranch";
+
+/// The file with ignore rules applied to the whole repository in addition to ".vcsignore".
+pub const EXCLUDES_FILE_KEY: &str = "core.excludesfile";
+
+/// The number of unchanged lines around the changes in the diff.
+pub const DIFF_CONTEXT_KEY: &str = "diff.context";
+
+/// Whether the output is colored: "auto", "always" or "never".
+pub const COLOR_KEY: &str = "color.ui";
+
+/// The name of the first branch if it is not configured.
+pub const DEFAULT_BRANCH: &str = "master";
+
+///
+/// This is an enum for the modes of colored output.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ColorMode {
+    /// The output is colored if it is written to a terminal.
+    Auto,
+    /// The output is always colored.
+    Always,
+    /// The output is never colored.
+    Never,
+}
+
+impl ColorMode {
+    /// Checks that the standard output should be colored in this mode.
+    pub fn is_enabled(self) -> bool {
+        match self {
+            Self::Auto => io::stdout().is_terminal(),
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+}
+
+///
+/// This is a struct for working with the configuration: the settings of the repository
+/// from ".vcs/config" take precedence over the settings of the user.
+#[derive(Clone, Default, Debug)]
+pub struct Config {
+    repo: BTreeMap<String, String>,
+    user: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// Reads the configuration of the repository in the given directory and of the user.
+    pub fn read(root_dir: &Path) -> Result<Self, VcsError> {
+        Ok(Self {
+            repo: read_config_file(&repo_config_path(root_dir))?,
+            ..Self::read_user()?
+        })
+    }
+
+    /// Reads the configuration of the user only, for commands outside of a repository.
+    pub fn read_user() -> Result<Self, VcsError> {
+        let user = match user_config_path() {
+            Some(path) => read_config_file(&path)?,
+            None => BTreeMap::new(),
+        };
+        Ok(Self {
+            repo: BTreeMap::new(),
+            user,
+        })
+    }
+
+    /// Creates the configuration from the given settings of the repository and of the user.
+    pub fn init(repo: BTreeMap<String, String>, user: BTreeMap<String, String>) -> Self {
+        Self { repo, user }
+    }
+
+    /// Checks that the key is "section.name" of letters, digits and dashes.
+    pub fn check_key(key: &str) -> Result<(), VcsError> {
+        let valid = |part: &str| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        };
+        match key.split_once('.') {
+            Some((section, name)) if valid(section) && name.split('.').all(valid) => Ok(()),
+            _ => Err(VcsError::InvalidConfigKey(key.to_string())),
+        }
+    }
+
+    /// Checks that the value can be used for the known key, other keys accept any value
+    /// written on one line: line breaks would split the "key = value" line of the file.
+    pub fn check_value(key: &str, value: &str) -> Result<(), VcsError> {
+        let invalid = || VcsError::InvalidConfigValue {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        if value.contains(['\n', '\r']) {
+            return Err(invalid());
+        }
+        match key {
+            DEFAULT_BRANCH_KEY => Branch::check_name(value),
+            DIFF_CONTEXT_KEY => value.parse::<usize>().map(|_| ()).map_err(|_| invalid()),
+            COLOR_KEY => parse_color(value).map(|_| ()).ok_or_else(invalid),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the value of the key, the keys are case insensitive.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let key = key.to_lowercase();
+        self.repo
+            .get(&key)
+            .or_else(|| self.user.get(&key))
+            .map(String::as_str)
+    }
+
+    /// Returns all settings sorted by key, the settings of the repository override the user ones.
+    pub fn get_entries(&self) -> BTreeMap<&str, &str> {
+        let mut ans = BTreeMap::new();
+        for (key, value) in self.user.iter().chain(self.repo.iter()) {
+            ans.insert(key.as_str(), value.as_str());
+        }
+        ans
+    }
+
+    /// Returns the configured name of the user.
+    pub fn get_user_name(&self) -> Option<&str> {
+        self.get(USER_NAME_KEY)
+    }
+
+    /// Returns the configured email of the user.
+    pub fn get_user_email(&self) -> Option<&str> {
+        self.get(USER_EMAIL_KEY)
+    }
+
+    /// Returns the name of the first branch of the new repositories, "master" by default.
+    pub fn get_default_branch(&self) -> Result<&str, VcsError> {
+        let name = self.get(DEFAULT_BRANCH_KEY).unwrap_or(DEFAULT_BRANCH);
+        Branch::check_name(name)?;
+        Ok(name)
+    }
+
+    /// Returns the path of the additional ignore file, "~/" is the home directory.
+    pub fn get_excludes_file(&self) -> Option<PathBuf> {
+        let path = self.get(EXCLUDES_FILE_KEY)?;
+        match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+            (Some(rest), Some(home)) => Some(PathBuf::from(home).join(rest)),
+            _ => Some(PathBuf::from(path)),
+        }
+    }
+
+    /// Returns the number of unchanged lines around the changes in the diff.
+    pub fn get_diff_context(&self) -> Result<usize, VcsError> {
+        match self.get(DIFF_CONTEXT_KEY) {
+            None => Ok(DEFAULT_CONTEXT_LINES),
+            Some(value) => value.parse().map_err(|_| VcsError::InvalidConfigValue {
+                key: DIFF_CONTEXT_KEY.to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+
+    /// Returns the mode of colored output, "auto" by default.
+    pub fn get_color(&self) -> Result<ColorMode, VcsError> {
+        match self.get(COLOR_KEY) {
+            None => Ok(ColorMode::Auto),
+            Some(value) => parse_color(value).ok_or_else(|| VcsError::InvalidConfigValue {
+                key: COLOR_KEY.to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+}
+
+/// Parses the mode of colored output, boolean values are accepted too.
+fn parse_color(value: &str) -> Option<ColorMode> {
+    match value.to_lowercase().as_str() {
+        "auto" => Some(ColorMode::Auto),
+        "always" | "true" | "yes" | "on" => Some(ColorMode::Always),
+        "never" | "false" | "no" | "off" => Some(ColorMode::Never),
+        _ => None,
+    }
+}
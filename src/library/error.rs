@@ -0,0 +1,319 @@
+#![forbid(unsafe_code)]
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+///
+/// This is an enum for all errors of the library.
+#[derive(Debug)]
+pub enum VcsError {
+    /// Input or output operation with the given path failed.
+    Io { path: PathBuf, source: io::Error },
+    /// The file along the given path can't be serialized or deserialized.
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    /// The line with the given number of the configuration file is not "key = value".
+    InvalidConfig { path: PathBuf, line: usize },
+    /// The given configuration key is not "section.name".
+    InvalidConfigKey(String),
+    /// The given value can't be used for the given configuration key.
+    InvalidConfigValue { key: String, value: String },
+    /// The given configuration key is not set.
+    NoConfigValue(String),
+    /// The user configuration file can't be found because the home directory is unknown.
+    NoUserConfig,
+    /// The given path is not an existing directory.
+    NotADirectory(PathBuf),
+    /// The VCS is already initialized in the given directory.
+    AlreadyExists(PathBuf),
+    /// There is no VCS in the given directory or its parents.
+    NoRepository(PathBuf),
+    /// The repository buffer is damaged, the string describes the damage.
+    CorruptedRepository(String),
+    /// The repository buffer is written in the newer format version.
+    UnsupportedVersion(u32),
+    /// The repository buffer is written in the older format version, a command changing the repository migrates it.
+    OutdatedVersion(u32),
+    /// There is no object with the given hash in the object store.
+    NoObject(String),
+    /// There is no commit with the given hash or hash prefix.
+    NoCommit(String),
+    /// The revision expression can't be parsed for the given reason.
+    InvalidRevision { revision: String, reason: String },
+    /// The given text is not a date.
+    InvalidDate(String),
+    /// The given hash prefix matches several commits.
+    AmbiguousCommit(String),
+    /// The commit with the given hash has no parent.
+    NoParent(String),
+    /// There is no branch with the given name.
+    NoBranch(String),
+    /// The branch with the given name already exists.
+    BranchExists(String),
+    /// The given name can't be used for a branch for the given reason.
+    InvalidBranchName { name: String, reason: String },
+    /// There are changes in the directory which are not committed.
+    UncommittedFiles,
+    /// There are no changes to commit.
+    NoChanges,
+    /// The head is detached at the commit with the given hash, there is no current branch.
+    DetachedHead(String),
+    /// Leaving the detached head would lose the commits with the given hashes.
+    DetachedCommits(Vec<String>),
+    /// The branch with the given name is current and can't be deleted.
+    CurrentBranch(String),
+    /// The branch with the given name is the default branch of the repository and can't be deleted.
+    DefaultBranch(String),
+    /// The branch with the given name is not merged into other branches.
+    UnmergedBranch(String),
+    /// The last commit of the branch with the given name is already in the history of the current one.
+    AlreadyMerged(String),
+    /// There is no tag with the given name.
+    NoTag(String),
+    /// The tag with the given name already exists.
+    TagExists(String),
+    /// The given name can't be used for a tag for the given reason.
+    InvalidTagName { name: String, reason: String },
+    /// Nothing is found along the given path relative to the repository root.
+    PathNotFound(PathBuf),
+    /// The files along the given path are ignored by ".vcsignore".
+    IgnoredPath(PathBuf),
+    /// The given path is outside of the repository.
+    OutsideRepository(PathBuf),
+    /// Another command holds the lock along the given path.
+    RepositoryBusy(PathBuf),
+    /// The merge stopped with conflicts in the files along the given paths.
+    MergeConflict(Vec<PathBuf>),
+    /// A merge stopped by conflicts should be committed or aborted first.
+    MergeInProgress,
+    /// There is no merge to abort.
+    NoMergeInProgress,
+    /// The files along the given paths still contain conflict markers.
+    UnresolvedConflicts(Vec<PathBuf>),
+    /// The operation with the given name was interrupted, a command changing the repository rolls it back.
+    InterruptedOperation(String),
+}
+
+impl VcsError {
+    /// Creates an input or output error with the path it happened with.
+    pub fn io(path: &Path, source: io::Error) -> Self {
+        Self::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    /// Returns the name of the kind of the error used in the JSON output, it never changes.
+    pub fn get_kind(&self) -> &'static str {
+        match self {
+            Self::Io { .. } => "io",
+            Self::Json { .. } => "json",
+            Self::InvalidConfig { .. } => "invalid_config",
+            Self::InvalidConfigKey(_) => "invalid_config_key",
+            Self::InvalidConfigValue { .. } => "invalid_config_value",
+            Self::NoConfigValue(_) => "no_config_value",
+            Self::NoUserConfig => "no_user_config",
+            Self::NotADirectory(_) => "not_a_directory",
+            Self::AlreadyExists(_) => "already_exists",
+            Self::NoRepository(_) => "no_repository",
+            Self::CorruptedRepository(_) => "corrupted_repository",
+            Self::UnsupportedVersion(_) => "unsupported_version",
+            Self::OutdatedVersion(_) => "outdated_version",
+            Self::NoObject(_) => "no_object",
+            Self::NoCommit(_) => "no_commit",
+            Self::InvalidRevision { .. } => "invalid_revision",
+            Self::InvalidDate(_) => "invalid_date",
+            Self::AmbiguousCommit(_) => "ambiguous_commit",
+            Self::NoParent(_) => "no_parent",
+            Self::NoBranch(_) => "no_branch",
+            Self::BranchExists(_) => "branch_exists",
+            Self::InvalidBranchName { .. } => "invalid_branch_name",
+            Self::UncommittedFiles => "uncommitted_files",
+            Self::NoChanges => "no_changes",
+            Self::DetachedHead(_) => "detached_head",
+            Self::DetachedCommits(_) => "detached_commits",
+            Self::CurrentBranch(_) => "current_branch",
+            Self::DefaultBranch(_) => "default_branch",
+            Self::UnmergedBranch(_) => "unmerged_branch",
+            Self::AlreadyMerged(_) => "already_merged",
+            Self::NoTag(_) => "no_tag",
+            Self::TagExists(_) => "tag_exists",
+            Self::InvalidTagName { .. } => "invalid_tag_name",
+            Self::PathNotFound(_) => "path_not_found",
+            Self::IgnoredPath(_) => "ignored_path",
+            Self::OutsideRepository(_) => "outside_repository",
+            Self::RepositoryBusy(_) => "repository_busy",
+            Self::MergeConflict(_) => "merge_conflict",
+            Self::MergeInProgress => "merge_in_progress",
+            Self::NoMergeInProgress => "no_merge_in_progress",
+            Self::UnresolvedConflicts(_) => "unresolved_conflicts",
+            Self::InterruptedOperation(_) => "interrupted_operation",
+        }
+    }
+
+    /// Creates a serialization error with the path of the file.
+    pub fn json(path: &Path, source: serde_json::Error) -> Self {
+        Self::Json {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for VcsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "Can't access {}: {}", path.display(), source),
+            Self::Json { path, source } => write!(f, "Can't parse {}: {}", path.display(), source),
+            Self::InvalidConfig { path, line } => write!(
+                f,
+                "Bad config line {} in {}: expected key = value",
+                line,
+                path.display()
+            ),
+            Self::InvalidConfigKey(key) => {
+                write!(
+                    f,
+                    "'{}' is not a valid config key: expected section.name",
+                    key
+                )
+            }
+            Self::InvalidConfigValue { key, value } => {
+                write!(f, "'{}' is not a valid value for {}", value, key)
+            }
+            Self::NoConfigValue(key) => write!(f, "Config key {} is not set", key),
+            Self::NoUserConfig => write!(f, "Can't find the user config: set HOME or VCS_CONFIG"),
+            Self::NotADirectory(path) => write!(f, "{} isn't a existing directory", path.display()),
+            Self::AlreadyExists(path) => write!(f, "VCS already exists on path {}", path.display()),
+            Self::NoRepository(path) => write!(f, "No VCS in {} or its parents", path.display()),
+            Self::CorruptedRepository(reason) => write!(f, "Repository is corrupted: {}", reason),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "Repository format version {} is newer than supported",
+                version
+            ),
+            Self::OutdatedVersion(version) => write!(
+                f,
+                "Repository format version {} is outdated, run a command changing the repository (such as gc) to upgrade it",
+                version
+            ),
+            Self::NoObject(hash) => write!(f, "No object {} in the repository.", hash),
+            Self::NoCommit(hash) => write!(f, "No commit with hash {} exists.", hash),
+            Self::InvalidRevision { revision, reason } => {
+                write!(f, "'{}' is not a valid revision: {}.", revision, reason)
+            }
+            Self::InvalidDate(text) => write!(
+                f,
+                "'{}' is not a date: expected YYYY-MM-DD, YYYY-MM-DD HH:MM:SS or RFC 3339.",
+                text
+            ),
+            Self::AmbiguousCommit(hash) => write!(f, "Commit hash {} is ambiguous.", hash),
+            Self::NoParent(hash) => write!(f, "Commit {} has no parent.", hash),
+            Self::NoBranch(name) => write!(f, "No branch {} exists.", name),
+            Self::BranchExists(name) => write!(f, "Branch {} already exists.", name),
+            Self::InvalidBranchName { name, reason } => {
+                write!(f, "'{}' is not a valid branch name: {}.", name, reason)
+            }
+            Self::UncommittedFiles => {
+                write!(f, "Your local changes should be commited or dropped.")
+            }
+            Self::NoChanges => write!(f, "No changes to be committed"),
+            Self::DetachedHead(hash) => write!(f, "HEAD is detached at {}, not on a branch.", hash),
+            Self::DetachedCommits(hashes) => {
+                write!(
+                    f,
+                    "You are leaving {} commits not connected to any branch:",
+                    hashes.len()
+                )?;
+                for hash in hashes.iter() {
+                    write!(f, "\n  {}", hash)?;
+                }
+                write!(
+                    f,
+                    "\nCreate a branch with new_branch to keep them or use --force to leave them."
+                )
+            }
+            Self::CurrentBranch(name) => {
+                write!(
+                    f,
+                    "Branch {} is current, jump to another branch first.",
+                    name
+                )
+            }
+            Self::DefaultBranch(name) => {
+                write!(
+                    f,
+                    "Branch {} is the default branch and can't be deleted.",
+                    name
+                )
+            }
+            Self::UnmergedBranch(name) => write!(
+                f,
+                "Branch {} is not merged, use --force to delete it anyway.",
+                name
+            ),
+            Self::AlreadyMerged(name) => write!(f, "Branch {} is already merged.", name),
+            Self::NoTag(name) => write!(f, "No tag {} exists.", name),
+            Self::TagExists(name) => write!(f, "Tag {} already exists.", name),
+            Self::InvalidTagName { name, reason } => {
+                write!(f, "'{}' is not a valid tag name: {}.", name, reason)
+            }
+            Self::PathNotFound(path) => {
+                write!(f, "Path {} did not match any files.", path.display())
+            }
+            Self::IgnoredPath(path) => {
+                write!(f, "Path {} is ignored by .vcsignore.", path.display())
+            }
+            Self::OutsideRepository(path) => {
+                write!(f, "Path {} is outside of the repository.", path.display())
+            }
+            Self::RepositoryBusy(path) => write!(
+                f,
+                "Repository is busy: another vcs command holds {}. \
+                 If no command is running, remove this file.",
+                path.display()
+            ),
+            Self::MergeConflict(paths) => {
+                write!(
+                    f,
+                    "Automatic merge failed, fix conflicts and commit the result:"
+                )?;
+                for path in paths.iter() {
+                    write!(f, "\n  {}", path.display())?;
+                }
+                Ok(())
+            }
+            Self::MergeInProgress => write!(
+                f,
+                "A merge is in progress: commit the result or abort it with merge --abort."
+            ),
+            Self::NoMergeInProgress => write!(f, "There is no merge to abort."),
+            Self::UnresolvedConflicts(paths) => {
+                write!(f, "Fix conflicts and add the files before you commit:")?;
+                for path in paths.iter() {
+                    write!(f, "\n  {}", path.display())?;
+                }
+                Ok(())
+            }
+            Self::InterruptedOperation(operation) => write!(
+                f,
+                "The {} operation was interrupted, run a command changing the repository (such as gc) to roll it back.",
+                operation
+            ),
+        }
+    }
+}
+
+impl Error for VcsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Json { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,14 @@
+#![forbid(unsafe_code)]
+
+use serde::Deserialize;
+use serde::Serialize;
+
+///
+/// This is an enum for the current position of the repository.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub enum Head {
+    /// The current commit is the last commit of the branch with the given name.
+    Branch(String),
+    /// The current commit with the given hash is not followed by a branch.
+    Detached(String),
+}
@@ -0,0 +1,72 @@
+#![forbid(unsafe_code)]
+
+use super::config::Config;
+use super::error::VcsError;
+use serde::Deserialize;
+use serde::Serialize;
+use std::env;
+use std::fmt;
+use std::path::Path;
+
+///
+/// This is a struct for working with the identity of the commit author or committer.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Identity {
+    name: String,
+    email: String,
+}
+
+impl Identity {
+    /// Creates the identity with the given name and email.
+    pub fn init(name: &str, email: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            email: email.to_string(),
+        }
+    }
+
+    /// Returns the identity of the author of the changes in the repository: it is taken
+    /// from `VCS_AUTHOR_NAME` and `VCS_AUTHOR_EMAIL`, the repository configuration,
+    /// the user configuration or the name of the system user, in this order.
+    pub fn author(root_dir: &Path) -> Result<Self, VcsError> {
+        Self::resolve(root_dir, "VCS_AUTHOR_NAME", "VCS_AUTHOR_EMAIL")
+    }
+
+    /// Returns the identity of the one who commits to the repository: it is taken
+    /// from `VCS_COMMITTER_NAME` and `VCS_COMMITTER_EMAIL`, the repository configuration,
+    /// the user configuration or the name of the system user, in this order.
+    pub fn committer(root_dir: &Path) -> Result<Self, VcsError> {
+        Self::resolve(root_dir, "VCS_COMMITTER_NAME", "VCS_COMMITTER_EMAIL")
+    }
+
+    fn resolve(root_dir: &Path, name_env: &str, email_env: &str) -> Result<Self, VcsError> {
+        let config = Config::read(root_dir)?;
+        let name = env::var(name_env)
+            .ok()
+            .or_else(|| config.get_user_name().map(str::to_string))
+            .or_else(|| env::var("USER").ok())
+            .or_else(|| env::var("USERNAME").ok())
+            .unwrap_or_else(|| "unknown".to_string());
+        let email = env::var(email_env)
+            .ok()
+            .or_else(|| config.get_user_email().map(str::to_string))
+            .unwrap_or_default();
+        Ok(Self::init(&name, &email))
+    }
+
+    /// Returns the name of the person.
+    pub fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the email of the person.
+    pub fn get_email(&self) -> &str {
+        self.email.as_str()
+    }
+}
+
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} <{}>", self.name, self.email)
+    }
+}
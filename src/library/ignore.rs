@@ -0,0 +1,210 @@
+#![forbid(unsafe_code)]
+
+use super::config::Config;
+use super::error::VcsError;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Name of the files with ignore rules, every directory of the repository may contain one.
+pub const IGNORE_FILE: &str = ".vcsignore";
+
+///
+/// This is a struct for one line of the ignore file.
+#[derive(Clone, Debug)]
+struct IgnoreRule {
+    base: PathBuf,
+    segments: Vec<String>,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+}
+
+///
+/// This is a struct for the ignore rules collected from the ignore files.
+/// The patterns have gitignore syntax: `*`, `?`, `[a-z]`, `**`, leading `!` for negation,
+/// trailing `/` for directories only and `/` inside the pattern to anchor it to the ignore file.
+#[derive(Clone, Debug, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+/// Checks that the name matches the glob pattern of one path segment.
+fn match_segment(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).any(|skip| match_segment(&pattern[1..], &name[skip..])),
+        Some('?') => !name.is_empty() && match_segment(&pattern[1..], &name[1..]),
+        Some('[') => match (name.first(), pattern.iter().position(|&c| c == ']')) {
+            (Some(&c), Some(end)) if end > 1 => {
+                let mut class = &pattern[1..end];
+                let negated = matches!(class.first(), Some('!') | Some('^'));
+                if negated {
+                    class = &class[1..];
+                }
+                let mut found = false;
+                let mut ind = 0;
+                while ind < class.len() {
+                    if ind + 2 < class.len() && class[ind + 1] == '-' {
+                        found |= class[ind] <= c && c <= class[ind + 2];
+                        ind += 3;
+                    } else {
+                        found |= class[ind] == c;
+                        ind += 1;
+                    }
+                }
+                found != negated && match_segment(&pattern[end + 1..], &name[1..])
+            }
+            (Some(&c), _) => c == '[' && match_segment(&pattern[1..], &name[1..]),
+            (None, _) => false,
+        },
+        Some('\\') if pattern.len() > 1 => {
+            name.first() == Some(&pattern[1]) && match_segment(&pattern[2..], &name[1..])
+        }
+        Some(&c) => name.first() == Some(&c) && match_segment(&pattern[1..], &name[1..]),
+    }
+}
+
+/// Checks that the path segments match the pattern segments, `**` matches any number of segments.
+fn match_segments(pattern: &[String], path: &[String]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some(segment) if segment == "**" => {
+            (0..=path.len()).any(|skip| match_segments(&pattern[1..], &path[skip..]))
+        }
+        Some(segment) => {
+            !path.is_empty()
+                && match_segment(
+                    &segment.chars().collect::<Vec<_>>(),
+                    &path[0].chars().collect::<Vec<_>>(),
+                )
+                && match_segments(&pattern[1..], &path[1..])
+        }
+    }
+}
+
+impl IgnoreRule {
+    /// Parses the line of the ignore file located in the directory `base`.
+    fn parse(base: &Path, line: &str) -> Option<Self> {
+        let mut line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let negated = line.starts_with('!');
+        if negated {
+            line = &line[1..];
+        }
+        if line.starts_with("\\#") || line.starts_with("\\!") {
+            line = &line[1..];
+        }
+        let dir_only = line.ends_with('/');
+        let line = line.trim_end_matches('/');
+        let anchored = line.contains('/');
+        let segments: Vec<String> = line
+            .trim_start_matches('/')
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| segment.to_string())
+            .collect();
+        if segments.is_empty() {
+            return None;
+        }
+        Some(Self {
+            base: base.to_path_buf(),
+            segments,
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    /// Checks that the rule matches the path relative to the repository root.
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let relative = match path.strip_prefix(&self.base) {
+            Ok(relative) => relative,
+            Err(_) => return false,
+        };
+        let components: Vec<String> = relative
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect();
+        if self.anchored {
+            match_segments(&self.segments, &components)
+        } else {
+            match components.last() {
+                Some(name) => match_segments(&self.segments, std::slice::from_ref(name)),
+                None => false,
+            }
+        }
+    }
+}
+
+impl IgnoreRules {
+    /// Creates an empty set of rules.
+    pub fn init() -> Self {
+        Self::default()
+    }
+
+    /// Creates the rules of the repository in the given directory: the ones of the file
+    /// configured by "core.excludesfile", if any, apply to the whole repository.
+    /// The ignore files of the repository are added later and take precedence.
+    pub fn for_repo(root_dir: &Path) -> Result<Self, VcsError> {
+        let mut rules = Self::init();
+        let path = match Config::read(root_dir)?.get_excludes_file() {
+            Some(path) => root_dir.join(path),
+            None => return Ok(rules),
+        };
+        if path.is_file() {
+            let text = fs::read_to_string(&path).map_err(|e| VcsError::io(&path, e))?;
+            rules.add_rules(Path::new(""), &text);
+        }
+        Ok(rules)
+    }
+
+    /// Adds the rules written in the text of the ignore file located in the directory `base`,
+    /// the path is relative to the repository root.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::path::Path;
+    /// use vcs::library::ignore::IgnoreRules;
+    /// let mut rules = IgnoreRules::init();
+    /// rules.add_rules(Path::new(""), "*.log\n!keep.log\ntarget/");
+    /// assert!(rules.is_ignored(Path::new("src/debug.log"), false));
+    /// assert!(!rules.is_ignored(Path::new("keep.log"), false));
+    /// assert!(rules.is_ignored(Path::new("target"), true));
+    /// assert!(!rules.is_ignored(Path::new("target"), false));
+    /// ```
+    pub fn add_rules(&mut self, base: &Path, text: &str) {
+        self.rules.extend(
+            text.lines()
+                .filter_map(|line| IgnoreRule::parse(base, line)),
+        );
+    }
+
+    /// Adds the rules of the ignore file in the directory `dir` relative to `root_dir`, if there is one.
+    pub fn add_file(&mut self, root_dir: &Path, dir: &Path) -> Result<(), VcsError> {
+        let path = root_dir.join(dir).join(IGNORE_FILE);
+        if !path.is_file() {
+            return Ok(());
+        }
+        let text = fs::read_to_string(&path).map_err(|e| VcsError::io(&path, e))?;
+        self.add_rules(dir, &text);
+        Ok(())
+    }
+
+    /// Checks that the path relative to the repository root is ignored, the last matching rule wins.
+    /// Parent directories are not checked: the content of ignored directories is not visited.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(path, is_dir))
+            .is_some_and(|rule| !rule.negated)
+    }
+}
@@ -0,0 +1,56 @@
+#![forbid(unsafe_code)]
+
+use super::tree::{EntryMode, FlatTree};
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::Path;
+
+///
+/// This is a struct for the staging area: the content of the next commit.
+/// Entries are the files and empty directories with paths relative to the repository root.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug, Default)]
+pub struct Index {
+    entries: FlatTree,
+}
+
+impl Index {
+    /// Creates the index with the given entries.
+    pub fn init(entries: FlatTree) -> Self {
+        Self { entries }
+    }
+
+    /// Returns the entries of the index.
+    pub fn get_entries(&self) -> &FlatTree {
+        &self.entries
+    }
+
+    /// Adds the entry, empty directories containing it are no longer kept as separate entries.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::path::Path;
+    /// use vcs::library::index::Index;
+    /// use vcs::library::tree::{EntryMode, FlatTree};
+    /// let mut index = Index::init(FlatTree::new());
+    /// index.add_entry(Path::new("dir"), EntryMode::Directory, "a");
+    /// index.add_entry(Path::new("dir/file.txt"), EntryMode::File, "b");
+    /// assert_eq!(index.get_entries().len(), 1);
+    /// ```
+    pub fn add_entry(&mut self, path: &Path, mode: EntryMode, hash: &str) {
+        for ancestor in path.ancestors().skip(1) {
+            if let Some((EntryMode::Directory, _)) = self.entries.get(ancestor) {
+                self.entries.remove(ancestor);
+            }
+        }
+        self.entries
+            .insert(path.to_path_buf(), (mode, hash.to_string()));
+    }
+
+    /// Removes the entries with the given path or inside the directory with the given path.
+    /// Returns whether something was removed.
+    pub fn remove_entries(&mut self, path: &Path) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|entry, _| !entry.starts_with(path));
+        len != self.entries.len()
+    }
+}
@@ -0,0 +1,240 @@
+#![forbid(unsafe_code)]
+
+use super::error::VcsError;
+use crate::utils::atomic_write::write_atomically;
+use chrono::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// Lock which can't be read is stale after this time: its owner had time to write it.
+const UNREADABLE_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+static SHARED_LOCKS_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+///
+/// This is an enum for the ways to access the repository.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LockMode {
+    /// Readonly access, several commands may hold it at once.
+    Shared,
+    /// Access of the command which changes the repository.
+    Exclusive,
+}
+
+///
+/// This is a struct for the owner of the lock, it is written to the lock file.
+#[derive(Serialize, Deserialize)]
+struct LockOwner {
+    pid: u32,
+    date: DateTime<Local>,
+}
+
+///
+/// This is a struct for the lock of the repository, the lock is released on drop.
+#[derive(Debug)]
+pub struct RepositoryLock {
+    path: PathBuf,
+    mode: LockMode,
+}
+
+/// Returns the path of the exclusive lock.
+pub fn exclusive_lock_path(root_dir: &Path) -> PathBuf {
+    root_dir.join(".vcs").join("index.lock")
+}
+
+/// Returns the directory of the shared locks.
+pub fn shared_locks_dir(root_dir: &Path) -> PathBuf {
+    root_dir.join(".vcs").join("readers")
+}
+
+/// Returns the path of the file locked by the OS while a stale lock is replaced, it is never removed.
+fn takeover_guard_path(root_dir: &Path) -> PathBuf {
+    root_dir.join(".vcs").join("lock.guard")
+}
+
+#[cfg(target_os = "linux")]
+fn is_process_alive(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn is_process_alive(pid: u32) -> bool {
+    // "ps -p" finds the processes of other users too, unlike "kill -0".
+    process::Command::new("ps")
+        .args(["-p", &pid.to_string()])
+        .stdout(process::Stdio::null())
+        .stderr(process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(true)
+}
+
+#[cfg(windows)]
+fn is_process_alive(pid: u32) -> bool {
+    let pid = pid.to_string();
+    process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .output()
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .split_whitespace()
+                .any(|word| word == pid)
+        })
+        .unwrap_or(true)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_process_alive(_pid: u32) -> bool {
+    // The processes can't be listed, so the lock is never taken from its owner.
+    true
+}
+
+/// Checks that the lock along the given path was left by a command which is no longer running.
+/// The lock of a running command is never stale, however old it is.
+fn is_stale(path: &Path) -> bool {
+    match fs::read(path)
+        .ok()
+        .and_then(|data| serde_json::from_slice::<LockOwner>(&data).ok())
+    {
+        Some(owner) => !is_process_alive(owner.pid),
+        None => fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age > UNREADABLE_LOCK_TIMEOUT),
+    }
+}
+
+/// Runs `replace` while the guard of the repository is locked by the OS,
+/// so the commands replacing or removing the same stale lock don't race.
+/// The OS releases the guard when the command exits, so it is never stale itself.
+fn with_takeover_guard<T>(
+    root_dir: &Path,
+    replace: impl FnOnce() -> Result<T, VcsError>,
+) -> Result<T, VcsError> {
+    let path = takeover_guard_path(root_dir);
+    let guard = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(|e| VcsError::io(&path, e))?;
+    guard.lock().map_err(|e| VcsError::io(&path, e))?;
+    replace()
+}
+
+/// Creates the lock file along the given path in the repository, the stale lock is replaced
+/// by renaming the new lock over it, so the lock file never disappears meanwhile.
+fn create_lock_file(root_dir: &Path, path: &Path) -> Result<(), VcsError> {
+    let owner = LockOwner {
+        pid: process::id(),
+        date: Local::now(),
+    };
+    let data = serde_json::to_vec(&owner).map_err(|e| VcsError::json(path, e))?;
+    for _ in 0..2 {
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+        {
+            Ok(mut file) => return file.write_all(&data).map_err(|e| VcsError::io(path, e)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                // Under the guard the lock can only be removed by its live owner.
+                let replaced = with_takeover_guard(root_dir, || {
+                    if !path.exists() {
+                        return Ok(false);
+                    }
+                    if !is_stale(path) {
+                        return Err(VcsError::RepositoryBusy(path.to_path_buf()));
+                    }
+                    write_atomically(path, &data)?;
+                    Ok(true)
+                })?;
+                if replaced {
+                    return Ok(());
+                }
+            }
+            Err(e) => return Err(VcsError::io(path, e)),
+        }
+    }
+    Err(VcsError::RepositoryBusy(path.to_path_buf()))
+}
+
+/// Returns the path of the live shared lock, stale ones are removed.
+fn find_shared_lock(root_dir: &Path) -> Result<Option<PathBuf>, VcsError> {
+    let dir = shared_locks_dir(root_dir);
+    if !dir.is_dir() {
+        return Ok(None);
+    }
+    for entry in fs::read_dir(&dir).map_err(|e| VcsError::io(&dir, e))? {
+        let path = entry.map_err(|e| VcsError::io(&dir, e))?.path();
+        let removed = with_takeover_guard(root_dir, || {
+            if !is_stale(&path) {
+                return Ok(!path.exists());
+            }
+            match fs::remove_file(&path) {
+                Err(e) if e.kind() != ErrorKind::NotFound => Err(VcsError::io(&path, e)),
+                _ => Ok(true),
+            }
+        })?;
+        if !removed {
+            return Ok(Some(path));
+        }
+    }
+    Ok(None)
+}
+
+impl RepositoryLock {
+    /// Locks the repository in the given directory.
+    /// Returns `VcsError::RepositoryBusy` if the lock is held by another command.
+    pub fn acquire(root_dir: &Path, mode: LockMode) -> Result<Self, VcsError> {
+        let exclusive_path = exclusive_lock_path(root_dir);
+        match mode {
+            LockMode::Exclusive => {
+                create_lock_file(root_dir, &exclusive_path)?;
+                let lock = Self {
+                    path: exclusive_path,
+                    mode,
+                };
+                match find_shared_lock(root_dir)? {
+                    Some(path) => Err(VcsError::RepositoryBusy(path)),
+                    None => Ok(lock),
+                }
+            }
+            LockMode::Shared => {
+                let dir = shared_locks_dir(root_dir);
+                fs::create_dir_all(&dir).map_err(|e| VcsError::io(&dir, e))?;
+                let number = SHARED_LOCKS_COUNT.fetch_add(1, Ordering::SeqCst);
+                let path = dir.join(format!("{}-{}.lock", process::id(), number));
+                create_lock_file(root_dir, &path)?;
+                let lock = Self { path, mode };
+                if exclusive_path.exists() && !is_stale(&exclusive_path) {
+                    return Err(VcsError::RepositoryBusy(exclusive_path));
+                }
+                Ok(lock)
+            }
+        }
+    }
+
+    /// Returns the mode of the lock.
+    pub fn get_mode(&self) -> LockMode {
+        self.mode
+    }
+
+    /// Returns the path of the lock file.
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for RepositoryLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,141 @@
+#![forbid(unsafe_code)]
+
+use super::error::VcsError;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use std::path::PathBuf;
+
+///
+/// This is an enum for the ways to output a commit in the log.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub enum LogFormat {
+    /// The hash, the identities, the date, the message and the changed files.
+    #[default]
+    Full,
+    /// The short hash, the references and the message in one line.
+    Oneline,
+    /// The template with placeholders like "%h %an %s", see `commands::log::format_commit`.
+    Template(String),
+}
+
+///
+/// This is a struct for the options of the log: the output format, the filters of the commits
+/// and whether the history of all branches and tags is shown as a graph.
+#[derive(Clone, Default, Debug)]
+pub struct LogOptions {
+    format: LogFormat,
+    max_count: Option<usize>,
+    since: Option<DateTime<Local>>,
+    until: Option<DateTime<Local>>,
+    paths: Vec<PathBuf>,
+    message: Option<String>,
+    all: bool,
+    graph: bool,
+}
+
+impl LogOptions {
+    /// Creates the options of the full log of a revision range without filters.
+    pub fn init() -> Self {
+        Self::default()
+    }
+
+    /// Parses the date of `--since` and `--until`: RFC 3339, "YYYY-MM-DD HH:MM:SS"
+    /// or "YYYY-MM-DD" which is the start of the day, in the local time zone.
+    ///
+    /// # Examples
+    /// ```
+    /// use vcs::library::log_options::LogOptions;
+    /// let day = LogOptions::parse_date("2024-03-01").unwrap();
+    /// assert_eq!(day, LogOptions::parse_date("2024-03-01 00:00:00").unwrap());
+    /// assert!(LogOptions::parse_date("yesterday").is_err());
+    /// ```
+    pub fn parse_date(text: &str) -> Result<DateTime<Local>, VcsError> {
+        let text = text.trim();
+        if let Ok(date) = DateTime::parse_from_rfc3339(text) {
+            return Ok(date.with_timezone(&Local));
+        }
+        let naive = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").or_else(|_| {
+            NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .map(|date| date.and_hms_opt(0, 0, 0).unwrap_or_default())
+        });
+        naive
+            .ok()
+            .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+            .ok_or_else(|| VcsError::InvalidDate(text.to_string()))
+    }
+
+    /// Returns the output format of the commits.
+    pub fn get_format(&self) -> &LogFormat {
+        &self.format
+    }
+
+    /// Returns the maximal number of the output commits.
+    pub fn get_max_count(&self) -> Option<usize> {
+        self.max_count
+    }
+
+    /// Returns the date the output commits are created at or after.
+    pub fn get_since(&self) -> Option<&DateTime<Local>> {
+        self.since.as_ref()
+    }
+
+    /// Returns the date the output commits are created at or before.
+    pub fn get_until(&self) -> Option<&DateTime<Local>> {
+        self.until.as_ref()
+    }
+
+    /// Returns the paths relative to the repository root, the output commits change one of them.
+    pub fn get_paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Returns the substring of the messages of the output commits.
+    pub fn get_message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// Checks whether the commits of all branches, tags and the head are output.
+    pub fn is_all(&self) -> bool {
+        self.all
+    }
+
+    /// Checks whether the commits are output with the graph of their parents.
+    pub fn is_graph(&self) -> bool {
+        self.graph
+    }
+
+    /// Changes the output format of the commits.
+    pub fn set_format(&mut self, format: LogFormat) {
+        self.format = format;
+    }
+
+    /// Changes the maximal number of the output commits.
+    pub fn set_max_count(&mut self, max_count: Option<usize>) {
+        self.max_count = max_count;
+    }
+
+    /// Changes the dates the output commits are created between, both are included.
+    pub fn set_dates(&mut self, since: Option<DateTime<Local>>, until: Option<DateTime<Local>>) {
+        self.since = since;
+        self.until = until;
+    }
+
+    /// Changes the paths relative to the repository root, the output commits change one of them.
+    pub fn set_paths(&mut self, paths: Vec<PathBuf>) {
+        self.paths = paths;
+    }
+
+    /// Changes the substring of the messages of the output commits.
+    pub fn set_message(&mut self, message: Option<String>) {
+        self.message = message;
+    }
+
+    /// Changes whether the commits of all branches, tags and the head are output.
+    pub fn set_all(&mut self, all: bool) {
+        self.all = all;
+    }
+
+    /// Changes whether the commits are output with the graph of their parents.
+    pub fn set_graph(&mut self, graph: bool) {
+        self.graph = graph;
+    }
+}
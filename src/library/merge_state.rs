@@ -0,0 +1,60 @@
+#![forbid(unsafe_code)]
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::Path;
+use std::path::PathBuf;
+
+///
+/// This is a struct for the merge stopped by conflicts: it is finished by the next commit or aborted.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct MergeState {
+    branch: String,
+    commit: String,
+    conflicts: Vec<PathBuf>,
+    unresolved: Vec<PathBuf>,
+}
+
+impl MergeState {
+    /// Creates the state of the merge of the given branch at the commit with the given hash
+    /// with the given conflicting paths and the paths which must be added before the commit.
+    pub fn init(
+        branch: &str,
+        commit: &str,
+        conflicts: Vec<PathBuf>,
+        unresolved: Vec<PathBuf>,
+    ) -> Self {
+        Self {
+            branch: branch.to_string(),
+            commit: commit.to_string(),
+            conflicts,
+            unresolved,
+        }
+    }
+
+    /// Returns the name of the merged branch.
+    pub fn get_branch(&self) -> &str {
+        &self.branch
+    }
+
+    /// Returns the hash of the last commit of the merged branch at the time of the merge.
+    pub fn get_commit(&self) -> &str {
+        self.commit.as_str()
+    }
+
+    /// Returns the paths of the conflicts relative to the repository root.
+    pub fn get_conflicts(&self) -> &[PathBuf] {
+        &self.conflicts
+    }
+
+    /// Returns the paths of the conflicts without markers which aren't added since the merge.
+    pub fn get_unresolved(&self) -> &[PathBuf] {
+        &self.unresolved
+    }
+
+    /// Marks the unresolved paths inside the added path as resolved.
+    pub fn resolve(&mut self, path: &Path) {
+        self.unresolved
+            .retain(|unresolved| !unresolved.starts_with(path));
+    }
+}
@@ -1,4 +1,17 @@
 pub mod branch;
 pub mod commit;
+pub mod config;
+pub mod error;
 pub mod files;
+pub mod head;
+pub mod identity;
+pub mod ignore;
+pub mod index;
+pub mod lock;
+pub mod log_options;
+pub mod merge_state;
+pub mod report;
+pub mod revision;
+pub mod tag;
+pub mod tree;
 pub mod vcs_repository;
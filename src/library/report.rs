@@ -0,0 +1,950 @@
+#![forbid(unsafe_code)]
+
+use super::commit::Commit;
+use super::error::VcsError;
+use super::head::Head;
+use super::identity::Identity;
+use super::merge_state::MergeState;
+use super::tag::Tag;
+use super::vcs_repository::VcsRepository;
+use crate::utils::operation_hash::calculate_hash;
+use crate::utils::print_files::format_changed_paths;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// The result of a command: it is printed as text or serialized as the JSON document.
+/// With `--json` the command prints `{"command": ..., "ok": true, "result": ...}` with the report
+/// as the result, or `{"command": ..., "ok": false, "error": ...}` with the `ErrorReport`.
+/// The exit status is 0 if the command succeeds and 1 if it fails, with or without `--json`.
+/// The names of the fields are a stable interface, new fields may be added.
+pub trait Report: Serialize {
+    /// Returns the text printed to the terminal.
+    fn to_text(&self) -> String;
+}
+
+///
+/// This is a struct for the paths of the files changed between two states of the repository.
+#[derive(Clone, PartialEq, Serialize, Debug, Default)]
+pub struct Changes {
+    /// Paths of the added files relative to the repository root.
+    added: Vec<PathBuf>,
+    /// Paths of the modified files relative to the repository root.
+    modified: Vec<PathBuf>,
+    /// Paths of the deleted files relative to the repository root.
+    deleted: Vec<PathBuf>,
+}
+
+impl Changes {
+    /// Creates the changes from the added, modified and deleted paths.
+    pub fn init(added: Vec<PathBuf>, modified: Vec<PathBuf>, deleted: Vec<PathBuf>) -> Self {
+        Self {
+            added,
+            modified,
+            deleted,
+        }
+    }
+
+    /// Returns the paths of the added files.
+    pub fn get_added(&self) -> &[PathBuf] {
+        &self.added
+    }
+
+    /// Returns the paths of the modified files.
+    pub fn get_modified(&self) -> &[PathBuf] {
+        &self.modified
+    }
+
+    /// Returns the paths of the deleted files.
+    pub fn get_deleted(&self) -> &[PathBuf] {
+        &self.deleted
+    }
+
+    /// Checks whether no files are changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.deleted.is_empty()
+    }
+
+    /// Returns the lines "  added: path", "  modified: path" and "  deleted: path".
+    fn to_lines(&self) -> String {
+        format_changed_paths(&self.added, &self.modified, &self.deleted)
+    }
+}
+
+///
+/// This is a struct for the current position of the repository: the branch is none if the head is detached.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct HeadReport {
+    /// Name of the current branch, null if the head is detached.
+    branch: Option<String>,
+    /// Hash of the current commit.
+    commit: String,
+}
+
+impl HeadReport {
+    /// Creates the report of the current position of the VCS.
+    pub fn from_vcs(vcs: &VcsRepository) -> Self {
+        let branch = match vcs.get_head() {
+            Head::Branch(name) => Some(name.clone()),
+            Head::Detached(_) => None,
+        };
+        Self {
+            branch,
+            commit: calculate_hash(vcs.get_current_commit()),
+        }
+    }
+
+    /// Returns the current branch, none if the head is detached.
+    pub fn get_branch(&self) -> Option<&str> {
+        self.branch.as_deref()
+    }
+
+    /// Returns the hash of the current commit.
+    pub fn get_commit(&self) -> &str {
+        self.commit.as_str()
+    }
+
+    /// Returns the name of the current branch or "HEAD".
+    fn get_name(&self) -> &str {
+        self.branch.as_deref().unwrap_or("HEAD")
+    }
+}
+
+///
+/// This is a struct for the result of `init`.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct InitReport {
+    /// Path of the repository as it was given.
+    path: PathBuf,
+    /// Position of the head at the initial commit.
+    head: HeadReport,
+}
+
+impl InitReport {
+    /// Creates the report of the repository initialized along the given path.
+    pub fn init(path: &str, vcs: &VcsRepository) -> Self {
+        Self {
+            path: PathBuf::from(path),
+            head: HeadReport::from_vcs(vcs),
+        }
+    }
+}
+
+impl Report for InitReport {
+    fn to_text(&self) -> String {
+        format!(
+            "Initialized VCS repository in {}\nCreated commit:\n[{} {}] Initial commit\n",
+            self.path.display(),
+            self.head.get_name(),
+            self.head.commit
+        )
+    }
+}
+
+///
+/// This is a struct for the result of `status`: unstaged changes have no added files,
+/// they are untracked.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct StatusReport {
+    /// Current position of the repository.
+    head: HeadReport,
+    /// Merge stopped by conflicts: the merged "branch", its "commit", the "conflicts" paths
+    /// and the "unresolved" ones which must be added before the commit, null if there is none.
+    merge: Option<MergeState>,
+    /// Changes of the index against the current commit.
+    staged: Changes,
+    /// Changes of the working tree against the index.
+    unstaged: Changes,
+    /// Paths of the files which are neither staged nor ignored.
+    untracked: Vec<PathBuf>,
+}
+
+impl StatusReport {
+    /// Creates the report of the status.
+    pub fn init(
+        head: HeadReport,
+        merge: Option<MergeState>,
+        staged: Changes,
+        unstaged: Changes,
+        untracked: Vec<PathBuf>,
+    ) -> Self {
+        Self {
+            head,
+            merge,
+            staged,
+            unstaged,
+            untracked,
+        }
+    }
+
+    /// Returns the staged changes.
+    pub fn get_staged(&self) -> &Changes {
+        &self.staged
+    }
+
+    /// Returns the changes which are not staged.
+    pub fn get_unstaged(&self) -> &Changes {
+        &self.unstaged
+    }
+
+    /// Returns the paths of the untracked files.
+    pub fn get_untracked(&self) -> &[PathBuf] {
+        &self.untracked
+    }
+}
+
+impl Report for StatusReport {
+    fn to_text(&self) -> String {
+        let mut ans = match &self.head.branch {
+            Some(name) => format!("On branch {}\n", name),
+            None => format!("HEAD detached at {}\n", self.head.commit),
+        };
+        if let Some(state) = &self.merge {
+            let _ = writeln!(ans, "You are merging branch {}.", state.get_branch());
+            ans.push_str("Conflicts to be fixed, added and committed:\n");
+            for path in state.get_conflicts().iter() {
+                let note = match state.get_unresolved().contains(path) {
+                    true => " (not added)",
+                    false => "",
+                };
+                let _ = writeln!(ans, "  {}{}", path.display(), note);
+            }
+        }
+        if self.staged.is_empty() {
+            ans.push_str("No changes to be committed\n");
+        } else {
+            ans.push_str("Changes to be commited:\n");
+            ans.push_str(&self.staged.to_lines());
+        }
+        if !self.unstaged.is_empty() {
+            ans.push_str("Changes not staged for commit:\n");
+            ans.push_str(&self.unstaged.to_lines());
+        }
+        if !self.untracked.is_empty() {
+            ans.push_str("Untracked files:\n");
+            for path in self.untracked.iter() {
+                let _ = writeln!(ans, "  {}", path.display());
+            }
+        }
+        ans
+    }
+}
+
+///
+/// This is a struct for the result of `commit`: the new commit, the files it changes
+/// and the branch whose merge it finishes, if any.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct CommitReport {
+    /// Position of the head after the commit.
+    head: HeadReport,
+    /// Message of the commit.
+    message: String,
+    /// Hashes of the parents, the merged commit is the second one.
+    parents: Vec<String>,
+    /// Files changed against the first parent.
+    changes: Changes,
+    /// Branch whose merge the commit finishes, null for an ordinary commit.
+    merged_branch: Option<String>,
+}
+
+impl CommitReport {
+    /// Creates the report of the commit the head of the VCS moved to.
+    pub fn init(vcs: &VcsRepository, changes: Changes, merged_branch: Option<String>) -> Self {
+        let commit = vcs.get_current_commit();
+        Self {
+            head: HeadReport::from_vcs(vcs),
+            message: commit.get_msg().to_string(),
+            parents: commit.get_parent_hashes().to_vec(),
+            changes,
+            merged_branch,
+        }
+    }
+
+    /// Returns the position of the head after the commit.
+    pub fn get_head(&self) -> &HeadReport {
+        &self.head
+    }
+
+    /// Returns the files changed by the commit.
+    pub fn get_changes(&self) -> &Changes {
+        &self.changes
+    }
+}
+
+impl Report for CommitReport {
+    fn to_text(&self) -> String {
+        format!(
+            "[{} {}] {}\n{} files changed, {} added, {} deleted\n{}",
+            self.head.get_name(),
+            self.head.commit,
+            self.message,
+            self.changes.modified.len(),
+            self.changes.added.len(),
+            self.changes.deleted.len(),
+            self.changes.to_lines()
+        )
+    }
+}
+
+///
+/// This is a struct for the result of `merge` finished without conflicts.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct MergeReport {
+    /// Name of the merged branch.
+    branch: String,
+    /// Position of the head at the merge commit.
+    head: HeadReport,
+    /// Files changed by the merge.
+    changes: Changes,
+}
+
+impl MergeReport {
+    /// Creates the report of the merge of the branch, the head of the VCS is the merge commit.
+    pub fn init(vcs: &VcsRepository, branch: &str, changes: Changes) -> Self {
+        Self {
+            branch: branch.to_string(),
+            head: HeadReport::from_vcs(vcs),
+            changes,
+        }
+    }
+
+    /// Returns the files changed by the merge.
+    pub fn get_changes(&self) -> &Changes {
+        &self.changes
+    }
+}
+
+impl Report for MergeReport {
+    fn to_text(&self) -> String {
+        let mut ans = format!(
+            "Successfully created merge commit:\n[{} {}] merge branch {}.\n",
+            self.head.get_name(),
+            self.head.commit,
+            self.branch
+        );
+        if self.changes.is_empty() {
+            ans.push_str("No changes to be committed\n");
+        } else {
+            let _ = writeln!(
+                ans,
+                "  {} files modified, {} added, {} deleted",
+                self.changes.modified.len(),
+                self.changes.added.len(),
+                self.changes.deleted.len()
+            );
+            ans.push_str(&self.changes.to_lines());
+        }
+        ans
+    }
+}
+
+///
+/// This is a struct for one commit of the log: the changes are none for the first commit
+/// of a branch, they are counted from the first parent otherwise.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct LogEntry {
+    /// Hash of the commit.
+    hash: String,
+    /// Hashes of the parents, empty for the initial commit.
+    parents: Vec<String>,
+    /// "name" and "email" of the author, null for the commits written before the identities.
+    author: Option<Identity>,
+    /// "name" and "email" of the committer, null for the commits written before the identities.
+    committer: Option<Identity>,
+    /// Date of the commit in RFC 3339.
+    date: DateTime<Local>,
+    /// Message of the commit.
+    message: String,
+    /// References to the commit as printed by the log, like "HEAD -> master" or "tag: v1.0".
+    refs: Vec<String>,
+    /// Files changed against the first parent, null for the first commit of a branch.
+    changes: Option<Changes>,
+}
+
+impl LogEntry {
+    /// Creates the log entry of the commit with the references to it and its changes.
+    pub fn init(commit: &Commit, refs: Vec<String>, changes: Option<Changes>) -> Self {
+        Self {
+            hash: calculate_hash(commit),
+            parents: commit.get_parent_hashes().to_vec(),
+            author: commit.get_author().cloned(),
+            committer: commit.get_committer().cloned(),
+            date: *commit.get_date(),
+            message: commit.get_msg().to_string(),
+            refs,
+            changes,
+        }
+    }
+
+    /// Returns the hash of the commit.
+    pub fn get_hash(&self) -> &str {
+        self.hash.as_str()
+    }
+
+    /// Returns the names of the references to the commit.
+    pub fn get_refs(&self) -> &[String] {
+        &self.refs
+    }
+
+    /// Returns the files changed by the commit.
+    pub fn get_changes(&self) -> Option<&Changes> {
+        self.changes.as_ref()
+    }
+}
+
+///
+/// This is a struct for the result of `log`: the entries of the commits and the log printed
+/// in the chosen format.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct LogReport {
+    /// Entries of the commits in the order of the log.
+    commits: Vec<LogEntry>,
+    #[serde(skip)]
+    text: String,
+}
+
+impl LogReport {
+    /// Creates the report of the log entries and their text.
+    pub fn init(commits: Vec<LogEntry>, text: String) -> Self {
+        Self { commits, text }
+    }
+
+    /// Returns the entries of the log.
+    pub fn get_commits(&self) -> &[LogEntry] {
+        &self.commits
+    }
+}
+
+impl Report for LogReport {
+    fn to_text(&self) -> String {
+        self.text.clone()
+    }
+}
+
+///
+/// This is a struct for the result of `diff`: the unified diff of the files.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct DiffReport {
+    /// Unified diff, colored only if the colors are enabled for the terminal.
+    patch: String,
+}
+
+impl DiffReport {
+    /// Creates the report of the unified diff.
+    pub fn init(patch: String) -> Self {
+        Self { patch }
+    }
+}
+
+impl Report for DiffReport {
+    fn to_text(&self) -> String {
+        self.patch.clone()
+    }
+}
+
+///
+/// This is a struct for the result of the commands which change the index, like `add` and `reset`.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct PathsReport {
+    /// Given paths relative to the repository root.
+    paths: Vec<PathBuf>,
+}
+
+impl PathsReport {
+    /// Creates the report of the paths relative to the repository root.
+    pub fn init(paths: Vec<PathBuf>) -> Self {
+        Self { paths }
+    }
+}
+
+impl Report for PathsReport {
+    fn to_text(&self) -> String {
+        String::new()
+    }
+}
+
+///
+/// This is a struct for the result of `jump`, `to_branch` tells a jump to a branch from a jump to a commit.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct JumpReport {
+    /// Position of the head after the jump.
+    head: HeadReport,
+    /// Whether a branch was given rather than a commit.
+    to_branch: bool,
+}
+
+impl JumpReport {
+    /// Creates the report of the jump to the current position of the VCS.
+    pub fn init(vcs: &VcsRepository, to_branch: bool) -> Self {
+        Self {
+            head: HeadReport::from_vcs(vcs),
+            to_branch,
+        }
+    }
+}
+
+impl Report for JumpReport {
+    fn to_text(&self) -> String {
+        match (&self.head.branch, self.to_branch) {
+            (Some(name), true) => format!(
+                "Successfully jumped to branch {}. Current commit: {}.\n",
+                name, self.head.commit
+            ),
+            (Some(name), false) => format!(
+                "Successfully jumped to commit {}. Current branch: {}.\n",
+                self.head.commit, name
+            ),
+            (None, _) => format!(
+                "Successfully jumped to commit {}. HEAD is detached.\n{}\n",
+                self.head.commit,
+                "New commits will belong to no branch, create one with new_branch to keep them."
+            ),
+        }
+    }
+}
+
+///
+/// This is a struct for the result of `new_branch`: the branch starts from the commit of the source.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct NewBranchReport {
+    /// Name of the new branch.
+    branch: String,
+    /// Name of the branch it starts from or "HEAD".
+    source: String,
+    /// Hash of the commit it starts from.
+    source_commit: String,
+}
+
+impl NewBranchReport {
+    /// Creates the report of the branch created from the commit of the source branch or "HEAD".
+    pub fn init(branch: &str, source: &str, source_commit: &str) -> Self {
+        Self {
+            branch: branch.to_string(),
+            source: source.to_string(),
+            source_commit: source_commit.to_string(),
+        }
+    }
+}
+
+impl Report for NewBranchReport {
+    fn to_text(&self) -> String {
+        format!(
+            "Created a new branch {} from {}'s commit {}\n",
+            self.branch, self.source, self.source_commit
+        )
+    }
+}
+
+///
+/// This is a struct for one branch of the list: its last commit and whether it is current.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct BranchEntry {
+    /// Name of the branch.
+    name: String,
+    /// Hash of its last commit.
+    commit: String,
+    /// Message of its last commit.
+    message: String,
+    /// Whether the head is on the branch.
+    current: bool,
+}
+
+impl BranchEntry {
+    /// Creates the entry of the branch.
+    pub fn init(name: &str, commit: &str, message: &str, current: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            commit: commit.to_string(),
+            message: message.to_string(),
+            current,
+        }
+    }
+
+    /// Returns the name of the branch.
+    pub fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Checks whether the branch is current.
+    pub fn is_current(&self) -> bool {
+        self.current
+    }
+}
+
+///
+/// This is a struct for the result of `branch list`: the branches sorted by name
+/// and the commit of the detached head, if any.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct BranchListReport {
+    /// Hash of the commit of the detached head, null if the head is on a branch.
+    detached: Option<String>,
+    /// Branches sorted by name.
+    branches: Vec<BranchEntry>,
+}
+
+impl BranchListReport {
+    /// Creates the report of the branches.
+    pub fn init(detached: Option<String>, branches: Vec<BranchEntry>) -> Self {
+        Self { detached, branches }
+    }
+
+    /// Returns the branches sorted by name.
+    pub fn get_branches(&self) -> &[BranchEntry] {
+        &self.branches
+    }
+}
+
+impl Report for BranchListReport {
+    fn to_text(&self) -> String {
+        let mut ans = String::new();
+        if let Some(hash) = &self.detached {
+            let _ = writeln!(ans, "* (HEAD detached at {})", hash);
+        }
+        let width = self
+            .branches
+            .iter()
+            .map(|branch| branch.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        for branch in self.branches.iter() {
+            let _ = writeln!(
+                ans,
+                "{} {:width$} {} {}",
+                if branch.current { '*' } else { ' ' },
+                branch.name,
+                branch.commit,
+                branch.message,
+                width = width
+            );
+        }
+        ans
+    }
+}
+
+///
+/// This is a struct for the result of `branch show`.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct BranchReport {
+    /// Name of the branch.
+    name: String,
+    /// Whether the head is on the branch.
+    current: bool,
+    /// Hash of its last commit.
+    last_commit: String,
+    /// Date of its last commit in RFC 3339.
+    date: DateTime<Local>,
+    /// Message of its last commit.
+    message: String,
+    /// Hash of its first commit.
+    first_commit: String,
+    /// Number of its commits.
+    commits: usize,
+    /// Whether its last commit is in the history of another branch.
+    merged: bool,
+}
+
+impl BranchReport {
+    /// Creates the report of the branch from its last and first commits.
+    pub fn init(
+        vcs: &VcsRepository,
+        name: &str,
+        last: &Commit,
+        first: &Commit,
+        commits: usize,
+        merged: bool,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            current: vcs.get_head() == &Head::Branch(name.to_string()),
+            last_commit: calculate_hash(last),
+            date: *last.get_date(),
+            message: last.get_msg().to_string(),
+            first_commit: calculate_hash(first),
+            commits,
+            merged,
+        }
+    }
+
+    /// Returns the number of the commits of the branch.
+    pub fn get_commits(&self) -> usize {
+        self.commits
+    }
+}
+
+impl Report for BranchReport {
+    fn to_text(&self) -> String {
+        let mut ans = match self.current {
+            true => format!("Branch {} (current)\n", self.name),
+            false => format!("Branch {}\n", self.name),
+        };
+        let _ = writeln!(ans, "Last commit: {}", self.last_commit);
+        let _ = writeln!(ans, "Date: {}", self.date.format("%c %z"));
+        let _ = writeln!(ans, "Message: {}", self.message);
+        let _ = writeln!(ans, "First commit: {}", self.first_commit);
+        let _ = writeln!(ans, "Commits: {}", self.commits);
+        let _ = writeln!(ans, "Merged: {}", if self.merged { "yes" } else { "no" });
+        ans
+    }
+}
+
+///
+/// This is an enum for the kinds of references changed by the commands.
+#[derive(Clone, Copy, PartialEq, Serialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum RefKind {
+    /// The reference is a branch.
+    Branch,
+    /// The reference is a tag.
+    Tag,
+}
+
+///
+/// This is a struct for the result of the commands which create, rename or delete a branch or a tag:
+/// the old name is none for a new reference, the new name is none for a deleted one.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct RefReport {
+    /// "branch" or "tag".
+    kind: RefKind,
+    /// Name before the change, null for a new reference.
+    old_name: Option<String>,
+    /// Name after the change, null for a deleted reference.
+    new_name: Option<String>,
+    /// Hash of the commit the reference points to.
+    commit: String,
+}
+
+impl RefReport {
+    /// Creates the report of the reference change.
+    pub fn init(
+        kind: RefKind,
+        old_name: Option<&str>,
+        new_name: Option<&str>,
+        commit: &str,
+    ) -> Self {
+        Self {
+            kind,
+            old_name: old_name.map(str::to_string),
+            new_name: new_name.map(str::to_string),
+            commit: commit.to_string(),
+        }
+    }
+}
+
+impl Report for RefReport {
+    fn to_text(&self) -> String {
+        let kind = match self.kind {
+            RefKind::Branch => "branch",
+            RefKind::Tag => "tag",
+        };
+        match (&self.old_name, &self.new_name) {
+            (Some(old), Some(new)) => format!("Renamed {} {} to {}\n", kind, old, new),
+            (Some(old), None) => format!("Deleted {} {} (was {})\n", kind, old, self.commit),
+            (None, Some(new)) => {
+                format!("Created {} {} for commit {}\n", kind, new, self.commit)
+            }
+            (None, None) => String::new(),
+        }
+    }
+}
+
+///
+/// This is a struct for the result of `tag list`: the tags sorted by name.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct TagListReport {
+    /// Tags sorted by name: "name", "commit" and the "annotation" with "tagger", "date" and "message" or null.
+    tags: Vec<Tag>,
+}
+
+impl TagListReport {
+    /// Creates the report of the tags.
+    pub fn init(tags: Vec<Tag>) -> Self {
+        Self { tags }
+    }
+}
+
+impl Report for TagListReport {
+    fn to_text(&self) -> String {
+        let mut ans = String::new();
+        let width = self
+            .tags
+            .iter()
+            .map(|tag| tag.get_name().chars().count())
+            .max()
+            .unwrap_or(0);
+        for tag in self.tags.iter() {
+            let _ = writeln!(
+                ans,
+                "{:width$} {}",
+                tag.get_name(),
+                tag.get_commit_hash(),
+                width = width
+            );
+            if let Some(annotation) = tag.get_annotation() {
+                let _ = writeln!(ans, "  Tagger: {}", annotation.get_tagger());
+                let _ = writeln!(ans, "  Date: {}", annotation.get_date().format("%c %z"));
+                let _ = writeln!(ans, "  Message: {}", annotation.get_msg());
+            }
+        }
+        ans
+    }
+}
+
+///
+/// This is an enum for the config commands, they print the settings differently.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ConfigAction {
+    Get,
+    Change,
+    List,
+}
+
+///
+/// This is a struct for the result of the config commands: the settings which are read or changed,
+/// the value of a removed key is none.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct ConfigReport {
+    /// Values by their keys, the value of a removed key is null.
+    settings: BTreeMap<String, Option<String>>,
+    #[serde(skip)]
+    action: ConfigAction,
+}
+
+impl ConfigReport {
+    /// Creates the report of the value of the key which is read.
+    pub fn get(key: &str, value: &str) -> Self {
+        Self::init(key, Some(value), ConfigAction::Get)
+    }
+
+    /// Creates the report of the key which is set to the value or removed.
+    pub fn change(key: &str, value: Option<&str>) -> Self {
+        Self::init(key, value, ConfigAction::Change)
+    }
+
+    /// Creates the report of all settings.
+    pub fn list(settings: BTreeMap<&str, &str>) -> Self {
+        Self {
+            settings: settings
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), Some(value.to_string())))
+                .collect(),
+            action: ConfigAction::List,
+        }
+    }
+
+    fn init(key: &str, value: Option<&str>, action: ConfigAction) -> Self {
+        Self {
+            settings: BTreeMap::from([(key.to_string(), value.map(str::to_string))]),
+            action,
+        }
+    }
+
+    /// Returns the settings by their keys.
+    pub fn get_settings(&self) -> &BTreeMap<String, Option<String>> {
+        &self.settings
+    }
+}
+
+impl Report for ConfigReport {
+    fn to_text(&self) -> String {
+        let mut ans = String::new();
+        for (key, value) in self.settings.iter() {
+            match (self.action, value) {
+                (ConfigAction::List, Some(value)) => {
+                    let _ = writeln!(ans, "{} = {}", key, value);
+                }
+                (ConfigAction::Get, Some(value)) => {
+                    let _ = writeln!(ans, "{}", value);
+                }
+                _ => {}
+            }
+        }
+        ans
+    }
+}
+
+///
+/// This is a struct for the result of `gc`.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct GcReport {
+    /// Number of the removed unreachable commits.
+    removed_commits: usize,
+}
+
+impl GcReport {
+    /// Creates the report of the removed commits.
+    pub fn init(removed_commits: usize) -> Self {
+        Self { removed_commits }
+    }
+}
+
+impl Report for GcReport {
+    fn to_text(&self) -> String {
+        format!("Removed {} unreachable commits\n", self.removed_commits)
+    }
+}
+
+///
+/// This is a struct for the result of `merge --abort`: the branch whose merge is aborted.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct AbortReport {
+    /// Name of the branch whose merge is aborted.
+    branch: String,
+}
+
+impl AbortReport {
+    /// Creates the report of the aborted merge of the branch.
+    pub fn init(branch: &str) -> Self {
+        Self {
+            branch: branch.to_string(),
+        }
+    }
+}
+
+impl Report for AbortReport {
+    fn to_text(&self) -> String {
+        "Merge aborted\n".to_string()
+    }
+}
+
+///
+/// This is a struct for the error of a command in the JSON output: the kind from
+/// `VcsError::get_kind`, the message and the paths or the commits the error is about.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct ErrorReport {
+    /// Stable name of the error, see `VcsError::get_kind`.
+    kind: String,
+    /// Text of the error.
+    message: String,
+    /// Paths the error is about, omitted if there are none.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    paths: Vec<PathBuf>,
+    /// Hashes of the commits the error is about, omitted if there are none.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    commits: Vec<String>,
+}
+
+impl ErrorReport {
+    /// Creates the report of the error, `uncommitted` are the files which block the command
+    /// if the error is about them.
+    pub fn init(error: &VcsError, uncommitted: Vec<PathBuf>) -> Self {
+        let (paths, commits) = match error {
+            VcsError::MergeConflict(paths) | VcsError::UnresolvedConflicts(paths) => {
+                (paths.clone(), vec![])
+            }
+            VcsError::UncommittedFiles => (uncommitted, vec![]),
+            VcsError::PathNotFound(path)
+            | VcsError::IgnoredPath(path)
+            | VcsError::OutsideRepository(path) => (vec![path.clone()], vec![]),
+            VcsError::DetachedCommits(commits) => (vec![], commits.clone()),
+            _ => (vec![], vec![]),
+        };
+        Self {
+            kind: error.get_kind().to_string(),
+            message: error.to_string(),
+            paths,
+            commits,
+        }
+    }
+}
@@ -0,0 +1,136 @@
+#![forbid(unsafe_code)]
+
+use super::error::VcsError;
+
+/// The name of the current commit in the revisions.
+pub const HEAD: &str = "HEAD";
+
+/// The separator of the revisions in the range.
+pub const RANGE_SEPARATOR: &str = "..";
+
+///
+/// This is an enum for the steps from the commit to its ancestors.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AncestryStep {
+    /// "^n": the n-th parent of the commit, "^0" is the commit itself.
+    Parent(usize),
+    /// "~n": the n-th ancestor of the commit following the first parents.
+    Ancestor(usize),
+}
+
+///
+/// This is a struct for working with revisions: a commit named by "HEAD", a branch name,
+/// a tag name or a hash prefix, followed by steps to its ancestors like "HEAD~2" or "master^2".
+#[derive(Clone, PartialEq, Debug)]
+pub struct Revision {
+    base: String,
+    steps: Vec<AncestryStep>,
+}
+
+impl Revision {
+    /// Parses the revision expression.
+    pub fn parse(expr: &str) -> Result<Self, VcsError> {
+        let invalid = |reason: &str| {
+            Err(VcsError::InvalidRevision {
+                revision: expr.to_string(),
+                reason: reason.to_string(),
+            })
+        };
+        let base_len = expr.find(['~', '^']).unwrap_or(expr.len());
+        if base_len == 0 {
+            return invalid("the commit name is missing");
+        }
+
+        let mut steps = vec![];
+        let mut rest = &expr[base_len..];
+        while let Some(operator) = rest.chars().next() {
+            if operator != '~' && operator != '^' {
+                return invalid("only ~ and ^ can follow the commit name");
+            }
+            rest = &rest[1..];
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let count = match digits {
+                0 => 1,
+                _ => match rest[..digits].parse() {
+                    Ok(count) => count,
+                    Err(_) => return invalid("the number is too large"),
+                },
+            };
+            rest = &rest[digits..];
+            steps.push(match operator {
+                '~' => AncestryStep::Ancestor(count),
+                _ => AncestryStep::Parent(count),
+            });
+        }
+
+        Ok(Self {
+            base: expr[..base_len].to_string(),
+            steps,
+        })
+    }
+
+    /// Creates the revision of the current commit.
+    pub fn head() -> Self {
+        Self {
+            base: HEAD.to_string(),
+            steps: vec![],
+        }
+    }
+
+    /// Returns the name of the commit the steps start from.
+    pub fn get_base(&self) -> &str {
+        self.base.as_str()
+    }
+
+    /// Returns the steps from the base commit to the ancestor.
+    pub fn get_steps(&self) -> &[AncestryStep] {
+        &self.steps
+    }
+}
+
+///
+/// This is a struct for working with revision ranges: "A..B" is the commits reachable from B
+/// but not from A, omitted A or B is "HEAD". A single revision is the commits reachable from it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RevisionRange {
+    from: Option<Revision>,
+    to: Revision,
+}
+
+impl RevisionRange {
+    /// Parses the revision range or the single revision.
+    pub fn parse(expr: &str) -> Result<Self, VcsError> {
+        let (from, to) = match expr.split_once(RANGE_SEPARATOR) {
+            None => return Ok(Self::init(None, Revision::parse(expr)?)),
+            Some(parts) => parts,
+        };
+        if to.starts_with('.') {
+            return Err(VcsError::InvalidRevision {
+                revision: expr.to_string(),
+                reason: "only ranges with two dots are supported".to_string(),
+            });
+        }
+        let parse_or_head = |part: &str| match part.is_empty() {
+            true => Ok(Revision::head()),
+            false => Revision::parse(part),
+        };
+        Ok(Self::init(Some(parse_or_head(from)?), parse_or_head(to)?))
+    }
+
+    /// Creates the range of the commits reachable from `to` but not from `from`.
+    pub fn init(from: Option<Revision>, to: Revision) -> Self {
+        Self { from, to }
+    }
+
+    /// Returns the revision whose ancestors are excluded from the range.
+    pub fn get_from(&self) -> Option<&Revision> {
+        self.from.as_ref()
+    }
+
+    /// Returns the revision whose ancestors are included into the range.
+    pub fn get_to(&self) -> &Revision {
+        &self.to
+    }
+}
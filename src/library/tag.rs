@@ -0,0 +1,86 @@
+#![forbid(unsafe_code)]
+
+use super::error::VcsError;
+use crate::utils::work_with_refs::check_ref_name;
+use chrono::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
+
+///
+/// This is a struct for working with the annotation of a tag: who created it, when and why.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Annotation {
+    tagger: String,
+    date: DateTime<Local>,
+    message: String,
+}
+
+impl Annotation {
+    /// Creates the annotation with the given tagger and message dated now.
+    pub fn init(tagger: &str, message: &str) -> Self {
+        Self {
+            tagger: tagger.to_string(),
+            date: Local::now(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Returns the name of the tag creator.
+    pub fn get_tagger(&self) -> &str {
+        self.tagger.as_str()
+    }
+
+    /// Returns the date of the tag creation.
+    pub fn get_date(&self) -> &DateTime<Local> {
+        &self.date
+    }
+
+    /// Returns the message of the tag.
+    pub fn get_msg(&self) -> &str {
+        self.message.as_str()
+    }
+}
+
+///
+/// This is a struct for working with tags: a stable name of the commit.
+/// Lightweight tags are only names, annotated tags are stored in the object store with their annotation.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Tag {
+    name: String,
+    commit: String,
+    annotation: Option<Annotation>,
+}
+
+impl Tag {
+    /// Creates the tag with the given name for the commit with the given hash.
+    pub fn init(name: &str, commit_hash: &str, annotation: Option<Annotation>) -> Self {
+        Self {
+            name: name.to_string(),
+            commit: commit_hash.to_string(),
+            annotation,
+        }
+    }
+
+    /// Checks that the given name can be used for a tag and for the path of its reference.
+    pub fn check_name(name: &str) -> Result<(), VcsError> {
+        check_ref_name(name).map_err(|reason| VcsError::InvalidTagName {
+            name: name.to_string(),
+            reason: reason.to_string(),
+        })
+    }
+
+    /// Returns the name of the tag.
+    pub fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the hash of the tagged commit.
+    pub fn get_commit_hash(&self) -> &str {
+        self.commit.as_str()
+    }
+
+    /// Returns the annotation of the tag, lightweight tags have none.
+    pub fn get_annotation(&self) -> Option<&Annotation> {
+        self.annotation.as_ref()
+    }
+}
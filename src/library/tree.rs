@@ -0,0 +1,90 @@
+#![forbid(unsafe_code)]
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Flat description of a tree: relative paths of the files and empty directories
+/// with their modes and hashes.
+pub type FlatTree = BTreeMap<PathBuf, (EntryMode, String)>;
+
+///
+/// This is an enum for the kinds of tree entries.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum EntryMode {
+    File,
+    Executable,
+    Directory,
+}
+
+///
+/// This is a struct for an entry of the tree: a file or a subdirectory with its name.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct TreeEntry {
+    name: String,
+    mode: EntryMode,
+    hash: String,
+}
+
+impl TreeEntry {
+    /// Creates an entry with given name, mode and hash of the object it points to.
+    pub fn init(name: &str, mode: EntryMode, hash: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            mode,
+            hash: hash.to_string(),
+        }
+    }
+
+    /// Returns the name of the entry.
+    pub fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the mode of the entry.
+    pub fn get_mode(&self) -> EntryMode {
+        self.mode
+    }
+
+    /// Returns the hash of the blob or the tree the entry points to.
+    pub fn get_hash(&self) -> &str {
+        self.hash.as_str()
+    }
+}
+
+///
+/// This is a struct for working with trees: objects describing directories.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug, Default)]
+pub struct Tree {
+    entries: Vec<TreeEntry>,
+}
+
+impl Tree {
+    /// Creates an empty tree.
+    pub fn init() -> Self {
+        Self { entries: vec![] }
+    }
+
+    /// Adds the entry to the tree keeping entries sorted by name.
+    /// The entry with the same name is replaced.
+    pub fn add_entry(&mut self, entry: TreeEntry) {
+        match self
+            .entries
+            .binary_search_by(|other| other.name.as_str().cmp(entry.get_name()))
+        {
+            Ok(ind) => self.entries[ind] = entry,
+            Err(ind) => self.entries.insert(ind, entry),
+        }
+    }
+
+    /// Returns entries of the tree sorted by name.
+    pub fn get_entries(&self) -> &[TreeEntry] {
+        &self.entries
+    }
+
+    /// Checks whether the tree has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -2,38 +2,76 @@
 
 use super::branch::Branch;
 use super::commit::Commit;
+use super::config::DEFAULT_BRANCH;
+use super::error::VcsError;
+use super::head::Head;
+use super::revision::{AncestryStep, Revision, RevisionRange, HEAD};
+use super::tag::Tag;
 use crate::utils::delete_files::delete_commit_files;
 use crate::utils::extract_files::{files_from_commit, files_from_dir};
 use crate::utils::operation_hash::calculate_hash;
 use crate::utils::work_with_commit_files::all_changed_files;
+use crate::utils::work_with_index::{read_index, read_index_file};
+use crate::utils::work_with_refs::tag_object_hash;
+use crate::utils::work_with_trees::write_tree;
 use serde::Deserialize;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+/// The version of the format the repository buffer is written in.
+pub const FORMAT_VERSION: u32 = 5;
+
+/// The minimal length of the commit hash prefix which can be used instead of the full hash.
+pub const MIN_HASH_PREFIX_LEN: usize = 4;
 
 ///
 /// This is a struct for working with vcs repository.
+/// The commits are buffered in the metadata file, the branches and the current position
+/// are references in ".vcs/refs/heads" and ".vcs/HEAD" which are loaded by `set_refs`,
+/// the tags are references in ".vcs/refs/tags" which are loaded by `set_tags`.
 #[derive(Serialize, Deserialize)]
 pub struct VcsRepository {
-    pub branches: Vec<Branch>,
-    current_branch_id: usize,
-    all_commits: HashMap<u64, Commit>,
+    version: u32,
+    all_commits: HashMap<String, Commit>,
+    default_branch: String,
+    #[serde(skip)]
     root_dir: PathBuf,
-    current_commit: Commit,
+    #[serde(skip)]
+    branches: Vec<Branch>,
+    #[serde(skip, default = "default_head")]
+    head: Head,
+    #[serde(skip)]
+    tags: Vec<Tag>,
+}
+
+fn default_head() -> Head {
+    Head::Branch(DEFAULT_BRANCH.to_string())
+}
+
+/// Returns the name whose reference would be a directory with the reference of the given name
+/// or would contain it.
+fn find_ref_clash<'a>(name: &str, mut names: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    names.find(|other| {
+        other.starts_with(&format!("{}/", name)) || name.starts_with(&format!("{}/", other))
+    })
 }
 
 impl VcsRepository {
     /// Сreates a VCS in a directory following given path.
-    pub fn init(path: &Path) -> Result<Self, &'static str> {
+    /// The first branch with the given name becomes the default branch of the repository.
+    pub fn init(path: &Path, branch_name: &str) -> Result<Self, VcsError> {
+        Branch::check_name(branch_name)?;
         let commit = Commit::init(path, "Initial commit", None, true)?;
-        let current_branch = Branch::init_master(&commit);
+        let hash = calculate_hash(&commit);
         Ok(Self {
-            current_commit: commit.clone(),
-            current_branch_id: 0,
-            branches: vec![current_branch],
-            all_commits: HashMap::from([(calculate_hash(&commit), commit)]),
+            version: FORMAT_VERSION,
+            branches: vec![Branch::init(branch_name, &hash)],
+            head: Head::Branch(branch_name.to_string()),
+            all_commits: HashMap::from([(hash, commit)]),
+            default_branch: branch_name.to_string(),
             root_dir: path.to_path_buf(),
+            tags: vec![],
         })
     }
 
@@ -44,92 +82,306 @@ impl VcsRepository {
         &self.root_dir
     }
 
-    /// Returns the immutable refernce to VCS current branch. 
-    pub fn get_current_branch(&self) -> &Branch {
-        &self.branches[self.current_branch_id]
+    /// Returns the branches of the VCS sorted by name.
+    pub fn get_branches(&self) -> &[Branch] {
+        &self.branches
+    }
+
+    /// Returns the name of the default branch, the first branch of the repository.
+    pub fn get_default_branch(&self) -> &str {
+        self.default_branch.as_str()
+    }
+
+    /// Returns the tags of the VCS sorted by name.
+    pub fn get_tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    /// Returns the immutable reference to the tag with given name.
+    pub fn get_tag_by_name(&self, name: &str) -> Result<&Tag, VcsError> {
+        self.tags
+            .iter()
+            .find(|tag| tag.get_name() == name)
+            .ok_or_else(|| VcsError::NoTag(name.to_string()))
+    }
+
+    /// Returns the current position of the VCS.
+    pub fn get_head(&self) -> &Head {
+        &self.head
+    }
+
+    /// Returns the hash of the VCS current commit.
+    fn get_head_hash(&self) -> &str {
+        match &self.head {
+            Head::Branch(name) => self
+                .branches
+                .iter()
+                .find(|branch| branch.get_name() == name)
+                .map_or("", |branch| branch.get_commit_hash()),
+            Head::Detached(hash) => hash,
+        }
     }
 
-    /// Returns the mutable reference to the VCS curretn branch.
-    pub fn get_mut_current_branch(&mut self) -> &mut Branch {
-        &mut self.branches[self.current_branch_id]
+    /// Returns the immutable refernce to VCS current branch.
+    /// There is no current branch if the head is detached.
+    pub fn get_current_branch(&self) -> Result<&Branch, VcsError> {
+        match &self.head {
+            Head::Branch(name) => self.get_branch_by_name(name),
+            Head::Detached(hash) => Err(VcsError::DetachedHead(hash.clone())),
+        }
+    }
+
+    /// Returns the name of the current branch or "HEAD" if the head is detached.
+    pub fn get_head_name(&self) -> &str {
+        match &self.head {
+            Head::Branch(name) => name,
+            Head::Detached(_) => "HEAD",
+        }
     }
 
     /// Returns the immutable reference to the VCS current commit.
+    /// The head is checked to point to an existing commit by `check_consistency`.
     pub fn get_current_commit(&self) -> &Commit {
-        &self.current_commit
+        &self.all_commits[self.get_head_hash()]
     }
 
     /// Returns the immutable reference to the last commit of VCS current branch.
-    pub fn get_last_branch_commit(&self) -> &Commit {
-        let branch = self.get_current_branch();
-        branch.get_last_commit()
+    pub fn get_last_branch_commit(&self) -> Result<&Commit, VcsError> {
+        self.get_branch_commit(self.get_current_branch()?)
+    }
+
+    /// Returns the immutable reference to the last commit of the given branch.
+    pub fn get_branch_commit(&self, branch: &Branch) -> Result<&Commit, VcsError> {
+        self.get_commit_by_hash(branch.get_commit_hash())
     }
 
     /// Returns the immutable reference to tha branch with given name.
-    pub fn get_branch_by_name(&self, name: &str) -> Result<&Branch, &'static str> {
+    pub fn get_branch_by_name(&self, name: &str) -> Result<&Branch, VcsError> {
         for branch in self.branches.iter() {
             if branch.get_name() == name {
-                return Ok(&branch);
+                return Ok(branch);
             }
         }
-        Err("No branch with this name")
+        Err(VcsError::NoBranch(name.to_string()))
     }
 
     /// Returns the immutable reference to the commit with given hash.
-    pub fn get_commit_by_hash(&self, hash: u64) -> Result<&Commit, &'static str> {
-        match self.all_commits.get(&hash) {
-            None => Err("No commit with this hash"),
-            Some(commit) => Ok(commit),
+    /// The hash may be abbreviated to an unambiguous prefix of at least `MIN_HASH_PREFIX_LEN` symbols.
+    pub fn get_commit_by_hash(&self, hash: &str) -> Result<&Commit, VcsError> {
+        let hash = hash.to_lowercase();
+        if let Some(commit) = self.all_commits.get(&hash) {
+            return Ok(commit);
+        }
+        if hash.len() < MIN_HASH_PREFIX_LEN {
+            return Err(VcsError::NoCommit(hash));
+        }
+
+        let mut found = self
+            .all_commits
+            .iter()
+            .filter(|(commit_hash, _)| commit_hash.starts_with(&hash));
+        match (found.next(), found.next()) {
+            (None, _) => Err(VcsError::NoCommit(hash)),
+            (Some((_, commit)), None) => Ok(commit),
+            _ => Err(VcsError::AmbiguousCommit(hash)),
         }
     }
 
-    /// Returns the immutable reference to the branch with given first commit.
-    pub fn get_branch_by_first_commit(&self, commit: &Commit) -> Result<&Branch, &'static str> {
-        for branch in self.branches.iter() {
-            if branch.get_first_commit() == commit {
-                return Ok(&branch);
+    /// Returns the immutable reference to the commit with given name: "HEAD", a tag name,
+    /// a branch name or a hash prefix, they are looked for in this order.
+    pub fn get_commit_by_name(&self, name: &str) -> Result<&Commit, VcsError> {
+        if name == HEAD {
+            return Ok(self.get_current_commit());
+        }
+        if let Ok(tag) = self.get_tag_by_name(name) {
+            return self.get_commit_by_hash(tag.get_commit_hash());
+        }
+        if let Ok(branch) = self.get_branch_by_name(name) {
+            return self.get_branch_commit(branch);
+        }
+        self.get_commit_by_hash(name)
+    }
+
+    /// Returns the immutable reference to the commit of the revision.
+    pub fn resolve_revision(&self, revision: &Revision) -> Result<&Commit, VcsError> {
+        let mut commit = self.get_commit_by_name(revision.get_base())?;
+        for step in revision.get_steps().iter() {
+            let (parent, count) = match *step {
+                AncestryStep::Parent(0) | AncestryStep::Ancestor(0) => continue,
+                AncestryStep::Parent(index) => (index - 1, 1),
+                AncestryStep::Ancestor(count) => (0, count),
+            };
+            for _ in 0..count {
+                let hash = match commit.get_parent_hashes().get(parent) {
+                    Some(hash) => hash,
+                    None => return Err(VcsError::NoParent(calculate_hash(commit))),
+                };
+                commit = self.get_commit_by_hash(hash)?;
             }
         }
-        Err("No branch with given first commit")
+        Ok(commit)
+    }
+
+    /// Returns the immutable reference to the commit of the revision expression,
+    /// such as "HEAD~2", "master^2", a tag name or a hash prefix.
+    pub fn resolve_commit(&self, expr: &str) -> Result<&Commit, VcsError> {
+        self.resolve_revision(&Revision::parse(expr)?)
+    }
+
+    /// Returns the commits of the revision range, the newest commits go first.
+    pub fn get_range_history(&self, range: &RevisionRange) -> Result<Vec<&Commit>, VcsError> {
+        let excluded = match range.get_from() {
+            Some(from) => self.get_ancestors(self.resolve_revision(from)?)?,
+            None => HashSet::new(),
+        };
+        Ok(self
+            .get_history(self.resolve_revision(range.get_to())?)?
+            .into_iter()
+            .filter(|commit| !excluded.contains(&calculate_hash(commit)))
+            .collect())
+    }
+
+    /// Returns the first commit of the branch: the nearest commit marked as the first in its branch
+    /// on the way from the last commit through the first parents.
+    pub fn get_first_commit(&self, branch: &Branch) -> Result<&Commit, VcsError> {
+        let mut commit = self.get_branch_commit(branch)?;
+        while !commit.is_first() {
+            commit = self.get_commit_by_hash(commit.get_parent_hash()?)?;
+        }
+        Ok(commit)
     }
 
     /// Returns the immutable reference to the branch with given commit.
-    pub fn get_branch_by_commit(&self, commit: &Commit) -> Result<&Branch, &'static str> {
-        if commit.is_first() {
-            self.get_branch_by_first_commit(commit)
-        } else {
-            let parent_commit_hash = commit.get_parent_hash()?;
-            let parent_commit = self.get_commit_by_hash(parent_commit_hash)?;
-            self.get_branch_by_commit(parent_commit)
+    /// Commits of the merged branches belong to the first branch whose history contains them.
+    pub fn get_branch_by_commit(&self, commit: &Commit) -> Result<&Branch, VcsError> {
+        let mut first = commit;
+        while !first.is_first() {
+            first = self.get_commit_by_hash(first.get_parent_hash()?)?;
+        }
+        let hash = calculate_hash(commit);
+        for branch in self.branches.iter() {
+            if self.get_first_commit(branch)? == first
+                && self
+                    .get_ancestors(self.get_branch_commit(branch)?)?
+                    .contains(&hash)
+            {
+                return Ok(branch);
+            }
         }
+
+        for branch in self.branches.iter() {
+            if self
+                .get_ancestors(self.get_branch_commit(branch)?)?
+                .contains(&hash)
+            {
+                return Ok(branch);
+            }
+        }
+        Err(VcsError::CorruptedRepository(format!(
+            "no branch contains commit {}",
+            hash
+        )))
+    }
+
+    /// Returns the hashes of the commits which are reachable from the detached head
+    /// but not from the branches or the tags, the newest commits go first.
+    /// They are lost once the head leaves them.
+    pub fn get_detached_commits(&self) -> Result<Vec<String>, VcsError> {
+        if let Head::Branch(_) = self.head {
+            return Ok(vec![]);
+        }
+        let mut reachable = HashSet::new();
+        for branch in self.branches.iter() {
+            reachable.extend(self.get_ancestors(self.get_branch_commit(branch)?)?);
+        }
+        for tag in self.tags.iter() {
+            reachable.extend(self.get_ancestors(self.get_commit_by_hash(tag.get_commit_hash())?)?);
+        }
+        Ok(self
+            .get_history(self.get_current_commit())?
+            .into_iter()
+            .map(calculate_hash)
+            .filter(|hash| !reachable.contains(hash))
+            .collect())
     }
 
-    /// Returns the branch ID in this state VCS.
-    pub fn get_branch_id(&self, branch: &Branch) -> Result<usize, &'static str> {
-        for i in 0..self.branches.len() {
-            if self.branches[i].get_name() == branch.get_name() {
-                return Ok(i);
+    /// Returns the hashes of the given commit and all commits reachable from it through the parents.
+    pub fn get_ancestors(&self, commit: &Commit) -> Result<HashSet<String>, VcsError> {
+        let mut ans = HashSet::from([calculate_hash(commit)]);
+        let mut to_visit = vec![commit];
+        while let Some(commit) = to_visit.pop() {
+            for parent_hash in commit.get_parent_hashes() {
+                if ans.insert(parent_hash.clone()) {
+                    to_visit.push(self.get_commit_by_hash(parent_hash)?);
+                }
             }
         }
-        Err("No branch")
+        Ok(ans)
     }
 
-    /// Returns the immutable reference to the last commit of VCS master branch.
-    pub fn get_last_master_commit(&self) -> &Commit {
-        self.branches[0].get_last_commit()
+    /// Returns the given commit and all its ancestors, the newest commits go first.
+    pub fn get_history(&self, commit: &Commit) -> Result<Vec<&Commit>, VcsError> {
+        let mut ans = vec![];
+        for hash in self.get_ancestors(commit)? {
+            ans.push(self.get_commit_by_hash(&hash)?);
+        }
+        ans.sort_by(|a, b| b.get_date().cmp(a.get_date()));
+        Ok(ans)
+    }
+
+    /// Returns the newest common ancestor of two commits, the base of their merge.
+    pub fn get_merge_base(&self, commit1: &Commit, commit2: &Commit) -> Result<&Commit, VcsError> {
+        let ancestors = self.get_ancestors(commit1)?;
+        self.get_history(commit2)?
+            .into_iter()
+            .find(|commit| ancestors.contains(&calculate_hash(commit)))
+            .ok_or_else(|| {
+                VcsError::CorruptedRepository(format!(
+                    "commits {} and {} have no common ancestor",
+                    calculate_hash(commit1),
+                    calculate_hash(commit2)
+                ))
+            })
     }
 
     // change
 
-    /// Changes the current commit of the VCS.
-    pub fn change_current_commit(&mut self, commit: &Commit) {
-        self.current_commit = commit.clone();
+    /// Changes the directory of repository, it is resolved at runtime and not buffered.
+    pub fn set_dir(&mut self, path: &Path) {
+        self.root_dir = path.to_path_buf();
     }
 
-    /// Changes the current branch of the VCS.
-    pub fn change_current_branch(&mut self, branch: &Branch) -> Result<(), &'static str> {
-        self.current_branch_id = self.get_branch_id(branch)?;
-        Ok(())
+    /// Changes the branches and the current position of the VCS read from the references.
+    pub fn set_refs(&mut self, branches: Vec<Branch>, head: Head) {
+        self.branches = branches;
+        self.head = head;
+    }
+
+    /// Changes the tags of the VCS read from the references.
+    pub fn set_tags(&mut self, tags: Vec<Tag>) {
+        self.tags = tags;
+    }
+
+    /// Changes the current position of the VCS: the current commit is resolved from it.
+    pub fn change_head(&mut self, head: Head) {
+        self.head = head;
+    }
+
+    /// Moves the head to the given commit, the commit is added to the VCS.
+    /// The current branch follows the head unless it is detached.
+    pub fn update_head(&mut self, commit: &Commit) {
+        let hash = calculate_hash(commit);
+        self.add_commit(commit);
+        match &self.head {
+            Head::Branch(name) => {
+                for branch in self.branches.iter_mut() {
+                    if branch.get_name() == name {
+                        branch.set_commit_hash(&hash);
+                    }
+                }
+            }
+            Head::Detached(_) => self.head = Head::Detached(hash),
+        }
     }
 
     /// Adds commit and commit hash to the struct.
@@ -138,58 +390,217 @@ impl VcsRepository {
             .insert(calculate_hash(commit), commit.clone());
     }
 
-    /// Adds branch to the VCS.
-    pub fn add_branch(&mut self, branch: &Branch) {
-        self.branches.push(branch.clone());
-        self.current_branch_id = self.branches.len() - 1;
-        self.current_commit = branch.get_last_commit().clone();
-        self.all_commits.insert(
-            calculate_hash(&self.current_commit),
-            self.current_commit.clone(),
-        );
+    /// Adds branch with the given first commit to the VCS and makes it current.
+    pub fn add_branch(&mut self, branch_name: &str, commit: &Commit) {
+        let hash = calculate_hash(commit);
+        self.add_commit(commit);
+        self.branches.push(Branch::init(branch_name, &hash));
+        self.branches.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+        self.head = Head::Branch(branch_name.to_string());
+    }
+
+    /// Renames the branch, the head and the default branch follow it.
+    pub fn rename_branch(&mut self, old_name: &str, new_name: &str) -> Result<(), VcsError> {
+        self.get_branch_by_name(old_name)?;
+        Branch::check_name(new_name)?;
+        if self.exists_branch(new_name) {
+            return Err(VcsError::BranchExists(new_name.to_string()));
+        }
+        self.check_no_ref_clash(new_name, old_name)?;
+        for branch in self.branches.iter_mut() {
+            if branch.get_name() == old_name {
+                branch.set_name(new_name);
+            }
+        }
+        self.branches.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+        if self.head == Head::Branch(old_name.to_string()) {
+            self.head = Head::Branch(new_name.to_string());
+        }
+        if self.default_branch == old_name {
+            self.default_branch = new_name.to_string();
+        }
+        Ok(())
+    }
+
+    /// Adds the tag to the VCS, the tagged commit should exist.
+    pub fn add_tag(&mut self, tag: Tag) -> Result<(), VcsError> {
+        Tag::check_name(tag.get_name())?;
+        if self.get_tag_by_name(tag.get_name()).is_ok() {
+            return Err(VcsError::TagExists(tag.get_name().to_string()));
+        }
+        let names = self.tags.iter().map(|tag| tag.get_name());
+        if let Some(name) = find_ref_clash(tag.get_name(), names) {
+            return Err(VcsError::InvalidTagName {
+                name: tag.get_name().to_string(),
+                reason: format!("it clashes with tag {}", name),
+            });
+        }
+        self.get_commit_by_hash(tag.get_commit_hash())?;
+        self.tags.push(tag);
+        self.tags.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+        Ok(())
     }
 
+    /// Deletes the tag with the given name and returns it, the tagged commit is kept.
+    pub fn delete_tag(&mut self, name: &str) -> Result<Tag, VcsError> {
+        match self.tags.iter().position(|tag| tag.get_name() == name) {
+            Some(ind) => Ok(self.tags.remove(ind)),
+            None => Err(VcsError::NoTag(name.to_string())),
+        }
+    }
 
-    /// Deletes all commшts of given branch with their directories.
-    fn delete_all_commits(&mut self, branch: &Branch) {
-        let mut commit = branch.get_last_commit().clone();
-        while !commit.is_first() {
-            self.all_commits.remove(&calculate_hash(&commit));
-            delete_commit_files(&commit);
-            commit = self
-                .get_commit_by_hash(commit.get_parent_hash().unwrap())
-                .unwrap()
-                .clone();
+    /// Deletes the commits which can't be reached from the branches, the tags or the current commit.
+    /// Returns the number of deleted commits, their objects are kept until `collect_garbage` is called.
+    pub fn prune_commits(&mut self) -> Result<usize, VcsError> {
+        let mut reachable = self.get_ancestors(self.get_current_commit())?;
+        for branch in self.branches.iter() {
+            reachable.extend(self.get_ancestors(self.get_branch_commit(branch)?)?);
         }
-        self.all_commits.remove(&calculate_hash(&commit));
-        delete_commit_files(&commit);
+        for tag in self.tags.iter() {
+            reachable.extend(self.get_ancestors(self.get_commit_by_hash(tag.get_commit_hash())?)?);
+        }
+        let count = self.all_commits.len();
+        self.all_commits.retain(|hash, _| reachable.contains(hash));
+        Ok(count - self.all_commits.len())
     }
 
-    /// Deletes given branch.
-    pub fn delete_branch(&mut self, branch_name: &str) {
+    /// Deletes the objects which are not referenced by the commits of the VCS, by the staged changes
+    /// or by the tags.
+    pub fn collect_garbage(&self) -> Result<(), VcsError> {
+        let index = read_index_file(&self.root_dir)?;
+        let mut kept = HashSet::new();
+        for tag in self.tags.iter() {
+            kept.extend(tag_object_hash(&self.root_dir, tag)?);
+        }
+        delete_commit_files(
+            &self.root_dir,
+            self.all_commits.values(),
+            index.as_ref(),
+            kept,
+        )
+    }
+
+    /// Deletes given branch, its commits are kept until `prune_commits` is called.
+    pub fn delete_branch(&mut self, branch_name: &str) -> Result<(), VcsError> {
         for ind in 0..self.branches.len() {
             if self.branches[ind].get_name() == branch_name {
-                self.delete_all_commits(&self.branches[ind].clone());
                 self.branches.remove(ind);
-                return;
+                return Ok(());
             }
         }
+        Err(VcsError::NoBranch(branch_name.to_string()))
     }
 
     // check
 
+    /// Checks whether the last commit of the branch is in the history of another branch.
+    pub fn is_merged(&self, branch: &Branch) -> Result<bool, VcsError> {
+        let hash = branch.get_commit_hash();
+        for other in self.branches.iter() {
+            if other.get_name() != branch.get_name()
+                && self
+                    .get_ancestors(self.get_branch_commit(other)?)?
+                    .contains(hash)
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Checks that moving the head to the given commit doesn't leave detached commits behind.
+    pub fn check_no_detached_commits(&self, target: &Commit) -> Result<(), VcsError> {
+        let detached = self.get_detached_commits()?;
+        match detached.first() {
+            Some(hash) if !self.get_ancestors(target)?.contains(hash) => {
+                Err(VcsError::DetachedCommits(detached))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that a new branch can be created with the given name: the name is valid,
+    /// no branch has it and its reference doesn't clash with the reference of another branch.
+    pub fn check_new_branch_name(&self, branch_name: &str) -> Result<(), VcsError> {
+        Branch::check_name(branch_name)?;
+        if self.exists_branch(branch_name) {
+            return Err(VcsError::BranchExists(branch_name.to_string()));
+        }
+        self.check_no_ref_clash(branch_name, "")
+    }
+
+    /// Checks that the reference of the branch with the given name would be neither a directory
+    /// with references of other branches nor inside the reference of another branch.
+    /// The branch with the ignored name is going to be renamed and doesn't clash.
+    fn check_no_ref_clash(&self, branch_name: &str, ignored: &str) -> Result<(), VcsError> {
+        let names = self
+            .branches
+            .iter()
+            .map(|branch| branch.get_name())
+            .filter(|name| *name != ignored);
+        match find_ref_clash(branch_name, names) {
+            Some(name) => Err(VcsError::InvalidBranchName {
+                name: branch_name.to_string(),
+                reason: format!("it clashes with branch {}", name),
+            }),
+            None => Ok(()),
+        }
+    }
+
     /// Checks existance of the branch with name branch_name.
     pub fn exists_branch(&self, branch_name: &str) -> bool {
         self.get_branch_by_name(branch_name).is_ok()
     }
 
+    /// Checks that the buffer and the references read from the disk refer only to existing
+    /// branches and commits.
+    pub fn check_consistency(&self) -> Result<(), VcsError> {
+        if let Head::Branch(name) = &self.head {
+            if !self.exists_branch(name) {
+                return Err(VcsError::CorruptedRepository(format!(
+                    "no current branch {}",
+                    name
+                )));
+            }
+        }
+        if !self.all_commits.contains_key(self.get_head_hash()) {
+            return Err(VcsError::CorruptedRepository(format!(
+                "no current commit {}",
+                self.get_head_hash()
+            )));
+        }
+        for branch in self.branches.iter() {
+            if !self.all_commits.contains_key(branch.get_commit_hash()) {
+                return Err(VcsError::CorruptedRepository(format!(
+                    "no commit {} of branch {}",
+                    branch.get_commit_hash(),
+                    branch.get_name()
+                )));
+            }
+        }
+        for tag in self.tags.iter() {
+            if !self.all_commits.contains_key(tag.get_commit_hash()) {
+                return Err(VcsError::CorruptedRepository(format!(
+                    "no commit {} of tag {}",
+                    tag.get_commit_hash(),
+                    tag.get_name()
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// Checks for uncommitted files in the directory to which the VCS is linked
-    pub fn check_no_uncommited(&self) -> Result<(), &'static str> {
+    pub fn check_no_uncommited(&self) -> Result<(), VcsError> {
         let repo_files = files_from_dir(&self.root_dir)?;
-        let commit_files = files_from_commit(&self.current_commit)?;
+        let commit_files = files_from_commit(&self.root_dir, self.get_current_commit())?;
 
         if !all_changed_files(&commit_files, &repo_files).is_empty() {
-            return Err("uncommited files");
+            return Err(VcsError::UncommittedFiles);
+        }
+        let index_tree = write_tree(&self.root_dir, read_index(self)?.get_entries())?;
+        if index_tree != self.get_current_commit().get_tree_hash() {
+            return Err(VcsError::UncommittedFiles);
         }
 
         Ok(())
@@ -1,341 +1,602 @@
 #![forbid(unsafe_code)]
 
-mod commands;
-mod library;
-mod utils;
-
-use crate::utils::comand_parser::{Command, CommandParser};
-use crate::utils::extract_files::read_vcs;
-use crate::utils::operation_hash::calculate_hash;
-use crate::utils::pack_files::pack_vcs;
-use crate::utils::print_files::print_uncommitted_files;
 use clap::Parser;
 use path_absolutize::*;
+use serde_json::json;
+use std::cell::Cell;
 use std::env::current_dir;
 use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use vcs::commands;
+use vcs::library::config::Config;
+use vcs::library::error::VcsError;
+use vcs::library::head::Head;
+use vcs::library::identity::Identity;
+use vcs::library::lock::{LockMode, RepositoryLock};
+use vcs::library::log_options::{LogFormat, LogOptions};
+use vcs::library::report::{
+    ConfigReport, DiffReport, ErrorReport, GcReport, InitReport, JumpReport, NewBranchReport,
+    PathsReport, RefKind, RefReport, Report,
+};
+use vcs::library::revision::{RevisionRange, HEAD};
+use vcs::library::tag::Annotation;
+use vcs::library::vcs_repository::VcsRepository;
+use vcs::utils::comand_parser::{
+    BranchCommand, Command, CommandParser, ConfigCommand, LogArgs, TagCommand,
+};
+use vcs::utils::diff::colorize_diff;
+use vcs::utils::extract_files::{read_vcs, read_vcs_shared};
+use vcs::utils::pack_files::pack_vcs;
+use vcs::utils::print_files::uncommitted_files;
+use vcs::utils::work_with_config::{repo_config_path, user_config_path};
 
-fn get_dir_with_vcs() -> Result<PathBuf, &'static str> {
-    let mut dir = current_dir().unwrap();
+fn get_dir_with_vcs() -> Result<PathBuf, VcsError> {
+    let mut dir = current_dir().map_err(|e| VcsError::io(Path::new("."), e))?;
+    let start_dir = dir.clone();
     while !dir.join(".vcs").exists() {
         if !dir.pop() {
-            return Err("No VCS in this project");
+            return Err(VcsError::NoRepository(start_dir));
         }
     }
     Ok(dir)
 }
 
-fn call_init(str_path: &str) {
-    let path_absolute = Path::new(&str_path).absolutize().unwrap();
-    let path = path_absolute.to_str().unwrap();
-    let vcs = match commands::init::init(&Path::new(path)) {
-        Ok(new_vcs) => new_vcs,
-        Err(error) => {
-            if error == "is not a dir" {
-                println!("{} isn't a existing directory", str_path);
-                return;
-            }
-            if error == "already exists" {
-                println!("VCS already exists on path {}", str_path);
-                return;
+/// Locks the repository in the given mode and reads it, the lock is held until it is dropped.
+fn open_vcs(mode: LockMode) -> Result<(RepositoryLock, VcsRepository), VcsError> {
+    let dir = get_dir_with_vcs()?;
+    let lock = RepositoryLock::acquire(&dir, mode)?;
+    let vcs = match mode {
+        LockMode::Shared => read_vcs_shared(dir)?,
+        LockMode::Exclusive => read_vcs(dir)?,
+    };
+    Ok((lock, vcs))
+}
+
+/// Returns the given paths relative to the repository root.
+fn paths_in_repo(root_dir: &Path, paths: &[String]) -> Result<Vec<PathBuf>, VcsError> {
+    paths
+        .iter()
+        .map(|path| {
+            let absolute = Path::new(path)
+                .absolutize()
+                .map_err(|e| VcsError::io(Path::new(path), e))?;
+            absolute
+                .strip_prefix(root_dir)
+                .map(Path::to_path_buf)
+                .map_err(|_| VcsError::OutsideRepository(absolute.to_path_buf()))
+        })
+        .collect()
+}
+
+/// Prints the error of the command as text, `action` names the command in the hints.
+fn report_error(vcs: Option<&VcsRepository>, error: &VcsError, action: &str) {
+    match error {
+        VcsError::UncommittedFiles => {
+            println!(
+                "error: Your local changes to the following files should be commited or dropped:"
+            );
+            if let Some(vcs) = vcs {
+                match uncommitted_files(vcs) {
+                    Ok(paths) => paths
+                        .iter()
+                        .for_each(|path| println!("  {}", path.display())),
+                    Err(err) => println!("error: {}", err),
+                }
             }
+            println!(
+                "Please commit your changes or drop them before you {}.",
+                action
+            );
+            println!("Aborting...");
+        }
+        VcsError::NoChanges | VcsError::AlreadyMerged(_) => println!("{}", error),
+        VcsError::NoCommit(_)
+        | VcsError::AmbiguousCommit(_)
+        | VcsError::InvalidRevision { .. }
+        | VcsError::NoParent(_)
+        | VcsError::NoBranch(_)
+        | VcsError::BranchExists(_)
+        | VcsError::InvalidBranchName { .. }
+        | VcsError::NoTag(_)
+        | VcsError::TagExists(_)
+        | VcsError::InvalidTagName { .. }
+        | VcsError::CurrentBranch(_)
+        | VcsError::UnmergedBranch(_)
+        | VcsError::DetachedHead(_)
+        | VcsError::DetachedCommits(_)
+        | VcsError::RepositoryBusy(_)
+        | VcsError::MergeInProgress
+        | VcsError::UnresolvedConflicts(_) => {
             println!("{}", error);
-            return;
+            println!("Aborting...");
         }
-    };
+        VcsError::MergeConflict(_) | VcsError::NoMergeInProgress => println!("{}", error),
+        _ => println!("error: {}", error),
+    }
+}
 
-    pack_vcs(&Path::new(&str_path), &vcs);
-    println!("Initialized VCS repository in {}", str_path);
-    println!("Created commit:");
-    println!(
-        "[master {}] Initial commit",
-        calculate_hash(vcs.get_current_commit())
-    );
+///
+/// This is a struct for the way the command prints its result: `command` names it in the JSON document,
+/// `action` names it in the hints of the text errors.
+/// With `--json` one line is printed: {"command": ..., "ok": true, "result": ...}
+/// or {"command": ..., "ok": false, "error": {"kind": ..., "message": ..., "paths"/"commits": [...]}}.
+/// The exit status is 0 if the command succeeds and 1 if it prints an error, in both modes.
+struct Output {
+    command: &'static str,
+    action: &'static str,
+    json: bool,
+    failed: Cell<bool>,
 }
 
-fn call_status() {
-    let dir = match get_dir_with_vcs() {
-        Ok(dir) => dir,
-        Err(str_err) => {
-            println!("{}", str_err);
-            return;
+impl Output {
+    fn init(json: bool, command: &'static str, action: &'static str) -> Self {
+        Self {
+            command,
+            action,
+            json,
+            failed: Cell::new(false),
         }
-    };
-    let vcs = match read_vcs(dir) {
-        Ok(vcs) => vcs,
-        Err(str_error) => {
-            println!("{}", str_error);
-            return;
+    }
+
+    /// Runs the command printing to this output and returns the exit status.
+    fn run(self, command: impl FnOnce(&Self)) -> ExitCode {
+        command(&self);
+        match self.failed.get() {
+            true => ExitCode::FAILURE,
+            false => ExitCode::SUCCESS,
         }
-    };
-    match commands::status::status(&vcs) {
-        Err(str_err) => {
-            println!("{}", str_err);
+    }
+
+    /// Prints the result of the command.
+    fn print(&self, report: &impl Report) {
+        match self.json {
+            true => println!(
+                "{}",
+                json!({"command": self.command, "ok": true, "result": report})
+            ),
+            false => print!("{}", report.to_text()),
         }
-        _ => {}
     }
-}
 
-fn call_jump_to_commit(commit_hash: u64) {
-    let dir = match get_dir_with_vcs() {
-        Ok(dir) => dir,
-        Err(str_err) => {
-            println!("{}", str_err);
-            return;
+    /// Prints the error of the command and marks it as failed, the VCS is used to list the uncommitted files.
+    fn error(&self, vcs: Option<&VcsRepository>, error: &VcsError) {
+        self.failed.set(true);
+        if !self.json {
+            return report_error(vcs, error, self.action);
+        }
+        let uncommitted = match (error, vcs) {
+            (VcsError::UncommittedFiles, Some(vcs)) => uncommitted_files(vcs).unwrap_or_default(),
+            _ => vec![],
+        };
+        let report = ErrorReport::init(error, uncommitted);
+        println!(
+            "{}",
+            json!({"command": self.command, "ok": false, "error": report})
+        );
+    }
+
+    /// Prints the result or the error of the command.
+    fn finish(&self, vcs: Option<&VcsRepository>, result: Result<impl Report, VcsError>) {
+        match result {
+            Ok(report) => self.print(&report),
+            Err(err) => self.error(vcs, &err),
         }
-    };
-    let mut vcs = match read_vcs(dir) {
-        Ok(vcs) => vcs,
-        Err(str_error) => {
-            println!("{}", str_error);
-            return;
+    }
+
+    /// Locks the repository in the given mode and reads it or prints the error.
+    fn open_vcs(&self, mode: LockMode) -> Option<(RepositoryLock, VcsRepository)> {
+        open_vcs(mode).map_err(|err| self.error(None, &err)).ok()
+    }
+
+    /// Buffers the state of the VCS and prints the report, or prints the error if the buffer can't be written.
+    fn save_vcs(&self, vcs: &VcsRepository, report: &impl Report) {
+        match pack_vcs(vcs.get_dir(), vcs) {
+            Ok(_) => self.print(report),
+            Err(err) => self.error(Some(vcs), &err),
         }
+    }
+}
+
+fn call_init(output: &Output, str_path: &str, initial_branch: Option<&str>) {
+    let path = match Path::new(&str_path).absolutize() {
+        Ok(path) => path.to_path_buf(),
+        Err(err) => return output.error(None, &VcsError::io(Path::new(str_path), err)),
     };
-    match commands::jump_to_commit::jump_to_commit(&mut vcs, commit_hash) {
-        Err(str_err) => {
-            if str_err == "uncommited files" {
-                println!("error: Your local changes to the following files should be commited or dropped:");
-                print_uncommitted_files(&vcs).unwrap();
-                println!("Please commit your changes or drop them before you jump.");
-                println!("Aborting...");
-                return;
-            }
-            if str_err == "No commit with this hash" {
-                println!("No commit with hash {} exists.", commit_hash);
-                println!("Aborting...");
-                return;
-            }
-            println!("{}", str_err);
-        }
-        Ok(_) => {
-            let branch_name = vcs.get_current_branch().get_name();
-            pack_vcs(vcs.get_dir(), &vcs);
-            println!(
-                "Successfully jumped to commit {}. Current branch: {}.",
-                commit_hash, branch_name
-            );
-        }
+    match commands::init::init(&path, initial_branch) {
+        Ok(vcs) => output.save_vcs(&vcs, &InitReport::init(str_path, &vcs)),
+        Err(err) => output.error(None, &err),
     }
 }
 
-fn call_new_commit(msg: &str) {
-    let dir = match get_dir_with_vcs() {
-        Ok(dir) => dir,
-        Err(str_err) => {
-            println!("{}", str_err);
-            return;
-        }
+fn call_status(output: &Output) {
+    let Some((_lock, vcs)) = output.open_vcs(LockMode::Shared) else {
+        return;
     };
-    let mut vcs = match read_vcs(dir) {
-        Ok(vcs) => vcs,
-        Err(str_error) => {
-            println!("{}", str_error);
-            return;
-        }
+    output.finish(Some(&vcs), commands::status::status(&vcs));
+}
+
+fn call_jump_to_commit(output: &Output, commit_hash: &str, force: bool) {
+    let Some((_lock, mut vcs)) = output.open_vcs(LockMode::Exclusive) else {
+        return;
+    };
+    match commands::jump_to_commit::jump_to_commit(&mut vcs, commit_hash, force) {
+        Ok(_) => output.save_vcs(&vcs, &JumpReport::init(&vcs, false)),
+        Err(err) => output.error(Some(&vcs), &err),
+    }
+}
+
+fn call_new_commit(output: &Output, msg: &str) {
+    let Some((_lock, mut vcs)) = output.open_vcs(LockMode::Exclusive) else {
+        return;
     };
     match commands::new_commit::new_commit(&mut vcs, msg) {
-        Ok(_) => {
-            pack_vcs(vcs.get_dir(), &vcs);
-        }
-        Err(str_err) => {
-            if str_err == "Current commit not last" {
-                println!("You can create a new commit only from last one.");
-                println!("Aborting...");
-                return;
-            }
-            if str_err == "No changes" {
-                println!("No changes to be committed");
-                return;
-            }
-            println!("{}", str_err);
-        }
+        Ok(report) => output.save_vcs(&vcs, &report),
+        Err(err) => output.error(Some(&vcs), &err),
     }
 }
 
-fn call_jump_to_branch(branch_name: &str) {
-    let dir = match get_dir_with_vcs() {
-        Ok(dir) => dir,
-        Err(str_err) => {
-            println!("{}", str_err);
-            return;
-        }
+fn call_add(output: &Output, paths: &[String]) {
+    let Some((_lock, vcs)) = output.open_vcs(LockMode::Exclusive) else {
+        return;
     };
-    let mut vcs = match read_vcs(dir) {
-        Ok(vcs) => vcs,
-        Err(str_error) => {
-            println!("{}", str_error);
-            return;
-        }
+    let result = paths_in_repo(vcs.get_dir(), paths).and_then(|paths| {
+        commands::add::add(&vcs, &paths)?;
+        Ok(PathsReport::init(paths))
+    });
+    output.finish(Some(&vcs), result);
+}
+
+fn call_reset(output: &Output, paths: &[String]) {
+    let Some((_lock, vcs)) = output.open_vcs(LockMode::Exclusive) else {
+        return;
+    };
+    let result = paths_in_repo(vcs.get_dir(), paths).and_then(|paths| {
+        commands::reset::reset(&vcs, &paths)?;
+        Ok(PathsReport::init(paths))
+    });
+    output.finish(Some(&vcs), result);
+}
+
+fn call_jump_to_branch(output: &Output, branch_name: &str, force: bool) {
+    let Some((_lock, mut vcs)) = output.open_vcs(LockMode::Exclusive) else {
+        return;
+    };
+    match commands::jump_to_branch::jump_to_branch(&mut vcs, branch_name, force) {
+        Ok(_) => output.save_vcs(&vcs, &JumpReport::init(&vcs, true)),
+        Err(err) => output.error(Some(&vcs), &err),
+    }
+}
+
+fn call_diff(
+    output: &Output,
+    commits: Option<Vec<String>>,
+    branches: Option<Vec<String>>,
+    context: Option<usize>,
+) {
+    let Some((_lock, vcs)) = output.open_vcs(LockMode::Shared) else {
+        return;
     };
-    match commands::jump_to_branch::jump_to_branch(&mut vcs, branch_name) {
-        Err(str_err) => {
-            if str_err == "uncommited files" {
-                println!("error: Your local changes to the following files should be commited or dropped:");
-                print_uncommitted_files(&vcs).unwrap();
-                println!("Please commit your changes or drop them before you jump.");
-                println!("Aborting...");
-                return;
+    let settings = Config::read(vcs.get_dir()).and_then(|config| {
+        let context = match context {
+            Some(context) => context,
+            None => config.get_diff_context()?,
+        };
+        Ok((context, config.get_color()?.is_enabled() && !output.json))
+    });
+    let result = settings.and_then(|(context, color)| {
+        let diff = match (commits, branches) {
+            (Some(commits), _) => {
+                commands::diff::diff_commits(&vcs, &commits[0], &commits[1], context)
             }
-            if str_err == "No branch with this name" {
-                println!("No branch {} exists", branch_name);
-                println!("Aborting...");
-                return;
+            (None, Some(branches)) => {
+                commands::diff::diff_branches(&vcs, &branches[0], &branches[1], context)
             }
-            println!("{}", str_err);
-        }
-        Ok(_) => {
-            let hash_commit = calculate_hash(vcs.get_current_commit());
-            pack_vcs(vcs.get_dir(), &vcs);
-            println!(
-                "Successfully jumped to branch {}. Current commit: {}.",
-                branch_name, hash_commit
-            );
-        }
+            (None, None) => commands::diff::diff_working_tree(&vcs, context),
+        }?;
+        Ok(DiffReport::init(match color {
+            true => colorize_diff(&diff),
+            false => diff,
+        }))
+    });
+    output.finish(Some(&vcs), result);
+}
+
+/// Returns the options of the log from the arguments, the paths are relative to the repository root.
+fn log_options(root_dir: &Path, args: LogArgs) -> Result<LogOptions, VcsError> {
+    let mut options = LogOptions::init();
+    options.set_format(match (args.oneline, args.format) {
+        (_, Some(template)) => LogFormat::Template(template),
+        (true, None) => LogFormat::Oneline,
+        (false, None) => LogFormat::Full,
+    });
+    options.set_max_count(args.max_count);
+    let parse_date = |date: Option<String>| date.as_deref().map(LogOptions::parse_date).transpose();
+    options.set_dates(parse_date(args.since)?, parse_date(args.until)?);
+    options.set_paths(paths_in_repo(root_dir, &args.path)?);
+    options.set_message(args.grep);
+    options.set_all(args.all);
+    options.set_graph(args.graph);
+    Ok(options)
+}
+
+fn call_log(output: &Output, revision: Option<&str>, options: LogArgs) {
+    let Some((_lock, vcs)) = output.open_vcs(LockMode::Shared) else {
+        return;
+    };
+    let result = log_options(vcs.get_dir(), options).and_then(|options| {
+        let range = RevisionRange::parse(revision.unwrap_or(HEAD))?;
+        commands::log::log_report(&vcs, &range, &options)
+    });
+    output.finish(Some(&vcs), result);
+}
+
+fn call_new_branch(output: &Output, branch_name: &str) {
+    let Some((_lock, mut vcs)) = output.open_vcs(LockMode::Exclusive) else {
+        return;
+    };
+    let source_branch = vcs.get_head_name().to_string();
+    match commands::new_branch::new_branch(&mut vcs, branch_name) {
+        Ok(hash_commit) => output.save_vcs(
+            &vcs,
+            &NewBranchReport::init(branch_name, &source_branch, &hash_commit),
+        ),
+        Err(err) => output.error(Some(&vcs), &err),
     }
 }
 
-fn call_log() {
-    let dir = match get_dir_with_vcs() {
-        Ok(dir) => dir,
-        Err(str_err) => {
-            println!("{}", str_err);
-            return;
-        }
+fn call_merge(output: &Output, branch_name: &str) {
+    let Some((_lock, mut vcs)) = output.open_vcs(LockMode::Exclusive) else {
+        return;
     };
-    let vcs = match read_vcs(dir) {
-        Ok(vcs) => vcs,
-        Err(str_error) => {
-            println!("{}", str_error);
-            return;
-        }
+    let result = commands::merge::merge(&mut vcs, branch_name);
+    output.finish(Some(&vcs), result);
+}
+
+fn call_list_branches(output: &Output) {
+    let Some((_lock, vcs)) = output.open_vcs(LockMode::Shared) else {
+        return;
     };
-    match commands::log::log(&vcs) {
-        Err(str_err) => {
-            println!("{}", str_err);
-        }
-        _ => {}
+    output.finish(Some(&vcs), commands::list_branches::list_branches(&vcs));
+}
+
+fn call_show_branch(output: &Output, branch_name: Option<&str>) {
+    let Some((_lock, vcs)) = output.open_vcs(LockMode::Shared) else {
+        return;
+    };
+    let name = match (branch_name, vcs.get_head()) {
+        (Some(name), _) => name.to_string(),
+        (None, Head::Branch(name)) => name.clone(),
+        (None, Head::Detached(_)) => vcs.get_default_branch().to_string(),
+    };
+    output.finish(Some(&vcs), commands::show_branch::show_branch(&vcs, &name));
+}
+
+fn call_rename_branch(output: &Output, old_name: &str, new_name: &str) {
+    let Some((_lock, mut vcs)) = output.open_vcs(LockMode::Exclusive) else {
+        return;
+    };
+    let result =
+        commands::rename_branch::rename_branch(&mut vcs, old_name, new_name).and_then(|_| {
+            Ok(vcs
+                .get_branch_by_name(new_name)?
+                .get_commit_hash()
+                .to_string())
+        });
+    match result {
+        Ok(hash_commit) => output.save_vcs(
+            &vcs,
+            &RefReport::init(
+                RefKind::Branch,
+                Some(old_name),
+                Some(new_name),
+                &hash_commit,
+            ),
+        ),
+        Err(err) => output.error(Some(&vcs), &err),
     }
 }
 
-fn call_new_branch(branch_name: &str) {
-    let dir = match get_dir_with_vcs() {
-        Ok(dir) => dir,
-        Err(str_err) => {
-            println!("{}", str_err);
-            return;
-        }
+fn call_delete_branch(output: &Output, branch_name: &str, force: bool) {
+    let Some((_lock, mut vcs)) = output.open_vcs(LockMode::Exclusive) else {
+        return;
     };
-    let mut vcs = match read_vcs(dir) {
-        Ok(vcs) => vcs,
-        Err(str_error) => {
-            println!("{}", str_error);
-            return;
-        }
+    match commands::delete_branch::delete_branch(&mut vcs, branch_name, force) {
+        Ok(hash_commit) => output.save_vcs(
+            &vcs,
+            &RefReport::init(RefKind::Branch, Some(branch_name), None, &hash_commit),
+        ),
+        Err(err) => output.error(Some(&vcs), &err),
+    }
+}
+
+fn call_create_tag(
+    output: &Output,
+    tag_name: &str,
+    commit: Option<&str>,
+    message: Option<&str>,
+    tagger: Option<String>,
+) {
+    let Some((_lock, mut vcs)) = output.open_vcs(LockMode::Exclusive) else {
+        return;
     };
-    match commands::new_branch::new_branch(&mut vcs, branch_name) {
-        Err(str_err) => {
-            if str_err == "Current branch is not master" {
-                println!(
-                    "Creating a new branch is possible only when you are in the master branch."
-                );
-                println!("Aborting...");
-                return;
-            }
-            if str_err == "Branch exists" {
-                println!("Branch {} already exists.", branch_name);
-                println!("Aborting...");
-                return;
-            }
-            println!("{}", str_err);
-        }
-        Ok(hash_commit) => {
-            pack_vcs(vcs.get_dir(), &vcs);
-            println!(
-                "Created a new branch {} from master's commit {}",
-                branch_name, hash_commit
-            );
-        }
+    let annotation = match (message, tagger) {
+        (None, _) => Ok(None),
+        (Some(message), Some(tagger)) => Ok(Some(Annotation::init(&tagger, message))),
+        (Some(message), None) => Identity::committer(vcs.get_dir())
+            .map(|tagger| Some(Annotation::init(&tagger.to_string(), message))),
+    };
+    let result = annotation.and_then(|annotation| {
+        commands::create_tag::create_tag(&mut vcs, tag_name, commit, annotation)
+    });
+    match result {
+        Ok(hash_commit) => output.save_vcs(
+            &vcs,
+            &RefReport::init(RefKind::Tag, None, Some(tag_name), &hash_commit),
+        ),
+        Err(err) => output.error(Some(&vcs), &err),
     }
 }
 
-fn call_merge(branch_name: &str) {
-    let dir = match get_dir_with_vcs() {
-        Ok(dir) => dir,
-        Err(str_err) => {
-            println!("{}", str_err);
-            return;
-        }
+fn call_list_tags(output: &Output) {
+    let Some((_lock, vcs)) = output.open_vcs(LockMode::Shared) else {
+        return;
     };
-    let mut vcs = match read_vcs(dir) {
-        Ok(vcs) => vcs,
-        Err(str_error) => {
-            println!("{}", str_error);
-            return;
-        }
+    output.print(&commands::list_tags::list_tags(&vcs));
+}
+
+fn call_delete_tag(output: &Output, tag_name: &str) {
+    let Some((_lock, mut vcs)) = output.open_vcs(LockMode::Exclusive) else {
+        return;
+    };
+    match commands::delete_tag::delete_tag(&mut vcs, tag_name) {
+        Ok(hash_commit) => output.save_vcs(
+            &vcs,
+            &RefReport::init(RefKind::Tag, Some(tag_name), None, &hash_commit),
+        ),
+        Err(err) => output.error(Some(&vcs), &err),
+    }
+}
+
+/// Runs the config subcommand on the user config if `global` is set,
+/// otherwise on the config of the repository. Reading works outside of a repository too.
+fn run_config(global: bool, command: ConfigCommand) -> Result<ConfigReport, VcsError> {
+    let dir = match global {
+        true => None,
+        false => match (get_dir_with_vcs(), &command) {
+            (Ok(dir), _) => Some(dir),
+            (Err(_), ConfigCommand::Get { .. } | ConfigCommand::List) => None,
+            (Err(err), _) => return Err(err),
+        },
+    };
+    let read_config = || match &dir {
+        Some(dir) => Config::read(dir),
+        None => Config::read_user(),
+    };
+    let path = match &dir {
+        Some(dir) => repo_config_path(dir),
+        None => user_config_path().ok_or(VcsError::NoUserConfig)?,
+    };
+    let mode = match command {
+        ConfigCommand::Get { .. } | ConfigCommand::List => LockMode::Shared,
+        _ => LockMode::Exclusive,
+    };
+    let _lock = dir
+        .as_ref()
+        .map(|dir| RepositoryLock::acquire(dir, mode))
+        .transpose()?;
+    Ok(match command {
+        ConfigCommand::Get { key } => {
+            let value = commands::config::get_config(&read_config()?, &key)?;
+            ConfigReport::get(&key.to_lowercase(), &value)
+        }
+        ConfigCommand::Set { key, value } => {
+            commands::config::set_config(&path, &key, &value)?;
+            ConfigReport::change(&key.to_lowercase(), Some(&value))
+        }
+        ConfigCommand::Unset { key } => {
+            commands::config::unset_config(&path, &key)?;
+            ConfigReport::change(&key.to_lowercase(), None)
+        }
+        ConfigCommand::List => commands::config::list_config(&read_config()?),
+    })
+}
+
+fn call_config(output: &Output, global: bool, command: ConfigCommand) {
+    output.finish(None, run_config(global, command));
+}
+
+fn call_gc(output: &Output) {
+    let Some((_lock, mut vcs)) = output.open_vcs(LockMode::Exclusive) else {
+        return;
+    };
+    let result = commands::gc::gc(&mut vcs).map(GcReport::init);
+    output.finish(Some(&vcs), result);
+}
+
+fn call_abort_merge(output: &Output) {
+    let Some((_lock, vcs)) = output.open_vcs(LockMode::Exclusive) else {
+        return;
     };
-    match commands::merge::merge(&mut vcs, branch_name) {
-        Ok(_) => {
-            pack_vcs(vcs.get_dir(), &vcs);
+    output.finish(Some(&vcs), commands::merge::abort_merge(&vcs));
+}
+
+fn main() -> ExitCode {
+    let parser = CommandParser::parse();
+    let json = parser.json;
+    match parser.command {
+        Command::Init {
+            path,
+            initial_branch,
+        } => Output::init(json, "init", "init")
+            .run(|output| call_init(output, &path, initial_branch.as_deref())),
+        Command::Status => Output::init(json, "status", "status").run(call_status),
+        Command::Jump {
+            commit,
+            branch,
+            force,
+        } => Output::init(json, "jump", "jump").run(|output| match (commit, branch) {
+            (Some(commit), _) => call_jump_to_commit(output, &commit, force),
+            (None, Some(branch)) => call_jump_to_branch(output, &branch, force),
+            (None, None) => unreachable!("clap requires a branch or a commit"),
+        }),
+        Command::Add { paths } => {
+            Output::init(json, "add", "add").run(|output| call_add(output, &paths))
+        }
+        Command::Reset { paths } => {
+            Output::init(json, "reset", "reset").run(|output| call_reset(output, &paths))
         }
-        Err(str_err) => {
-            if str_err == "Current commit no master last commit" {
-                println!("The merge is possible only when you are in the last commit in master.");
-                println!("Aborting...");
-                return;
+        Command::Commit { message } => {
+            Output::init(json, "commit", "commit").run(|output| call_new_commit(output, &message))
+        }
+        Command::Diff {
+            commits,
+            branches,
+            context,
+        } => Output::init(json, "diff", "diff")
+            .run(|output| call_diff(output, commits, branches, context)),
+        Command::Log { revision, options } => Output::init(json, "log", "log")
+            .run(|output| call_log(output, revision.as_deref(), options)),
+        Command::NewBranch { name } => Output::init(json, "new_branch", "create a branch")
+            .run(|output| call_new_branch(output, &name)),
+        Command::Branch { command } => match command {
+            BranchCommand::List => {
+                Output::init(json, "branch list", "list branches").run(call_list_branches)
             }
-            if str_err == "uncommited files" {
-                println!("error: Your local changes to the following files should be commited or dropped:");
-                print_uncommitted_files(&vcs).unwrap();
-                println!("Please commit your changes or drop them before you merge.");
-                println!("Aborting...");
-                return;
+            BranchCommand::Show { name } => Output::init(json, "branch show", "show a branch")
+                .run(|output| call_show_branch(output, name.as_deref())),
+            BranchCommand::Rename { old_name, new_name } => {
+                Output::init(json, "branch rename", "rename a branch")
+                    .run(|output| call_rename_branch(output, &old_name, &new_name))
             }
-            if str_err == "Aborting" {
-                println!("Aborting...");
-                return;
+            BranchCommand::Delete { name, force } => {
+                Output::init(json, "branch delete", "delete a branch")
+                    .run(|output| call_delete_branch(output, &name, force))
             }
-            if str_err == "No branch with this name" {
-                println!("No branch {} exists.", branch_name);
-                println!("Aborting...");
-                return;
+        },
+        Command::Tag { command } => match command {
+            TagCommand::Create {
+                name,
+                commit,
+                message,
+                tagger,
+            } => Output::init(json, "tag create", "create a tag").run(|output| {
+                call_create_tag(output, &name, commit.as_deref(), message.as_deref(), tagger)
+            }),
+            TagCommand::List => Output::init(json, "tag list", "list tags").run(call_list_tags),
+            TagCommand::Delete { name } => Output::init(json, "tag delete", "delete a tag")
+                .run(|output| call_delete_tag(output, &name)),
+        },
+        Command::Config { global, command } => Output::init(json, "config", "configure")
+            .run(|output| call_config(output, global, command)),
+        Command::Gc => Output::init(json, "gc", "collect garbage").run(call_gc),
+        Command::Merge { branch, abort } => match (abort, branch) {
+            (true, _) => {
+                Output::init(json, "merge --abort", "abort the merge").run(call_abort_merge)
             }
-            println!("{}", str_err);
-        }
-    }
-}
-
-fn main() {
-    match CommandParser::parse().command {
-        Command::Init { path } => {
-            call_init(&path);
-        }
-        Command::Status => {
-            call_status();
-        }
-        Command::Jump { commit, branch } => {
-            if commit.is_some() {
-                let commit_hash = match commit.clone().unwrap().parse::<u64>() {
-                    Ok(num) => num,
-                    Err(_) => {
-                        println!("the {} is not a valid hash", commit.unwrap());
-                        return;
-                    }
-                };
-                call_jump_to_commit(commit_hash);
-            } else {
-                call_jump_to_branch(&branch.unwrap());
+            (false, Some(branch)) => {
+                Output::init(json, "merge", "merge").run(|output| call_merge(output, &branch))
             }
-        }
-        Command::Commit { message } => {
-            call_new_commit(&message);
-        }
-        Command::Log {} => {
-            call_log();
-        }
-        Command::NewBranch { name } => {
-            call_new_branch(&name);
-        }
-        Command::Merge { branch } => {
-            call_merge(&branch);
-        }
+            (false, None) => unreachable!("clap requires a branch unless --abort is given"),
+        },
     }
 }
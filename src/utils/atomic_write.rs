@@ -0,0 +1,22 @@
+#![forbid(unsafe_code)]
+
+use crate::library::error::VcsError;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Returns the path of the temporary file which is renamed to the given path after writing.
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.tmp", name))
+}
+
+/// Writes the data to the file along the given path, so that the file contains either
+/// the old or the new data even if the process is interrupted.
+pub fn write_atomically(path: &Path, data: &[u8]) -> Result<(), VcsError> {
+    let temp = temp_path(path);
+    let mut output = fs::File::create(&temp).map_err(|e| VcsError::io(&temp, e))?;
+    output.write_all(data).map_err(|e| VcsError::io(&temp, e))?;
+    output.sync_all().map_err(|e| VcsError::io(&temp, e))?;
+    fs::rename(&temp, path).map_err(|e| VcsError::io(path, e))
+}
@@ -1,10 +1,17 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(name = "VCS")]
 #[command(version = "0.1.0")]
 #[command(about = "Simple tool for code version control", long_about = None)]
 pub struct CommandParser {
+    #[arg(
+        long,
+        global(true),
+        help("Prints one JSON document {\"command\", \"ok\", \"result\" or \"error\"} instead of the text")
+    )]
+    pub json: bool,
+
     #[command(subcommand)]
     pub command: Command,
 }
@@ -15,15 +22,61 @@ pub enum Command {
     #[command(about = "Prints current repo status")]
     Status,
     #[command(about = "Prints commits list")]
-    Log,
+    Log {
+        #[arg(
+            value_name("REVISION"),
+            help("Revision like HEAD~2, master^2, tag or hash prefix, or range A..B, HEAD by default")
+        )]
+        revision: Option<String>,
+
+        #[command(flatten)]
+        options: LogArgs,
+    },
+
+    #[command(
+        about = "Shows changes between the current commit and the working tree, commits or branches"
+    )]
+    Diff {
+        #[arg(long, num_args(2), value_names(["OLD_COMMIT", "NEW_COMMIT"]), conflicts_with("branches"))]
+        commits: Option<Vec<String>>,
+
+        #[arg(long, num_args(2), value_names(["OLD_BRANCH", "NEW_BRANCH"]))]
+        branches: Option<Vec<String>>,
+
+        #[arg(
+            long,
+            value_name("LINES"),
+            help("Unchanged lines around the changes, diff.context by default")
+        )]
+        context: Option<usize>,
+    },
 
     #[command(about = "Initializes repo")]
     Init {
         #[arg(long, value_name("DIRECTORY_PATH"))]
         path: String,
+
+        #[arg(
+            long,
+            value_name("BRANCH_NAME"),
+            help("The first and default branch, init.defaultbranch by default")
+        )]
+        initial_branch: Option<String>,
+    },
+
+    #[command(about = "Stages changes of the given files and directories")]
+    Add {
+        #[arg(required(true), value_name("PATH"))]
+        paths: Vec<String>,
+    },
+
+    #[command(about = "Unstages changes of the given files and directories")]
+    Reset {
+        #[arg(required(true), value_name("PATH"))]
+        paths: Vec<String>,
     },
 
-    #[command(about = "Commits current changes")]
+    #[command(about = "Commits staged changes")]
     Commit {
         #[arg(long)]
         message: String,
@@ -32,11 +85,26 @@ pub enum Command {
     #[command(arg_required_else_help(true))]
     #[command(about = "Jumps into branch or commit")]
     Jump {
-        #[arg(long, value_name("BRANCH_NAME"), conflicts_with("commit"))]
+        #[arg(
+            long,
+            value_name("BRANCH_NAME"),
+            required_unless_present("commit"),
+            conflicts_with("commit")
+        )]
         branch: Option<String>,
 
-        #[arg(long, value_name("COMMIT_HASH"))]
+        #[arg(
+            long,
+            value_name("COMMIT"),
+            help("Revision like HEAD~2, master^2, tag or hash prefix")
+        )]
         commit: Option<String>,
+
+        #[arg(
+            long,
+            help("Leaves the commits of the detached head which belong to no branch")
+        )]
+        force: bool,
     },
 
     #[command(name("new_branch"))]
@@ -46,9 +114,191 @@ pub enum Command {
         name: String,
     },
 
-    #[command(about = "Merges branch into master")]
+    #[command(about = "Lists, shows, renames and deletes branches")]
+    Branch {
+        #[command(subcommand)]
+        command: BranchCommand,
+    },
+
+    #[command(about = "Creates, lists and deletes tags")]
+    Tag {
+        #[command(subcommand)]
+        command: TagCommand,
+    },
+
+    #[command(about = "Gets and sets repository or user options")]
+    Config {
+        #[arg(
+            long,
+            global(true),
+            help("Uses the user config instead of the repository one")
+        )]
+        global: bool,
+
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+
+    #[command(about = "Removes unreachable commits and unused objects")]
+    Gc,
+
+    #[command(about = "Merges branch into the current branch")]
     Merge {
-        #[arg(long, value_name("BRANCH_NAME"))]
-        branch: String,
+        #[arg(
+            long,
+            value_name("BRANCH_NAME"),
+            required_unless_present("abort"),
+            conflicts_with("abort")
+        )]
+        branch: Option<String>,
+
+        #[arg(long, help("Aborts the merge stopped by conflicts"))]
+        abort: bool,
+    },
+}
+
+/// Options of the log command
+#[derive(Debug, Args)]
+pub struct LogArgs {
+    #[arg(
+        long,
+        conflicts_with("format"),
+        help("Prints the short hash, references and message of each commit in one line")
+    )]
+    pub oneline: bool,
+
+    #[arg(long, value_name("TEMPLATE"), help("Prints each commit by the template with %H, %h, %P, %p, %an, %ae, %cn, %ce, %ad, %as, %s, %d, %n"))]
+    pub format: Option<String>,
+
+    #[arg(short('n'), long, value_name("NUMBER"))]
+    pub max_count: Option<usize>,
+
+    #[arg(
+        long,
+        value_name("DATE"),
+        help("Commits created at or after the date: YYYY-MM-DD, YYYY-MM-DD HH:MM:SS or RFC 3339")
+    )]
+    pub since: Option<String>,
+
+    #[arg(
+        long,
+        value_name("DATE"),
+        help("Commits created at or before the date")
+    )]
+    pub until: Option<String>,
+
+    #[arg(
+        long,
+        value_name("PATH"),
+        help("Commits changing the files along the path, may be repeated")
+    )]
+    pub path: Vec<String>,
+
+    #[arg(long, value_name("TEXT"), help("Commits with the text in the message"))]
+    pub grep: Option<String>,
+
+    #[arg(
+        long,
+        conflicts_with("revision"),
+        help("Commits of all branches, tags and the head")
+    )]
+    pub all: bool,
+
+    #[arg(long, help("Draws the graph of branches and merges"))]
+    pub graph: bool,
+}
+
+/// Subcommands of the branch command
+#[derive(Debug, Subcommand)]
+pub enum BranchCommand {
+    #[command(about = "Lists branches, the current one is marked with *")]
+    List,
+
+    #[command(about = "Shows the commits of the branch, the current branch by default")]
+    Show {
+        #[arg(value_name("BRANCH_NAME"))]
+        name: Option<String>,
+    },
+
+    #[command(about = "Renames branch")]
+    Rename {
+        #[arg(value_name("OLD_NAME"))]
+        old_name: String,
+
+        #[arg(value_name("NEW_NAME"))]
+        new_name: String,
+    },
+
+    #[command(about = "Deletes branch, its commits are kept until garbage collection")]
+    Delete {
+        #[arg(value_name("BRANCH_NAME"))]
+        name: String,
+
+        #[arg(long, help("Deletes the branch even if it is not merged"))]
+        force: bool,
     },
 }
+
+/// Subcommands of the tag command
+#[derive(Debug, Subcommand)]
+pub enum TagCommand {
+    #[command(about = "Creates tag, it is annotated if the message is given")]
+    Create {
+        #[arg(value_name("TAG_NAME"))]
+        name: String,
+
+        #[arg(
+            long,
+            value_name("COMMIT"),
+            help("The tagged commit, the current one by default")
+        )]
+        commit: Option<String>,
+
+        #[arg(long)]
+        message: Option<String>,
+
+        #[arg(
+            long,
+            requires("message"),
+            help("The tag creator, the configured committer by default")
+        )]
+        tagger: Option<String>,
+    },
+
+    #[command(about = "Lists tags")]
+    List,
+
+    #[command(about = "Deletes tag, the tagged commit is kept until garbage collection")]
+    Delete {
+        #[arg(value_name("TAG_NAME"))]
+        name: String,
+    },
+}
+
+/// Subcommands of the config command
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    #[command(about = "Prints the value of the key")]
+    Get {
+        #[arg(value_name("KEY"))]
+        key: String,
+    },
+
+    #[command(about = "Sets the key to the value")]
+    Set {
+        #[arg(value_name("KEY"))]
+        key: String,
+
+        #[arg(value_name("VALUE"))]
+        value: String,
+    },
+
+    #[command(about = "Removes the key")]
+    Unset {
+        #[arg(value_name("KEY"))]
+        key: String,
+    },
+
+    #[command(about = "Lists all settings")]
+    List,
+}
@@ -1,11 +1,51 @@
 #![forbid(unsafe_code)]
 
+use super::object_store::remove_unreferenced;
+use super::work_with_trees::read_tree;
 use crate::library::commit::Commit;
-use std::fs;
+use crate::library::error::VcsError;
+use crate::library::index::Index;
+use crate::library::tree::EntryMode;
+use std::collections::HashSet;
+use std::path::Path;
 
-/// Deletes files of given commit.
-pub fn delete_commit_files(commit: &Commit) {
-    let path = commit.get_dir_commit();
-    println!("{}", path.display());
-    fs::remove_dir_all(path).unwrap();
+/// Collects hashes of the tree with the given hash, its subtrees and files.
+fn collect_tree_objects(
+    root_dir: &Path,
+    hash: &str,
+    ans: &mut HashSet<String>,
+) -> Result<(), VcsError> {
+    if !ans.insert(hash.to_string()) {
+        return Ok(());
+    }
+    for entry in read_tree(root_dir, hash)?.get_entries() {
+        if entry.get_mode() == EntryMode::Directory {
+            collect_tree_objects(root_dir, entry.get_hash(), ans)?;
+        } else {
+            ans.insert(entry.get_hash().to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Deletes the objects which are no longer referenced by the trees of the given commits or by the index.
+/// The objects with the given hashes, such as annotated tags, are kept too.
+pub fn delete_commit_files<'a>(
+    root_dir: &Path,
+    commits: impl Iterator<Item = &'a Commit>,
+    index: Option<&Index>,
+    kept: HashSet<String>,
+) -> Result<(), VcsError> {
+    let mut referenced = kept;
+    for commit in commits {
+        collect_tree_objects(root_dir, commit.get_tree_hash(), &mut referenced)?;
+    }
+    for (mode, hash) in index.iter().flat_map(|index| index.get_entries().values()) {
+        if *mode == EntryMode::Directory {
+            collect_tree_objects(root_dir, hash, &mut referenced)?;
+        } else {
+            referenced.insert(hash.clone());
+        }
+    }
+    remove_unreferenced(root_dir, &referenced)
 }
@@ -0,0 +1,288 @@
+#![forbid(unsafe_code)]
+
+use std::path::Path;
+
+/// Number of context lines around the changes used by default.
+pub const DEFAULT_CONTEXT_LINES: usize = 3;
+
+///
+/// This is an enum for one line of the difference between two texts.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DiffLine<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Checks that the data is not a text: it contains zero bytes or isn't valid UTF-8.
+pub fn is_binary(data: &[u8]) -> bool {
+    data.contains(&0) || std::str::from_utf8(data).is_err()
+}
+
+/// Returns the shortest edit script turning the old lines into the new ones
+/// (Myers algorithm in linear space: the middle snake is found and both halves are diffed recursively).
+pub fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffLine<'a>> {
+    let mut ans = vec![];
+    diff_range(old, new, &mut ans);
+    // Deletions go before insertions in every run of changed lines.
+    for run in ans.split_mut(|line| matches!(line, DiffLine::Equal(_))) {
+        run.sort_by_key(|line| matches!(line, DiffLine::Insert(_)));
+    }
+    ans
+}
+
+/// Appends the shortest edit script turning the old lines into the new ones to the answer.
+fn diff_range<'a>(old: &[&'a str], new: &[&'a str], ans: &mut Vec<DiffLine<'a>>) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_rest = &old[prefix..old.len() - suffix];
+    let new_rest = &new[prefix..new.len() - suffix];
+
+    ans.extend(old[..prefix].iter().map(|&line| DiffLine::Equal(line)));
+    if old_rest.is_empty() || new_rest.is_empty() {
+        ans.extend(old_rest.iter().map(|&line| DiffLine::Delete(line)));
+        ans.extend(new_rest.iter().map(|&line| DiffLine::Insert(line)));
+    } else {
+        let ((x, y), (u, v)) = middle_snake(old_rest, new_rest);
+        diff_range(&old_rest[..x], &new_rest[..y], ans);
+        let (mut x, mut y) = (x, y);
+        while (x, y) != (u, v) {
+            if x < u && y < v && old_rest[x] == new_rest[y] {
+                ans.push(DiffLine::Equal(old_rest[x]));
+                x += 1;
+                y += 1;
+            } else if u - x > v - y {
+                ans.push(DiffLine::Delete(old_rest[x]));
+                x += 1;
+            } else {
+                ans.push(DiffLine::Insert(new_rest[y]));
+                y += 1;
+            }
+        }
+        diff_range(&old_rest[u..], &new_rest[v..], ans);
+    }
+    ans.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|&line| DiffLine::Equal(line)),
+    );
+}
+
+/// Returns the start and the end of the middle snake of the edit graph: one edit followed or preceded by equal lines.
+/// The first lines and the last lines of the texts must differ, the texts must be non-empty.
+fn middle_snake(old: &[&str], new: &[&str]) -> ((usize, usize), (usize, usize)) {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let delta = n - m;
+    let max = (n + m + 1) / 2;
+    let offset = max + 1;
+    let mut forward = vec![0isize; 2 * offset as usize + 1];
+    let mut backward = vec![0isize; 2 * offset as usize + 1];
+
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let ind = (k + offset) as usize;
+            let (start_x, mut x) = if k == -d || (k != d && forward[ind - 1] < forward[ind + 1]) {
+                (forward[ind + 1], forward[ind + 1])
+            } else {
+                (forward[ind - 1], forward[ind - 1] + 1)
+            };
+            let start_y = start_x - if start_x == x { k + 1 } else { k - 1 };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[ind] = x;
+            let c = delta - k;
+            if delta % 2 != 0 && c.abs() < d && x + backward[(c + offset) as usize] >= n {
+                return (
+                    (start_x as usize, start_y as usize),
+                    (x as usize, y as usize),
+                );
+            }
+        }
+        for c in (-d..=d).step_by(2) {
+            let ind = (c + offset) as usize;
+            let (start_x, mut x) = if c == -d || (c != d && backward[ind - 1] < backward[ind + 1]) {
+                (backward[ind + 1], backward[ind + 1])
+            } else {
+                (backward[ind - 1], backward[ind - 1] + 1)
+            };
+            let start_y = start_x - if start_x == x { c + 1 } else { c - 1 };
+            let mut y = x - c;
+            while x < n && y < m && old[(n - x - 1) as usize] == new[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[ind] = x;
+            let k = delta - c;
+            if delta % 2 == 0 && k.abs() <= d && x + forward[(k + offset) as usize] >= n {
+                return (
+                    ((n - x) as usize, (m - y) as usize),
+                    ((n - start_x) as usize, (m - start_y) as usize),
+                );
+            }
+        }
+    }
+    unreachable!("the paths of the edit graph always overlap")
+}
+
+/// Writes the line of the hunk, the missing newline at the end of the file is marked.
+fn push_line(ans: &mut String, prefix: char, line: &str) {
+    ans.push(prefix);
+    ans.push_str(line);
+    if !line.ends_with('\n') {
+        ans.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// Returns the hunks of the unified diff between two texts.
+fn diff_hunks(old: &str, new: &str, context: usize) -> String {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let lines = diff_lines(&old_lines, &new_lines);
+
+    let changes: Vec<usize> = (0..lines.len())
+        .filter(|&ind| !matches!(lines[ind], DiffLine::Equal(_)))
+        .collect();
+    let mut ranges: Vec<(usize, usize)> = vec![];
+    for &ind in changes.iter() {
+        let start = ind.saturating_sub(context);
+        let end = (ind + context + 1).min(lines.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    let mut ans = String::new();
+    let (mut old_line, mut new_line, mut pos) = (0, 0, 0);
+    for (start, end) in ranges {
+        for line in lines[pos..start].iter() {
+            match line {
+                DiffLine::Equal(_) => {
+                    old_line += 1;
+                    new_line += 1;
+                }
+                DiffLine::Delete(_) => old_line += 1,
+                DiffLine::Insert(_) => new_line += 1,
+            }
+        }
+        let hunk = &lines[start..end];
+        let old_count = hunk
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Insert(_)))
+            .count();
+        let new_count = hunk
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Delete(_)))
+            .count();
+        let old_start = if old_count == 0 {
+            old_line
+        } else {
+            old_line + 1
+        };
+        let new_start = if new_count == 0 {
+            new_line
+        } else {
+            new_line + 1
+        };
+        ans.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_count, new_start, new_count
+        ));
+        for line in hunk.iter() {
+            match line {
+                DiffLine::Equal(text) => push_line(&mut ans, ' ', text),
+                DiffLine::Delete(text) => push_line(&mut ans, '-', text),
+                DiffLine::Insert(text) => push_line(&mut ans, '+', text),
+            }
+        }
+        old_line += old_count;
+        new_line += new_count;
+        pos = end;
+    }
+    ans
+}
+
+/// Returns the unified diff of the file along the given path,
+/// `None` data means that the file doesn't exist on this side.
+///
+/// # Examples
+/// ```
+/// use std::path::Path;
+/// use vcs::utils::diff::unified_diff;
+/// let diff = unified_diff(Path::new("a.txt"), Some(b"a\nb\n"), Some(b"a\nc\n"), 3);
+/// assert!(diff.ends_with("@@ -1,2 +1,2 @@\n a\n-b\n+c\n"));
+/// ```
+pub fn unified_diff(path: &Path, old: Option<&[u8]>, new: Option<&[u8]>, context: usize) -> String {
+    let old_name = match old {
+        Some(_) => format!("a/{}", path.display()),
+        None => "/dev/null".to_string(),
+    };
+    let new_name = match new {
+        Some(_) => format!("b/{}", path.display()),
+        None => "/dev/null".to_string(),
+    };
+    let (old, new) = (old.unwrap_or_default(), new.unwrap_or_default());
+
+    let mut ans = format!("diff a/{} b/{}\n", path.display(), path.display());
+    if is_binary(old) || is_binary(new) {
+        ans.push_str(&format!(
+            "Binary files {} and {} differ\n",
+            old_name, new_name
+        ));
+        return ans;
+    }
+    ans.push_str(&format!("--- {}\n+++ {}\n", old_name, new_name));
+    ans.push_str(&diff_hunks(
+        &String::from_utf8_lossy(old),
+        &String::from_utf8_lossy(new),
+        context,
+    ));
+    ans
+}
+
+/// Colors the unified diff with ANSI escape codes: file headers are bold, hunk headers are cyan,
+/// removed lines are red and added lines are green.
+///
+/// # Examples
+/// ```
+/// use vcs::utils::diff::colorize_diff;
+/// let diff = "diff a/x b/x\n--- a/x\n+++ b/x\n@@ -1 +1 @@\n-a\n+b\n";
+/// assert!(colorize_diff(diff).ends_with("\x1b[31m-a\x1b[0m\n\x1b[32m+b\x1b[0m\n"));
+/// ```
+pub fn colorize_diff(diff: &str) -> String {
+    let mut in_header = false;
+    let mut ans = String::new();
+    for line in diff.split_inclusive('\n') {
+        let text = line.trim_end_matches('\n');
+        if text.starts_with("diff ") {
+            in_header = true;
+        } else if text.starts_with("@@") {
+            in_header = false;
+        }
+        let color = match text.chars().next() {
+            _ if in_header => "1",
+            Some('@') => "36",
+            Some('-') => "31",
+            Some('+') => "32",
+            _ => {
+                ans.push_str(line);
+                continue;
+            }
+        };
+        ans.push_str(&format!(
+            "\x1b[{}m{}\x1b[0m{}",
+            color,
+            text,
+            &line[text.len()..]
+        ));
+    }
+    ans
+}
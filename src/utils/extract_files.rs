@@ -1,81 +1,141 @@
 #![forbid(unsafe_code)]
 
+use super::journal::{
+    finish_rollback, interrupted_operation, rollback_operation, COMMIT_OPERATION,
+};
+use super::migration::{check_version, migrate, remove_legacy_dirs};
+use super::object_store::read_object;
+use super::pack_files::{metadata_path, pack_vcs};
+use super::update_repo::update_repo;
+use super::work_with_refs::{read_branches, read_head, read_tags};
+use super::work_with_trees::flatten_tree;
 use crate::library::commit::Commit;
+use crate::library::error::VcsError;
 use crate::library::files::File;
+use crate::library::ignore::IgnoreRules;
+use crate::library::index::Index;
+use crate::library::tree::EntryMode;
 use crate::library::vcs_repository::VcsRepository;
+use serde_json::Value;
 use std::collections::VecDeque;
 use std::fs;
-use std::io::BufReader;
 use std::path::{Path, PathBuf};
 
-/// Extract files from the given dir except for directory ".vcs".
-pub fn files_from_dir(dir_root: &Path) -> Result<Vec<File>, &'static str> {
+/// Extract files from the given dir except for directory ".vcs" and files ignored by ".vcsignore".
+/// Paths of the files are relative to the given dir.
+pub fn files_from_dir(dir_root: &Path) -> Result<Vec<File>, VcsError> {
     let mut ans: Vec<File> = vec![];
+    let mut rules = IgnoreRules::for_repo(dir_root)?;
     let mut dirs: VecDeque<PathBuf> = VecDeque::new();
     dirs.push_back(dir_root.to_path_buf());
 
-    while !dirs.is_empty() {
-        let dir = dirs.pop_front().unwrap();
+    while let Some(dir) = dirs.pop_front() {
         if dir.ends_with(".vcs") {
             continue;
         }
         if !dir.is_dir() {
-            return Err("Given not a directory");
+            return Err(VcsError::NotADirectory(dir));
         }
-        for entry in fs::read_dir(dir).unwrap() {
-            let entry = entry.expect("Path doesn't exist");
-            let path = entry.path();
+        rules.add_file(dir_root, dir.strip_prefix(dir_root).unwrap_or(&dir))?;
+        for entry in fs::read_dir(&dir).map_err(|e| VcsError::io(&dir, e))? {
+            let path = entry.map_err(|e| VcsError::io(&dir, e))?.path();
             if path.ends_with(".vcs") {
                 continue;
             }
-            if path.is_dir() {
+            let relative_path = path.strip_prefix(dir_root).unwrap_or(&path);
+            let is_dir = path.is_dir();
+            if rules.is_ignored(relative_path, is_dir) {
+                continue;
+            }
+            if is_dir {
                 dirs.push_back(path);
             } else {
-                ans.push(File::init(&path))
+                let data = fs::read(&path).map_err(|e| VcsError::io(&path, e))?;
+                ans.push(File::from_data(relative_path, data));
             }
         }
     }
     Ok(ans)
 }
 
-fn read_from_json<P: AsRef<Path>>(path: P) -> Result<File, &'static str> {
-    let file = fs::File::open(path).expect("No file with this name");
-    let reader = BufReader::new(file);
-    let f = serde_json::from_reader(reader).expect("Can't read file");
-    Ok(f)
-}
-
-
-/// Extract the commit files.
-pub fn files_from_commit(commit: &Commit) -> Result<Vec<File>, &'static str> {
-    let dir = commit.get_dir_commit();
+/// Extract the commit files with their relative paths restored, reading their data from the object store.
+pub fn files_from_commit(root_dir: &Path, commit: &Commit) -> Result<Vec<File>, VcsError> {
     let mut ans: Vec<File> = vec![];
 
-    if !dir.exists() {
-        return Err("No dir with this path-commit");
+    for (path, (mode, hash)) in flatten_tree(root_dir, commit.get_tree_hash())? {
+        if mode == EntryMode::Directory {
+            continue;
+        }
+        let data = read_object(root_dir, &hash)?;
+        ans.push(File::from_data(&path, data));
     }
+    Ok(ans)
+}
 
-    if !dir.is_dir() {
-        return Err("Path-commit is not a directory");
-    }
+/// Extract the staged files with their data from the object store.
+pub fn files_from_index(root_dir: &Path, index: &Index) -> Result<Vec<File>, VcsError> {
+    let mut ans: Vec<File> = vec![];
 
-    for entry in fs::read_dir(&dir).unwrap() {
-        let entry = entry.expect("Path doesn't exist");
-        let path = entry.path();
-        ans.push(read_from_json(path).unwrap());
+    for (path, (mode, hash)) in index.get_entries().iter() {
+        if *mode == EntryMode::Directory {
+            continue;
+        }
+        let data = read_object(root_dir, hash)?;
+        ans.push(File::from_data(path, data));
     }
     Ok(ans)
 }
 
-fn read_vcs_from_json(path: PathBuf) -> Result<VcsRepository, &'static str> {
-    let file = fs::File::open(path).expect("No file with this path");
-    let reader = BufReader::new(file);
-    let vcs = serde_json::from_reader(reader).expect("Can't read file");
+fn read_vcs_from_json(path: &Path) -> Result<Value, VcsError> {
+    let data = fs::read(path).map_err(|e| VcsError::io(path, e))?;
+    serde_json::from_slice(&data).map_err(|e| VcsError::json(path, e))
+}
+
+/// Reads the references of the repository into the migrated buffer and checks them.
+fn load_vcs(root_path: &Path, metadata: Value) -> Result<VcsRepository, VcsError> {
+    let mut vcs: VcsRepository = serde_json::from_value(metadata)
+        .map_err(|e| VcsError::json(&metadata_path(root_path), e))?;
+    vcs.set_dir(root_path);
+    vcs.set_refs(read_branches(root_path)?, read_head(root_path)?);
+    vcs.set_tags(read_tags(root_path)?);
+    vcs.check_consistency()?;
     Ok(vcs)
 }
 
-/// Read buffer of VCS repository.
-pub fn read_vcs(root_path: PathBuf) -> Result<VcsRepository, &'static str> {
-    let vcs_dir = root_path.join(".vcs").join("VCSRepository.json");
-    read_vcs_from_json(vcs_dir)
+/// Read buffer of VCS repository located in the given root directory, the repository should be locked exclusively.
+/// Buffers written in the older formats are migrated to the current one.
+/// An operation interrupted before it was finished is rolled back.
+pub fn read_vcs(root_path: PathBuf) -> Result<VcsRepository, VcsError> {
+    let rolled_back = rollback_operation(&root_path)?;
+    let (metadata, migrated, legacy_dirs) =
+        migrate(&root_path, read_vcs_from_json(&metadata_path(&root_path))?)?;
+
+    let vcs = load_vcs(&root_path, metadata)?;
+    if migrated {
+        // The old buffer needs the legacy directories until the new one replaces it.
+        pack_vcs(&root_path, &vcs)?;
+        remove_legacy_dirs(&legacy_dirs)?;
+    }
+    if let Some(operation) = rolled_back {
+        // A commit changes neither the working tree nor the index, the staged changes are kept.
+        if operation != COMMIT_OPERATION {
+            update_repo(&vcs)?;
+        }
+        vcs.collect_garbage()?;
+        finish_rollback(&root_path)?;
+    }
+    Ok(vcs)
+}
+
+/// Read buffer of VCS repository located in the given root directory without changing anything,
+/// the repository should be locked at least in the shared mode.
+/// Fails if an operation was interrupted or the buffer is written in an older format,
+/// they are rolled back and migrated only by `read_vcs`.
+pub fn read_vcs_shared(root_path: PathBuf) -> Result<VcsRepository, VcsError> {
+    if let Some(operation) = interrupted_operation(&root_path)? {
+        return Err(VcsError::InterruptedOperation(operation));
+    }
+    let metadata = read_vcs_from_json(&metadata_path(&root_path))?;
+    check_version(&metadata)?;
+    load_vcs(&root_path, metadata)
 }
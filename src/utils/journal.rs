@@ -0,0 +1,106 @@
+#![forbid(unsafe_code)]
+
+use super::atomic_write::write_atomically;
+use super::pack_files::{metadata_path, pack_vcs};
+use super::work_with_refs::{write_branches, write_head};
+use crate::library::branch::Branch;
+use crate::library::error::VcsError;
+use crate::library::head::Head;
+use crate::library::vcs_repository::VcsRepository;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+///
+/// This is a struct for the journal of the operation in progress,
+/// it keeps the buffer and the references of the VCS written before the operation started.
+#[derive(Serialize, Deserialize)]
+struct Journal {
+    operation: String,
+    metadata: Value,
+    branches: BTreeMap<String, String>,
+    head: Head,
+}
+
+/// The name of the operation creating a commit, rolling it back keeps the working tree and the index.
+pub const COMMIT_OPERATION: &str = "commit";
+
+/// Returns the path of the journal.
+pub fn journal_path(root_dir: &Path) -> PathBuf {
+    root_dir.join(".vcs").join("journal")
+}
+
+/// Starts the operation with the given name: the current state of the VCS is kept in the journal
+/// until the operation is finished.
+pub fn begin_operation(vcs: &VcsRepository, operation: &str) -> Result<(), VcsError> {
+    let path = journal_path(vcs.get_dir());
+    let journal = Journal {
+        operation: operation.to_string(),
+        metadata: serde_json::to_value(vcs).map_err(|e| VcsError::json(&path, e))?,
+        branches: vcs
+            .get_branches()
+            .iter()
+            .map(|branch| {
+                (
+                    branch.get_name().to_string(),
+                    branch.get_commit_hash().to_string(),
+                )
+            })
+            .collect(),
+        head: vcs.get_head().clone(),
+    };
+    let data = serde_json::to_vec(&journal).map_err(|e| VcsError::json(&path, e))?;
+    write_atomically(&path, &data)
+}
+
+/// Finishes the operation: buffers the new state of the VCS and removes the journal.
+pub fn finish_operation(vcs: &VcsRepository) -> Result<(), VcsError> {
+    pack_vcs(vcs.get_dir(), vcs)?;
+    let path = journal_path(vcs.get_dir());
+    fs::remove_file(&path).map_err(|e| VcsError::io(&path, e))
+}
+
+fn read_journal(root_dir: &Path) -> Result<Option<Journal>, VcsError> {
+    let path = journal_path(root_dir);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read(&path).map_err(|e| VcsError::io(&path, e))?;
+    serde_json::from_slice(&data)
+        .map(Some)
+        .map_err(|e| VcsError::json(&path, e))
+}
+
+/// Returns the name of the interrupted operation without rolling it back, if there is one.
+pub fn interrupted_operation(root_dir: &Path) -> Result<Option<String>, VcsError> {
+    Ok(read_journal(root_dir)?.map(|journal| journal.operation))
+}
+
+/// Restores the buffer of the VCS kept in the journal of the interrupted operation.
+/// Returns the name of the rolled back operation, if there was one.
+/// The journal itself is removed by `finish_rollback` once the working tree is restored.
+pub fn rollback_operation(root_dir: &Path) -> Result<Option<String>, VcsError> {
+    let path = journal_path(root_dir);
+    let Some(journal) = read_journal(root_dir)? else {
+        return Ok(None);
+    };
+    let metadata = serde_json::to_vec(&journal.metadata).map_err(|e| VcsError::json(&path, e))?;
+    write_atomically(&metadata_path(root_dir), &metadata)?;
+    let branches: Vec<Branch> = journal
+        .branches
+        .iter()
+        .map(|(name, hash)| Branch::init(name, hash))
+        .collect();
+    write_branches(root_dir, &branches)?;
+    write_head(root_dir, &journal.head)?;
+    Ok(Some(journal.operation))
+}
+
+/// Removes the journal of the rolled back operation.
+pub fn finish_rollback(root_dir: &Path) -> Result<(), VcsError> {
+    let path = journal_path(root_dir);
+    fs::remove_file(&path).map_err(|e| VcsError::io(&path, e))
+}
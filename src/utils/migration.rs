@@ -0,0 +1,337 @@
+#![forbid(unsafe_code)]
+
+use super::object_store::put_object;
+use super::operation_hash::calculate_hash;
+use super::work_with_refs::{read_branches, read_head, write_branches, write_head};
+use super::work_with_trees::write_tree;
+use crate::library::branch::Branch;
+use crate::library::commit::Commit;
+use crate::library::config::DEFAULT_BRANCH;
+use crate::library::error::VcsError;
+use crate::library::head::Head;
+use crate::library::tree::{EntryMode, FlatTree};
+use crate::library::vcs_repository::FORMAT_VERSION;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+///
+/// This is a struct for files of the commits written before the object store,
+/// they kept the data and the absolute path of the file.
+#[derive(Deserialize)]
+struct LegacyFile {
+    name: String,
+    data: Vec<u8>,
+    path: PathBuf,
+}
+
+/// Returns the error for the buffer which doesn't match the legacy format.
+fn broken_metadata() -> VcsError {
+    VcsError::CorruptedRepository("broken repository metadata".to_string())
+}
+
+/// Returns the key of the commit in the buffer: legacy hashes are numbers.
+fn commit_key(hash: &Value) -> Result<String, VcsError> {
+    match hash {
+        Value::Number(number) => Ok(number.to_string()),
+        Value::String(string) => Ok(string.clone()),
+        _ => Err(broken_metadata()),
+    }
+}
+
+/// Puts the files of the legacy commit to the object store and returns the hash of their tree.
+fn legacy_commit_tree(
+    root_dir: &Path,
+    old_hash: &str,
+    old_commit: &Value,
+) -> Result<String, VcsError> {
+    let old_root = PathBuf::from(old_commit["root_path"].as_str().unwrap_or_default());
+    let dir = root_dir.join(".vcs").join("objects").join(old_hash);
+
+    let mut flat_tree = FlatTree::new();
+    for entry in fs::read_dir(&dir).map_err(|e| VcsError::io(&dir, e))? {
+        let path = entry.map_err(|e| VcsError::io(&dir, e))?.path();
+        let data = fs::read(&path).map_err(|e| VcsError::io(&path, e))?;
+        let file: LegacyFile =
+            serde_json::from_slice(&data).map_err(|e| VcsError::json(&path, e))?;
+
+        let relative_path = match file.path.strip_prefix(&old_root) {
+            Ok(path) => path.to_path_buf(),
+            Err(_) => PathBuf::from(&file.name),
+        };
+        let hash = put_object(root_dir, &file.data)?;
+        flat_tree.insert(relative_path, (EntryMode::File, hash));
+    }
+    write_tree(root_dir, &flat_tree)
+}
+
+/// Converts the commit with the given old hash and its ancestors, returns the new hash.
+fn convert_commit(
+    root_dir: &Path,
+    old_hash: &str,
+    old_commits: &Map<String, Value>,
+    new_hashes: &mut HashMap<String, String>,
+    new_commits: &mut Map<String, Value>,
+) -> Result<String, VcsError> {
+    if let Some(hash) = new_hashes.get(old_hash) {
+        return Ok(hash.clone());
+    }
+    let old_commit = old_commits.get(old_hash).ok_or_else(broken_metadata)?;
+
+    let parents = match &old_commit["parent"] {
+        Value::Null => vec![],
+        parent => vec![Value::String(convert_commit(
+            root_dir,
+            &commit_key(parent)?,
+            old_commits,
+            new_hashes,
+            new_commits,
+        )?)],
+    };
+    let tree = match old_commit.get("tree") {
+        Some(tree) => tree.clone(),
+        None => Value::String(legacy_commit_tree(root_dir, old_hash, old_commit)?),
+    };
+
+    let commit: Commit = serde_json::from_value(json!({
+        "tree": tree,
+        "parents": parents,
+        "message": old_commit["message"],
+        "is_first_in_branch": old_commit["is_first_in_branch"],
+        "date": old_commit["date"],
+    }))
+    .map_err(|_| broken_metadata())?;
+
+    let hash = calculate_hash(&commit);
+    new_hashes.insert(old_hash.to_string(), hash.clone());
+    new_commits.insert(
+        hash.clone(),
+        serde_json::to_value(commit).map_err(|_| broken_metadata())?,
+    );
+    Ok(hash)
+}
+
+/// Returns the converted copy of the commit stored in the buffer outside of the commit list.
+fn convert_commit_copy(
+    commit: &Value,
+    old_commits: &Map<String, Value>,
+    new_hashes: &HashMap<String, String>,
+    new_commits: &Map<String, Value>,
+) -> Result<Value, VcsError> {
+    for (old_hash, old_commit) in old_commits.iter() {
+        if old_commit == commit {
+            return Ok(new_commits[&new_hashes[old_hash]].clone());
+        }
+    }
+    Err(broken_metadata())
+}
+
+/// Migrates the buffer with absolute paths to relative ones.
+/// Commits written before the object store are copied there, their old directories
+/// are added to `legacy_dirs` to be removed once the migrated buffer is written.
+fn migrate_to_relative_paths(
+    root_dir: &Path,
+    metadata: &Value,
+    legacy_dirs: &mut Vec<PathBuf>,
+) -> Result<Value, VcsError> {
+    let old_commits = metadata["all_commits"]
+        .as_object()
+        .ok_or_else(broken_metadata)?;
+
+    let mut new_hashes = HashMap::new();
+    let mut new_commits = Map::new();
+    for old_hash in old_commits.keys() {
+        convert_commit(
+            root_dir,
+            old_hash,
+            old_commits,
+            &mut new_hashes,
+            &mut new_commits,
+        )?;
+    }
+
+    let mut branches = vec![];
+    for branch in metadata["branches"]
+        .as_array()
+        .ok_or_else(broken_metadata)?
+    {
+        branches.push(json!({
+            "first_commit": convert_commit_copy(&branch["first_commit"], old_commits, &new_hashes, &new_commits)?,
+            "last_commit": convert_commit_copy(&branch["last_commit"], old_commits, &new_hashes, &new_commits)?,
+            "name": branch["name"],
+        }));
+    }
+    let current_commit = convert_commit_copy(
+        &metadata["current_commit"],
+        old_commits,
+        &new_hashes,
+        &new_commits,
+    )?;
+
+    for (old_hash, old_commit) in old_commits.iter() {
+        let dir = root_dir.join(".vcs").join("objects").join(old_hash);
+        if old_commit.get("tree").is_none() && dir.is_dir() {
+            legacy_dirs.push(dir);
+        }
+    }
+
+    Ok(json!({
+        "version": 1,
+        "branches": branches,
+        "current_branch_id": metadata["current_branch_id"],
+        "all_commits": new_commits,
+        "current_commit": current_commit,
+    }))
+}
+
+/// Replaces the single optional parent of the commit with the list of parents.
+fn convert_parent_to_list(commit: &mut Value) -> Result<(), VcsError> {
+    let commit = commit.as_object_mut().ok_or_else(broken_metadata)?;
+    if let Some(parent) = commit.remove("parent") {
+        let parents = match parent {
+            Value::Null => vec![],
+            parent => vec![parent],
+        };
+        commit.insert("parents".to_string(), Value::Array(parents));
+    }
+    Ok(())
+}
+
+/// Migrates the buffer with single parents of the commits to the lists of parents.
+/// The hashes of the commits are not changed.
+fn migrate_to_parent_lists(metadata: Value) -> Result<Value, VcsError> {
+    let mut metadata = metadata;
+    for commit in metadata["all_commits"]
+        .as_object_mut()
+        .ok_or_else(broken_metadata)?
+        .values_mut()
+    {
+        convert_parent_to_list(commit)?;
+    }
+    for branch in metadata["branches"]
+        .as_array_mut()
+        .ok_or_else(broken_metadata)?
+    {
+        convert_parent_to_list(&mut branch["first_commit"])?;
+        convert_parent_to_list(&mut branch["last_commit"])?;
+    }
+    convert_parent_to_list(&mut metadata["current_commit"])?;
+
+    metadata["version"] = json!(2);
+    Ok(metadata)
+}
+
+/// Returns the hash of the commit stored in the buffer.
+fn buffered_commit_hash(commit: &Value) -> Result<String, VcsError> {
+    let commit: Commit = serde_json::from_value(commit.clone()).map_err(|_| broken_metadata())?;
+    Ok(calculate_hash(&commit))
+}
+
+/// Migrates the buffer with the branches keeping copies of the commits to the references:
+/// the branches are written to ".vcs/refs/heads" and the current position to ".vcs/HEAD".
+fn migrate_to_refs(root_dir: &Path, metadata: Value) -> Result<Value, VcsError> {
+    let mut branches = vec![];
+    for branch in metadata["branches"]
+        .as_array()
+        .ok_or_else(broken_metadata)?
+    {
+        let name = branch["name"].as_str().ok_or_else(broken_metadata)?;
+        branches.push(Branch::init(
+            name,
+            &buffered_commit_hash(&branch["last_commit"])?,
+        ));
+    }
+    let current_branch = metadata["current_branch_id"]
+        .as_u64()
+        .and_then(|id| branches.get(id as usize))
+        .ok_or_else(broken_metadata)?;
+    let current_hash = buffered_commit_hash(&metadata["current_commit"])?;
+    let head = match current_branch.get_commit_hash() == current_hash {
+        true => Head::Branch(current_branch.get_name().to_string()),
+        false => Head::Detached(current_hash),
+    };
+
+    write_branches(root_dir, &branches)?;
+    write_head(root_dir, &head)?;
+    Ok(json!({
+        "version": 3,
+        "all_commits": metadata["all_commits"],
+    }))
+}
+
+/// Migrates the buffer to the one recording the default branch: it is "master" if there is
+/// such a branch, otherwise the current branch or the first branch by name.
+fn migrate_to_default_branch(root_dir: &Path, metadata: Value) -> Result<Value, VcsError> {
+    let mut metadata = metadata;
+    let branches = read_branches(root_dir)?;
+    let has_master = branches
+        .iter()
+        .any(|branch| branch.get_name() == DEFAULT_BRANCH);
+    let default_branch = match (has_master, read_head(root_dir)?) {
+        (true, _) => DEFAULT_BRANCH.to_string(),
+        (false, Head::Branch(name)) => name,
+        (false, Head::Detached(_)) => branches
+            .first()
+            .map_or(DEFAULT_BRANCH, |branch| branch.get_name())
+            .to_string(),
+    };
+    metadata["default_branch"] = json!(default_branch);
+    metadata["version"] = json!(5);
+    Ok(metadata)
+}
+
+/// Checks that the buffer of the repository is written in the current format version,
+/// so it can be read without a migration.
+pub fn check_version(metadata: &Value) -> Result<(), VcsError> {
+    let version = metadata["version"].as_u64().unwrap_or(0);
+    if version > FORMAT_VERSION as u64 {
+        return Err(VcsError::UnsupportedVersion(version as u32));
+    }
+    if version < FORMAT_VERSION as u64 {
+        return Err(VcsError::OutdatedVersion(version as u32));
+    }
+    Ok(())
+}
+
+/// Migrates the buffer of the repository to the current format version.
+/// Returns the migrated buffer, whether it was changed and the legacy directories which are
+/// still needed by the old buffer: they are removed by `remove_legacy_dirs` once the new one is written.
+pub fn migrate(root_dir: &Path, metadata: Value) -> Result<(Value, bool, Vec<PathBuf>), VcsError> {
+    let version = metadata["version"].as_u64().unwrap_or(0);
+    if version > FORMAT_VERSION as u64 {
+        return Err(VcsError::UnsupportedVersion(version as u32));
+    }
+
+    let mut metadata = metadata;
+    let mut legacy_dirs = vec![];
+    if version < 1 {
+        metadata = migrate_to_relative_paths(root_dir, &metadata, &mut legacy_dirs)?;
+    }
+    if version < 2 {
+        metadata = migrate_to_parent_lists(metadata)?;
+    }
+    if version < 3 {
+        metadata = migrate_to_refs(root_dir, metadata)?;
+    }
+    if version < 4 {
+        // The commits written before the identities have no author and committer,
+        // their hashes are not changed.
+        metadata["version"] = json!(4);
+    }
+    if version < 5 {
+        metadata = migrate_to_default_branch(root_dir, metadata)?;
+    }
+    Ok((metadata, version < FORMAT_VERSION as u64, legacy_dirs))
+}
+
+/// Removes the directories of the legacy commits returned by `migrate`.
+pub fn remove_legacy_dirs(legacy_dirs: &[PathBuf]) -> Result<(), VcsError> {
+    for dir in legacy_dirs.iter() {
+        if dir.is_dir() {
+            fs::remove_dir_all(dir).map_err(|e| VcsError::io(dir, e))?;
+        }
+    }
+    Ok(())
+}
@@ -1,10 +1,21 @@
 #![forbid(unsafe_code)]
 
+pub mod atomic_write;
 pub mod comand_parser;
 pub mod delete_files;
+pub mod diff;
 pub mod extract_files;
+pub mod journal;
+pub mod migration;
+pub mod object_store;
 pub mod operation_hash;
 pub mod pack_files;
 pub mod print_files;
+pub mod three_way_merge;
 pub mod update_repo;
 pub mod work_with_commit_files;
+pub mod work_with_config;
+pub mod work_with_index;
+pub mod work_with_merge_state;
+pub mod work_with_refs;
+pub mod work_with_trees;
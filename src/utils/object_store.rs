@@ -0,0 +1,85 @@
+#![forbid(unsafe_code)]
+
+use super::atomic_write::write_atomically;
+use crate::library::error::VcsError;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Calculates SHA-256 hash of the given data and returns it as a hex string.
+pub fn hash_data(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Returns the directory of the object store.
+pub fn objects_dir(root_dir: &Path) -> PathBuf {
+    root_dir.join(".vcs").join("objects")
+}
+
+/// Returns the path to the object with the given hash.
+pub fn object_path(root_dir: &Path, hash: &str) -> PathBuf {
+    let prefix = hash.get(..2).unwrap_or_default();
+    let rest = hash.get(2..).unwrap_or_default();
+    objects_dir(root_dir).join(prefix).join(rest)
+}
+
+/// Puts the given data to the object store and returns its hash.
+/// The data which is already in the store is not written again.
+pub fn put_object(root_dir: &Path, data: &[u8]) -> Result<String, VcsError> {
+    let hash = hash_data(data);
+    let path = object_path(root_dir, &hash);
+    if path.exists() {
+        return Ok(hash);
+    }
+
+    let dir = objects_dir(root_dir).join(&hash[..2]);
+    fs::create_dir_all(&dir).map_err(|e| VcsError::io(&dir, e))?;
+    write_atomically(&path, data)?;
+    Ok(hash)
+}
+
+/// Reads the object with the given hash from the object store.
+pub fn read_object(root_dir: &Path, hash: &str) -> Result<Vec<u8>, VcsError> {
+    if hash.len() <= 2 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(VcsError::NoObject(hash.to_string()));
+    }
+    let path = object_path(root_dir, hash);
+    if !path.is_file() {
+        return Err(VcsError::NoObject(hash.to_string()));
+    }
+    fs::read(&path).map_err(|e| VcsError::io(&path, e))
+}
+
+/// Deletes all objects whose hashes are not in the given set.
+pub fn remove_unreferenced(root_dir: &Path, referenced: &HashSet<String>) -> Result<(), VcsError> {
+    let dir = objects_dir(root_dir);
+    if !dir.exists() {
+        return Ok(());
+    }
+
+    for fan_out in fs::read_dir(&dir).map_err(|e| VcsError::io(&dir, e))? {
+        let fan_out = fan_out.map_err(|e| VcsError::io(&dir, e))?;
+        let prefix = fan_out.file_name().to_string_lossy().to_string();
+        let fan_out = fan_out.path();
+        for entry in fs::read_dir(&fan_out).map_err(|e| VcsError::io(&fan_out, e))? {
+            let entry = entry.map_err(|e| VcsError::io(&fan_out, e))?;
+            let path = entry.path();
+            let hash = prefix.clone() + &entry.file_name().to_string_lossy();
+            if !referenced.contains(&hash) {
+                fs::remove_file(&path).map_err(|e| VcsError::io(&path, e))?;
+            }
+        }
+        if fs::read_dir(&fan_out)
+            .map_err(|e| VcsError::io(&fan_out, e))?
+            .next()
+            .is_none()
+        {
+            fs::remove_dir(&fan_out).map_err(|e| VcsError::io(&fan_out, e))?;
+        }
+    }
+    Ok(())
+}
@@ -1,12 +1,9 @@
 #![forbid(unsafe_code)]
 
-use std::collections::hash_map::DefaultHasher;
-use std::hash::Hash;
-use std::hash::Hasher;
+use super::object_store::hash_data;
+use crate::library::commit::Commit;
 
-/// Calculates hash of the given object.
-pub fn calculate_hash<T: Hash>(t: &T) -> u64 {
-    let mut s = DefaultHasher::new();
-    t.hash(&mut s);
-    s.finish()
+/// Calculates hash of the given commit: SHA-256 of its canonical form as a hex string.
+pub fn calculate_hash(commit: &Commit) -> String {
+    hash_data(commit.get_canonical_form().as_bytes())
 }
@@ -2,45 +2,33 @@
 
 use super::extract_files::{files_from_commit, files_from_dir};
 use super::work_with_commit_files::all_changed_files;
+use crate::library::error::VcsError;
 use crate::library::vcs_repository::VcsRepository;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
-/// Prints uncommitted files
-pub fn print_uncommitted_files(vcs: &VcsRepository) -> Result<(), &'static str> {
+/// Returns the paths of the files changed since the current commit.
+pub fn uncommitted_files(vcs: &VcsRepository) -> Result<Vec<PathBuf>, VcsError> {
     let repo_files = files_from_dir(vcs.get_dir())?;
-    let commit_files = files_from_commit(vcs.get_current_commit())?;
+    let commit_files = files_from_commit(vcs.get_dir(), vcs.get_current_commit())?;
 
-    let files = all_changed_files(&repo_files, &commit_files);
-    for path in files.iter() {
-        println!("  {}", path.strip_prefix(vcs.get_dir()).unwrap().display());
-    }
-
-    Ok(())
+    Ok(all_changed_files(&repo_files, &commit_files))
 }
 
-/// Prints paths of changed files.
-pub fn print_changed_paths(
-    added: &Vec<PathBuf>,
-    modified: &Vec<PathBuf>,
-    deleted: &Vec<PathBuf>,
-    root_dir: &Path,
-) {
+/// Returns the lines with the paths of changed files.
+pub fn format_changed_paths(
+    added: &[PathBuf],
+    modified: &[PathBuf],
+    deleted: &[PathBuf],
+) -> String {
+    let mut ans = String::new();
     for path in added.iter() {
-        println!(
-            "  added: {}",
-            path.strip_prefix(root_dir).unwrap().display()
-        );
+        ans.push_str(&format!("  added: {}\n", path.display()));
     }
     for path in modified.iter() {
-        println!(
-            "  modified: {}",
-            path.strip_prefix(root_dir).unwrap().display()
-        );
+        ans.push_str(&format!("  modified: {}\n", path.display()));
     }
     for path in deleted.iter() {
-        println!(
-            "  deleted: {}",
-            path.strip_prefix(root_dir).unwrap().display()
-        );
+        ans.push_str(&format!("  deleted: {}\n", path.display()));
     }
+    ans
 }
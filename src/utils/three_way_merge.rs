@@ -0,0 +1,345 @@
+#![forbid(unsafe_code)]
+
+use super::diff::{diff_lines, is_binary, DiffLine};
+use super::object_store::{put_object, read_object};
+use crate::library::error::VcsError;
+use crate::library::tree::{EntryMode, FlatTree};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Marker starting the version of the current branch in the conflict.
+pub const CONFLICT_START: &str = "<<<<<<<";
+/// Marker separating two versions in the conflict.
+pub const CONFLICT_SEPARATOR: &str = "=======";
+/// Marker ending the version of the merged branch in the conflict.
+pub const CONFLICT_END: &str = ">>>>>>>";
+
+///
+/// This is a struct for the change of one side: the lines `start..end` of the ancestor are replaced.
+struct Change<'a> {
+    start: usize,
+    end: usize,
+    lines: Vec<&'a str>,
+}
+
+/// Returns the changes turning the ancestor lines into the given ones.
+fn changes<'a>(ancestor: &[&'a str], lines: &[&'a str]) -> Vec<Change<'a>> {
+    let mut ans: Vec<Change> = vec![];
+    let mut pos = 0;
+    let mut last_is_change = false;
+    for line in diff_lines(ancestor, lines) {
+        match line {
+            DiffLine::Equal(_) => {
+                pos += 1;
+                last_is_change = false;
+                continue;
+            }
+            DiffLine::Delete(_) | DiffLine::Insert(_) => {
+                if !last_is_change {
+                    ans.push(Change {
+                        start: pos,
+                        end: pos,
+                        lines: vec![],
+                    });
+                }
+                last_is_change = true;
+            }
+        }
+        if let Some(change) = ans.last_mut() {
+            match line {
+                DiffLine::Delete(_) => {
+                    pos += 1;
+                    change.end = pos;
+                }
+                DiffLine::Insert(text) => change.lines.push(text),
+                DiffLine::Equal(_) => {}
+            }
+        }
+    }
+    ans
+}
+
+/// Returns the lines `start..end` of the ancestor with the given changes applied.
+fn apply_changes<'a>(
+    ancestor: &[&'a str],
+    start: usize,
+    end: usize,
+    changes: &[&Change<'a>],
+) -> Vec<&'a str> {
+    let mut ans = vec![];
+    let mut pos = start;
+    for change in changes.iter() {
+        ans.extend_from_slice(&ancestor[pos..change.start]);
+        ans.extend_from_slice(&change.lines);
+        pos = change.end;
+    }
+    ans.extend_from_slice(&ancestor[pos..end]);
+    ans
+}
+
+/// Writes the lines of one side of the conflict, the last one is ended with a newline.
+fn push_conflict_side(ans: &mut String, lines: &[&str]) {
+    for line in lines.iter() {
+        ans.push_str(line);
+    }
+    if !ans.is_empty() && !ans.ends_with('\n') {
+        ans.push('\n');
+    }
+}
+
+/// Merges the changes of two texts made since their common ancestor.
+/// Overlapping different changes are written between conflict markers.
+/// Returns the merged text and whether there are conflicts.
+///
+/// # Examples
+/// ```
+/// use vcs::utils::three_way_merge::merge_texts;
+/// let (text, conflict) = merge_texts("a\nb\nc\n", "A\nb\nc\n", "a\nb\nC\n", "master", "branch");
+/// assert_eq!(text, "A\nb\nC\n");
+/// assert!(!conflict);
+/// ```
+pub fn merge_texts(
+    ancestor: &str,
+    ours: &str,
+    theirs: &str,
+    ours_name: &str,
+    theirs_name: &str,
+) -> (String, bool) {
+    let ancestor: Vec<&str> = ancestor.split_inclusive('\n').collect();
+    let ours: Vec<&str> = ours.split_inclusive('\n').collect();
+    let theirs: Vec<&str> = theirs.split_inclusive('\n').collect();
+    let our_changes = changes(&ancestor, &ours);
+    let their_changes = changes(&ancestor, &theirs);
+
+    let mut ans = String::new();
+    let mut has_conflicts = false;
+    let (mut pos, mut i, mut j) = (0, 0, 0);
+    while i < our_changes.len() || j < their_changes.len() {
+        let take_ours = j >= their_changes.len()
+            || (i < our_changes.len() && our_changes[i].start <= their_changes[j].start);
+        let first = if take_ours {
+            &our_changes[i]
+        } else {
+            &their_changes[j]
+        };
+        let (start, mut end) = (first.start, first.end);
+        let mut our_group: Vec<&Change> = vec![];
+        let mut their_group: Vec<&Change> = vec![];
+
+        loop {
+            let overlaps = |change: &Change| {
+                change.start < end
+                    || (change.start == end && (change.start == change.end || start == end))
+            };
+            if i < our_changes.len() && overlaps(&our_changes[i]) {
+                end = end.max(our_changes[i].end);
+                our_group.push(&our_changes[i]);
+                i += 1;
+            } else if j < their_changes.len() && overlaps(&their_changes[j]) {
+                end = end.max(their_changes[j].end);
+                their_group.push(&their_changes[j]);
+                j += 1;
+            } else {
+                break;
+            }
+        }
+
+        for line in ancestor[pos..start].iter() {
+            ans.push_str(line);
+        }
+        let our_lines = apply_changes(&ancestor, start, end, &our_group);
+        let their_lines = apply_changes(&ancestor, start, end, &their_group);
+        if their_group.is_empty() || our_lines == their_lines {
+            our_lines.iter().for_each(|line| ans.push_str(line));
+        } else if our_group.is_empty() {
+            their_lines.iter().for_each(|line| ans.push_str(line));
+        } else {
+            has_conflicts = true;
+            push_conflict_side(&mut ans, &[]);
+            ans.push_str(&format!("{} {}\n", CONFLICT_START, ours_name));
+            push_conflict_side(&mut ans, &our_lines);
+            ans.push_str(&format!("{}\n", CONFLICT_SEPARATOR));
+            push_conflict_side(&mut ans, &their_lines);
+            ans.push_str(&format!("{} {}\n", CONFLICT_END, theirs_name));
+        }
+        pos = end;
+    }
+    for line in ancestor[pos..].iter() {
+        ans.push_str(line);
+    }
+    (ans, has_conflicts)
+}
+
+/// Checks that the text contains a conflict: the start, separator and end markers
+/// at the beginning of the lines in this order. A lone marker such as a line of "=" is not a conflict.
+pub fn has_conflict_markers(data: &[u8]) -> bool {
+    let mut expected = [CONFLICT_START, CONFLICT_SEPARATOR, CONFLICT_END].into_iter();
+    let mut marker = expected.next();
+    for line in String::from_utf8_lossy(data).lines() {
+        let Some(current) = marker else {
+            break;
+        };
+        let found = if current == CONFLICT_SEPARATOR {
+            line == current
+        } else {
+            line.starts_with(current)
+        };
+        if found {
+            marker = expected.next();
+        }
+    }
+    marker.is_none()
+}
+
+///
+/// This is an enum for the conflicts of one entry.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum EntryConflict {
+    /// The changes are merged.
+    None,
+    /// The conflicting text changes are written between the conflict markers.
+    Markers,
+    /// The version of `ours` is kept, the conflict can't be shown in the file.
+    Unresolved,
+}
+
+/// Merges the entry changed on both sides, returns the merged entry and its conflict.
+fn merge_entry(
+    root_dir: &Path,
+    ancestor: Option<&(EntryMode, String)>,
+    ours: &(EntryMode, String),
+    theirs: &(EntryMode, String),
+    ours_name: &str,
+    theirs_name: &str,
+) -> Result<((EntryMode, String), EntryConflict), VcsError> {
+    if ours.0 == EntryMode::Directory || theirs.0 == EntryMode::Directory {
+        return Ok((ours.clone(), EntryConflict::Unresolved));
+    }
+    let ancestor_data = match ancestor {
+        Some((EntryMode::Directory, _)) | None => vec![],
+        Some((_, hash)) => read_object(root_dir, hash)?,
+    };
+    let our_data = read_object(root_dir, &ours.1)?;
+    let their_data = read_object(root_dir, &theirs.1)?;
+    if is_binary(&ancestor_data) || is_binary(&our_data) || is_binary(&their_data) {
+        return Ok((ours.clone(), EntryConflict::Unresolved));
+    }
+
+    let (text, conflict) = merge_texts(
+        &String::from_utf8_lossy(&ancestor_data),
+        &String::from_utf8_lossy(&our_data),
+        &String::from_utf8_lossy(&their_data),
+        ours_name,
+        theirs_name,
+    );
+    let mode = match ancestor {
+        Some((mode, _)) if *mode == ours.0 => theirs.0,
+        _ => ours.0,
+    };
+    let conflict = match conflict {
+        true => EntryConflict::Markers,
+        false => EntryConflict::None,
+    };
+    Ok(((mode, put_object(root_dir, text.as_bytes())?), conflict))
+}
+
+/// Resolves the clashes of a file of one side with a directory of the other side in the merged tree:
+/// the version of `ours` is kept and the clashing paths are returned.
+/// An empty directory clashing with files is dropped without a conflict.
+fn resolve_clashes(ours: &FlatTree, merged: &mut FlatTree) -> Vec<PathBuf> {
+    let parents: BTreeSet<PathBuf> = merged
+        .keys()
+        .flat_map(|path| path.ancestors().skip(1))
+        .filter(|parent| merged.contains_key(*parent))
+        .map(Path::to_path_buf)
+        .collect();
+    let mut ans = vec![];
+    for parent in parents.iter() {
+        let Some(entry) = merged.get(parent).cloned() else {
+            continue;
+        };
+        let children: Vec<PathBuf> = merged
+            .keys()
+            .filter(|path| *path != parent && path.starts_with(parent))
+            .cloned()
+            .collect();
+        if entry.0 == EntryMode::Directory {
+            merged.remove(parent);
+            continue;
+        }
+        ans.push(parent.clone());
+        ans.extend(children.iter().cloned());
+        if ours.get(parent) == Some(&entry) {
+            children.iter().for_each(|path| {
+                merged.remove(path);
+            });
+        } else {
+            merged.remove(parent);
+        }
+    }
+    ans
+}
+
+/// Merges the changes of two trees made since their common ancestor, the merged files are put to the object store.
+/// Returns the merged tree, the paths of the conflicts and the paths of the unresolved ones among them:
+/// conflicting text changes are kept with markers, for other conflicts the version of `ours`
+/// or the modified version is kept, they are resolved only by adding the paths again.
+pub fn merge_trees(
+    root_dir: &Path,
+    ancestor: &FlatTree,
+    ours: &FlatTree,
+    theirs: &FlatTree,
+    ours_name: &str,
+    theirs_name: &str,
+) -> Result<(FlatTree, Vec<PathBuf>, Vec<PathBuf>), VcsError> {
+    let paths: BTreeSet<&PathBuf> = ancestor
+        .keys()
+        .chain(ours.keys())
+        .chain(theirs.keys())
+        .collect();
+    let mut merged = FlatTree::new();
+    let mut conflicts = vec![];
+    let mut unresolved = vec![];
+
+    for path in paths {
+        let (base, our, their) = (ancestor.get(path), ours.get(path), theirs.get(path));
+        let entry = if our == their || base == their {
+            our.cloned()
+        } else if base == our {
+            their.cloned()
+        } else {
+            match (our, their) {
+                (Some(our), Some(their)) => {
+                    let (entry, conflict) =
+                        merge_entry(root_dir, base, our, their, ours_name, theirs_name)?;
+                    if conflict != EntryConflict::None {
+                        conflicts.push(path.clone());
+                    }
+                    if conflict == EntryConflict::Unresolved {
+                        unresolved.push(path.clone());
+                    }
+                    Some(entry)
+                }
+                (our, their) => {
+                    conflicts.push(path.clone());
+                    unresolved.push(path.clone());
+                    our.or(their).cloned()
+                }
+            }
+        };
+        if let Some(entry) = entry {
+            merged.insert(path.clone(), entry);
+        }
+    }
+    for path in resolve_clashes(ours, &mut merged) {
+        if !conflicts.contains(&path) {
+            conflicts.push(path.clone());
+        }
+        if !unresolved.contains(&path) {
+            unresolved.push(path);
+        }
+    }
+    conflicts.sort();
+    unresolved.sort();
+    Ok((merged, conflicts, unresolved))
+}
@@ -1,43 +1,74 @@
 #![forbid(unsafe_code)]
 
-use super::extract_files::files_from_commit;
+use super::object_store::read_object;
+use super::work_with_index::remove_index;
+use super::work_with_merge_state::remove_merge_state;
+use super::work_with_trees::{flatten_tree, set_file_mode};
+use crate::library::error::VcsError;
 use crate::library::files::File;
+use crate::library::ignore::IgnoreRules;
+use crate::library::tree::EntryMode;
 use crate::library::vcs_repository::VcsRepository;
 use std::fs;
-use std::io::Write;
 use std::path::Path;
 
-fn clean_dir(dir: &Path) -> Result<(), &'static str> {
-    for entry in fs::read_dir(dir).unwrap() {
-        let entry = entry.expect("Path doesn't exists");
+/// Deletes the files of the directory, files ignored by ".vcsignore" are kept untouched.
+fn clean_dir(root_dir: &Path, dir: &Path, rules: &mut IgnoreRules) -> Result<(), VcsError> {
+    rules.add_file(root_dir, dir.strip_prefix(root_dir).unwrap_or(dir))?;
+    for entry in fs::read_dir(dir).map_err(|e| VcsError::io(dir, e))? {
+        let entry = entry.map_err(|e| VcsError::io(dir, e))?;
         let path = entry.path();
-        if path.is_dir() {
-            if path.ends_with(".vcs") {
-                continue;
+        let is_dir = entry
+            .file_type()
+            .map_err(|e| VcsError::io(&path, e))?
+            .is_dir();
+        if path.ends_with(".vcs")
+            || rules.is_ignored(path.strip_prefix(root_dir).unwrap_or(&path), is_dir)
+        {
+            continue;
+        }
+        if is_dir {
+            clean_dir(root_dir, &path, rules)?;
+            let mut content = fs::read_dir(&path).map_err(|e| VcsError::io(&path, e))?;
+            if content.next().is_none() {
+                fs::remove_dir(&path).map_err(|e| VcsError::io(&path, e))?;
             }
-            fs::remove_dir_all(path).unwrap();
         } else {
-            fs::remove_file(path).unwrap();
+            fs::remove_file(&path).map_err(|e| VcsError::io(&path, e))?;
         }
     }
     Ok(())
 }
 
-fn add_file(file: &File) {
-    let mut path_to_file = file.get_path().to_path_buf();
-    path_to_file.pop();
-    fs::create_dir_all(path_to_file).unwrap();
-    let mut output = fs::File::create(file.get_path()).unwrap();
-    output.write_all(&file.get_data()).unwrap();
+fn add_file(file: &File) -> Result<(), VcsError> {
+    let path = file.get_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| VcsError::io(dir, e))?;
+    }
+    fs::write(path, file.get_data()).map_err(|e| VcsError::io(path, e))
 }
 
-
-/// Updates user repository.
-pub fn update_repo(vcs: &VcsRepository) -> Result<(), &'static str> {
-    clean_dir(vcs.get_dir())?;
-    let files = files_from_commit(&vcs.get_current_commit())?;
-    for file in files.iter() {
-        add_file(file);
+/// Restores files and empty directories of the tree with the given hash in the repository.
+/// Ignored files are preserved, other files are replaced.
+pub fn restore_tree(root_dir: &Path, tree_hash: &str) -> Result<(), VcsError> {
+    clean_dir(root_dir, root_dir, &mut IgnoreRules::for_repo(root_dir)?)?;
+    for (path, (mode, hash)) in flatten_tree(root_dir, tree_hash)? {
+        let path = root_dir.join(path);
+        if mode == EntryMode::Directory {
+            fs::create_dir_all(&path).map_err(|e| VcsError::io(&path, e))?;
+            continue;
+        }
+        add_file(&File::from_data(&path, read_object(root_dir, &hash)?))?;
+        set_file_mode(&path, mode)?;
     }
     Ok(())
 }
+
+/// Updates user repository: restores files and empty directories of the current commit.
+/// Ignored files are preserved, staged changes and the merge in progress are dropped.
+pub fn update_repo(vcs: &VcsRepository) -> Result<(), VcsError> {
+    let root_dir = vcs.get_dir();
+    restore_tree(root_dir, vcs.get_current_commit().get_tree_hash())?;
+    remove_index(root_dir)?;
+    remove_merge_state(root_dir)
+}
@@ -5,7 +5,7 @@ use array_tool::vec::Intersect;
 use std::path::PathBuf;
 
 /// Returns added files in new_files compared to old_files.
-pub fn added_files(new_files: &Vec<File>, old_files: &Vec<File>) -> Vec<PathBuf> {
+pub fn added_files(new_files: &[File], old_files: &[File]) -> Vec<PathBuf> {
     let mut ans: Vec<PathBuf> = vec![];
     for repo_file in new_files.iter() {
         let mut _file_find = false;
@@ -16,7 +16,7 @@ pub fn added_files(new_files: &Vec<File>, old_files: &Vec<File>) -> Vec<PathBuf>
             }
         }
 
-        if _file_find == false {
+        if !_file_find {
             ans.push(repo_file.get_path().to_path_buf());
         }
     }
@@ -24,7 +24,7 @@ pub fn added_files(new_files: &Vec<File>, old_files: &Vec<File>) -> Vec<PathBuf>
 }
 
 /// Returns modified files in new_files compared to old_files.
-pub fn modified_files(new_files: &Vec<File>, old_files: &Vec<File>) -> Vec<PathBuf> {
+pub fn modified_files(new_files: &[File], old_files: &[File]) -> Vec<PathBuf> {
     let mut ans: Vec<PathBuf> = vec![];
     for repo_file in new_files.iter() {
         for commit_file in old_files.iter() {
@@ -41,7 +41,7 @@ pub fn modified_files(new_files: &Vec<File>, old_files: &Vec<File>) -> Vec<PathB
 }
 
 /// Returns deleted files in new_files compared to old_files.
-pub fn deleted_files(new_files: &Vec<File>, old_files: &Vec<File>) -> Vec<PathBuf> {
+pub fn deleted_files(new_files: &[File], old_files: &[File]) -> Vec<PathBuf> {
     let mut ans: Vec<PathBuf> = vec![];
     for commit_file in old_files.iter() {
         let mut file_find = false;
@@ -51,7 +51,7 @@ pub fn deleted_files(new_files: &Vec<File>, old_files: &Vec<File>) -> Vec<PathBu
                 break;
             }
         }
-        if file_find == false {
+        if !file_find {
             ans.push(commit_file.get_path().to_path_buf());
         }
     }
@@ -59,7 +59,7 @@ pub fn deleted_files(new_files: &Vec<File>, old_files: &Vec<File>) -> Vec<PathBu
 }
 
 /// Returns all changed files between two given vectors.
-pub fn all_changed_files(new_files: &Vec<File>, old_files: &Vec<File>) -> Vec<PathBuf> {
+pub fn all_changed_files(new_files: &[File], old_files: &[File]) -> Vec<PathBuf> {
     let mut changed_files = added_files(new_files, old_files);
     changed_files.append(&mut modified_files(new_files, old_files));
     changed_files.append(&mut deleted_files(new_files, old_files));
@@ -68,16 +68,12 @@ pub fn all_changed_files(new_files: &Vec<File>, old_files: &Vec<File>) -> Vec<Pa
 }
 
 /// Returns files which both changed in two given vectors compared to the third
-/// 
+///
 /// # Arguments
 /// * `main_files` - files with which the files of the other two vectors are compared
 /// * `files1`
 /// * `files2`
-pub fn both_changed(
-    main_files: &Vec<File>,
-    files1: &Vec<File>,
-    files2: &Vec<File>,
-) -> Vec<PathBuf> {
+pub fn both_changed(main_files: &[File], files1: &[File], files2: &[File]) -> Vec<PathBuf> {
     let modified1 = modified_files(files1, main_files);
     let modified2 = modified_files(files2, main_files);
 
@@ -105,7 +101,7 @@ pub fn both_changed(
                 break;
             }
         }
-        if _exists == false {
+        if !_exists {
             res.push(path1.clone());
         }
     }
@@ -118,7 +114,7 @@ pub fn both_changed(
                 break;
             }
         }
-        if _exists == false {
+        if !_exists {
             res.push(path2.clone());
         }
     }
@@ -126,7 +122,7 @@ pub fn both_changed(
     res
 }
 
-pub fn added_files_with_data(new_files: &Vec<File>, old_files: &Vec<File>) -> Vec<File> {
+pub fn added_files_with_data(new_files: &[File], old_files: &[File]) -> Vec<File> {
     let mut ans: Vec<File> = vec![];
     for repo_file in new_files.iter() {
         let mut _file_find = false;
@@ -137,14 +133,14 @@ pub fn added_files_with_data(new_files: &Vec<File>, old_files: &Vec<File>) -> Ve
             }
         }
 
-        if _file_find == false {
+        if !_file_find {
             ans.push(repo_file.clone());
         }
     }
     ans
 }
 
-pub fn modified_files_with_data(new_files: &Vec<File>, old_files: &Vec<File>) -> Vec<File> {
+pub fn modified_files_with_data(new_files: &[File], old_files: &[File]) -> Vec<File> {
     let mut ans: Vec<File> = vec![];
     for repo_file in new_files.iter() {
         for commit_file in old_files.iter() {
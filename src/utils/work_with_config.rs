@@ -0,0 +1,99 @@
+#![forbid(unsafe_code)]
+
+use crate::library::error::VcsError;
+use crate::utils::atomic_write::write_atomically;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The environment variable with the path of the user configuration file.
+pub const USER_CONFIG_ENV: &str = "VCS_CONFIG";
+
+/// Returns the path of the configuration file of the repository.
+pub fn repo_config_path(root_dir: &Path) -> PathBuf {
+    root_dir.join(".vcs").join("config")
+}
+
+/// Returns the path of the configuration file of the user: the path from `VCS_CONFIG`
+/// or ".vcsconfig" in the home directory. There is none if the home directory is unknown.
+pub fn user_config_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os(USER_CONFIG_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".vcsconfig"))
+}
+
+/// Reads the configuration file: lines "key = value", empty lines and lines starting with "#"
+/// are skipped, the keys are case insensitive. There are no settings if the file doesn't exist.
+pub fn read_config_file(path: &Path) -> Result<BTreeMap<String, String>, VcsError> {
+    let mut ans = BTreeMap::new();
+    if !path.is_file() {
+        return Ok(ans);
+    }
+    let data = fs::read_to_string(path).map_err(|e| VcsError::io(path, e))?;
+    for (ind, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                ans.insert(key.trim().to_lowercase(), value.trim().to_string());
+            }
+            _ => {
+                return Err(VcsError::InvalidConfig {
+                    path: path.to_path_buf(),
+                    line: ind + 1,
+                })
+            }
+        }
+    }
+    Ok(ans)
+}
+
+/// Sets the key to the value in the configuration file or removes the key if there is no value,
+/// other lines of the file are kept. Returns whether the key was set before.
+pub fn write_config_value(path: &Path, key: &str, value: Option<&str>) -> Result<bool, VcsError> {
+    let data = match path.is_file() {
+        true => fs::read_to_string(path).map_err(|e| VcsError::io(path, e))?,
+        false => String::new(),
+    };
+    let mut found = false;
+    let mut lines = vec![];
+    for line in data.lines() {
+        let is_key = match line.split_once('=') {
+            Some((line_key, _)) => {
+                !line.trim_start().starts_with('#') && line_key.trim().eq_ignore_ascii_case(key)
+            }
+            None => false,
+        };
+        if !is_key {
+            lines.push(line.to_string());
+            continue;
+        }
+        if let (false, Some(value)) = (found, value) {
+            lines.push(format!("{} = {}", key, value));
+        }
+        found = true;
+    }
+    if let (false, Some(value)) = (found, value) {
+        lines.push(format!("{} = {}", key, value));
+    }
+    if found || value.is_some() {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent).map_err(|e| VcsError::io(parent, e))?;
+        }
+        let mut data = lines.join("\n");
+        if !data.is_empty() {
+            data.push('\n');
+        }
+        write_atomically(path, data.as_bytes())?;
+    }
+    Ok(found)
+}
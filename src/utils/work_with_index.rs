@@ -0,0 +1,68 @@
+#![forbid(unsafe_code)]
+
+use super::atomic_write::write_atomically;
+use super::work_with_trees::flatten_tree;
+use crate::library::error::VcsError;
+use crate::library::index::Index;
+use crate::library::vcs_repository::VcsRepository;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Returns the path of the index.
+pub fn index_path(root_dir: &Path) -> PathBuf {
+    root_dir.join(".vcs").join("index")
+}
+
+/// Reads the index file of the repository, there is no file if nothing was staged.
+pub fn read_index_file(root_dir: &Path) -> Result<Option<Index>, VcsError> {
+    let path = index_path(root_dir);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read(&path).map_err(|e| VcsError::io(&path, e))?;
+    serde_json::from_slice(&data)
+        .map(Some)
+        .map_err(|e| VcsError::json(&path, e))
+}
+
+/// Reads the index of the repository.
+/// If nothing is staged, the index matches the tree of the current commit.
+pub fn read_index(vcs: &VcsRepository) -> Result<Index, VcsError> {
+    match read_index_file(vcs.get_dir())? {
+        Some(index) => Ok(index),
+        None => {
+            let tree_hash = vcs.get_current_commit().get_tree_hash();
+            Ok(Index::init(flatten_tree(vcs.get_dir(), tree_hash)?))
+        }
+    }
+}
+
+/// Writes the index of the repository.
+pub fn write_index(root_dir: &Path, index: &Index) -> Result<(), VcsError> {
+    let path = index_path(root_dir);
+    let data = serde_json::to_vec(index).map_err(|e| VcsError::json(&path, e))?;
+    write_atomically(&path, &data)
+}
+
+/// Drops the staged changes: the index matches the current commit again.
+pub fn remove_index(root_dir: &Path) -> Result<(), VcsError> {
+    let path = index_path(root_dir);
+    if !path.exists() {
+        return Ok(());
+    }
+    fs::remove_file(&path).map_err(|e| VcsError::io(&path, e))
+}
+
+/// Returns the path relative to the repository root without "." components,
+/// the path "." becomes empty and means the whole repository.
+pub fn normalize_path(path: &Path) -> Result<PathBuf, VcsError> {
+    let mut ans = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::Normal(name) => ans.push(name),
+            _ => return Err(VcsError::OutsideRepository(path.to_path_buf())),
+        }
+    }
+    Ok(ans)
+}
@@ -0,0 +1,48 @@
+#![forbid(unsafe_code)]
+
+use super::atomic_write::write_atomically;
+use crate::library::error::VcsError;
+use crate::library::merge_state::MergeState;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Returns the path of the state of the merge in progress.
+pub fn merge_state_path(root_dir: &Path) -> PathBuf {
+    root_dir.join(".vcs").join("merge_state")
+}
+
+/// Reads the state of the merge in progress, there is no file if nothing is merged.
+pub fn read_merge_state(root_dir: &Path) -> Result<Option<MergeState>, VcsError> {
+    let path = merge_state_path(root_dir);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read(&path).map_err(|e| VcsError::io(&path, e))?;
+    serde_json::from_slice(&data)
+        .map(Some)
+        .map_err(|e| VcsError::json(&path, e))
+}
+
+/// Writes the state of the merge in progress.
+pub fn write_merge_state(root_dir: &Path, state: &MergeState) -> Result<(), VcsError> {
+    let path = merge_state_path(root_dir);
+    let data = serde_json::to_vec(state).map_err(|e| VcsError::json(&path, e))?;
+    write_atomically(&path, &data)
+}
+
+/// Forgets the merge in progress.
+pub fn remove_merge_state(root_dir: &Path) -> Result<(), VcsError> {
+    let path = merge_state_path(root_dir);
+    if !path.exists() {
+        return Ok(());
+    }
+    fs::remove_file(&path).map_err(|e| VcsError::io(&path, e))
+}
+
+/// Returns an error if a merge stopped by conflicts is not finished yet.
+pub fn check_no_merge(root_dir: &Path) -> Result<(), VcsError> {
+    match merge_state_path(root_dir).exists() {
+        true => Err(VcsError::MergeInProgress),
+        false => Ok(()),
+    }
+}
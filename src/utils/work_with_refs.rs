@@ -0,0 +1,241 @@
+#![forbid(unsafe_code)]
+
+use super::atomic_write::write_atomically;
+use super::object_store::{hash_data, object_path, put_object, read_object};
+use crate::library::branch::Branch;
+use crate::library::error::VcsError;
+use crate::library::head::Head;
+use crate::library::tag::Tag;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The prefix of the symbolic reference in the HEAD file.
+const SYMBOLIC_REF_PREFIX: &str = "ref: refs/heads/";
+
+/// Names which can't be used for references.
+pub const RESERVED_REF_NAMES: [&str; 1] = ["HEAD"];
+
+/// Symbols which are not allowed in the reference names.
+const SPECIAL_SYMBOLS: [char; 7] = ['~', '^', ':', '?', '*', '[', '\\'];
+
+/// Checks that the given name can be used for a reference and for its path,
+/// returns the reason why it can't otherwise.
+pub fn check_ref_name(name: &str) -> Result<(), &'static str> {
+    if name.is_empty() {
+        return Err("it is empty");
+    }
+    if RESERVED_REF_NAMES.contains(&name) {
+        return Err("it is reserved");
+    }
+    if name.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err("it contains whitespace");
+    }
+    if name.contains(SPECIAL_SYMBOLS) || name.contains("..") {
+        return Err("it contains .. or one of the symbols ~ ^ : ? * [ \\");
+    }
+    if name.starts_with('-') {
+        return Err("it starts with a dash");
+    }
+    if name.ends_with('/') {
+        return Err("it ends with a slash");
+    }
+    for component in name.split('/') {
+        if component.is_empty() {
+            return Err("it contains an empty path component");
+        }
+        if component.starts_with('.') {
+            return Err("its path component starts with a dot");
+        }
+    }
+    Ok(())
+}
+
+/// Returns the directory of the branch references.
+pub fn heads_dir(root_dir: &Path) -> PathBuf {
+    root_dir.join(".vcs").join("refs").join("heads")
+}
+
+/// Returns the directory of the tag references.
+pub fn tags_dir(root_dir: &Path) -> PathBuf {
+    root_dir.join(".vcs").join("refs").join("tags")
+}
+
+/// Returns the path of the file with the current position of the repository.
+pub fn head_path(root_dir: &Path) -> PathBuf {
+    root_dir.join(".vcs").join("HEAD")
+}
+
+/// Reads the reference file: the hash it points to.
+fn read_ref(path: &Path) -> Result<String, VcsError> {
+    let data = fs::read_to_string(path).map_err(|e| VcsError::io(path, e))?;
+    let hash = data.trim();
+    if hash.is_empty() || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(VcsError::CorruptedRepository(format!(
+            "broken reference {}",
+            path.display()
+        )));
+    }
+    Ok(hash.to_string())
+}
+
+fn read_refs_in(
+    refs_dir: &Path,
+    dir: &Path,
+    ans: &mut Vec<(String, String)>,
+) -> Result<(), VcsError> {
+    for entry in fs::read_dir(dir).map_err(|e| VcsError::io(dir, e))? {
+        let path = entry.map_err(|e| VcsError::io(dir, e))?.path();
+        if path.is_dir() {
+            read_refs_in(refs_dir, &path, ans)?;
+            continue;
+        }
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        if file_name.starts_with('.') && file_name.ends_with(".tmp") {
+            continue;
+        }
+        let name = path.strip_prefix(refs_dir).unwrap_or(&path);
+        ans.push((name.to_string_lossy().to_string(), read_ref(&path)?));
+    }
+    Ok(())
+}
+
+/// Reads the names and hashes of the references in the given directory sorted by name.
+fn read_refs(refs_dir: &Path) -> Result<Vec<(String, String)>, VcsError> {
+    let mut ans = vec![];
+    if refs_dir.is_dir() {
+        read_refs_in(refs_dir, refs_dir, &mut ans)?;
+    }
+    ans.sort();
+    Ok(ans)
+}
+
+/// Removes the directories of the reference which became empty, up to the references directory.
+fn remove_empty_dirs(refs_dir: &Path, path: &Path) -> Result<(), VcsError> {
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        if current == refs_dir {
+            break;
+        }
+        let mut content = fs::read_dir(current).map_err(|e| VcsError::io(current, e))?;
+        if content.next().is_some() {
+            break;
+        }
+        fs::remove_dir(current).map_err(|e| VcsError::io(current, e))?;
+        dir = current.parent();
+    }
+    Ok(())
+}
+
+/// Writes the given references to the directory, other references in it are removed.
+fn write_refs(refs_dir: &Path, refs: &[(String, String)]) -> Result<(), VcsError> {
+    for (old, _) in read_refs(refs_dir)? {
+        if !refs.iter().any(|(name, _)| *name == old) {
+            let path = refs_dir.join(old);
+            fs::remove_file(&path).map_err(|e| VcsError::io(&path, e))?;
+            remove_empty_dirs(refs_dir, &path)?;
+        }
+    }
+    for (name, hash) in refs.iter() {
+        let path = refs_dir.join(name);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| VcsError::io(dir, e))?;
+        }
+        if read_ref(&path).ok().as_ref() != Some(hash) {
+            write_atomically(&path, format!("{}\n", hash).as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads the branches of the repository sorted by name.
+pub fn read_branches(root_dir: &Path) -> Result<Vec<Branch>, VcsError> {
+    Ok(read_refs(&heads_dir(root_dir))?
+        .iter()
+        .map(|(name, hash)| Branch::init(name, hash))
+        .collect())
+}
+
+/// Writes the references of the given branches, references of other branches are removed.
+pub fn write_branches(root_dir: &Path, branches: &[Branch]) -> Result<(), VcsError> {
+    let refs: Vec<(String, String)> = branches
+        .iter()
+        .map(|branch| {
+            (
+                branch.get_name().to_string(),
+                branch.get_commit_hash().to_string(),
+            )
+        })
+        .collect();
+    write_refs(&heads_dir(root_dir), &refs)
+}
+
+/// Reads the tags of the repository sorted by name.
+/// The reference of a lightweight tag is the commit hash, the reference of an annotated tag
+/// is the hash of the tag in the object store.
+pub fn read_tags(root_dir: &Path) -> Result<Vec<Tag>, VcsError> {
+    let mut ans = vec![];
+    for (name, hash) in read_refs(&tags_dir(root_dir))? {
+        let path = object_path(root_dir, &hash);
+        if !path.is_file() {
+            ans.push(Tag::init(&name, &hash, None));
+            continue;
+        }
+        let data = read_object(root_dir, &hash)?;
+        ans.push(serde_json::from_slice(&data).map_err(|e| VcsError::json(&path, e))?);
+    }
+    Ok(ans)
+}
+
+/// Returns the data of the annotated tag in the object store, lightweight tags are not stored.
+fn tag_object(root_dir: &Path, tag: &Tag) -> Result<Option<Vec<u8>>, VcsError> {
+    if tag.get_annotation().is_none() {
+        return Ok(None);
+    }
+    let data = serde_json::to_vec(tag).map_err(|e| VcsError::json(&tags_dir(root_dir), e))?;
+    Ok(Some(data))
+}
+
+/// Returns the hash of the annotated tag in the object store, lightweight tags are not stored.
+pub fn tag_object_hash(root_dir: &Path, tag: &Tag) -> Result<Option<String>, VcsError> {
+    Ok(tag_object(root_dir, tag)?.map(|data| hash_data(&data)))
+}
+
+/// Writes the references of the given tags, references of other tags are removed.
+/// The reference of an annotated tag points to the tag put to the object store.
+pub fn write_tags(root_dir: &Path, tags: &[Tag]) -> Result<(), VcsError> {
+    let mut refs = vec![];
+    for tag in tags.iter() {
+        let hash = match tag_object(root_dir, tag)? {
+            Some(data) => put_object(root_dir, &data)?,
+            None => tag.get_commit_hash().to_string(),
+        };
+        refs.push((tag.get_name().to_string(), hash));
+    }
+    write_refs(&tags_dir(root_dir), &refs)
+}
+
+/// Reads the current position of the repository.
+pub fn read_head(root_dir: &Path) -> Result<Head, VcsError> {
+    let path = head_path(root_dir);
+    let data = fs::read_to_string(&path).map_err(|e| VcsError::io(&path, e))?;
+    let data = data.trim();
+    if let Some(name) = data.strip_prefix(SYMBOLIC_REF_PREFIX) {
+        return Ok(Head::Branch(name.to_string()));
+    }
+    if data.is_empty() || !data.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(VcsError::CorruptedRepository(format!(
+            "broken reference {}",
+            path.display()
+        )));
+    }
+    Ok(Head::Detached(data.to_string()))
+}
+
+/// Writes the current position of the repository.
+pub fn write_head(root_dir: &Path, head: &Head) -> Result<(), VcsError> {
+    let data = match head {
+        Head::Branch(name) => format!("{}{}\n", SYMBOLIC_REF_PREFIX, name),
+        Head::Detached(hash) => format!("{}\n", hash),
+    };
+    write_atomically(&head_path(root_dir), data.as_bytes())
+}
@@ -0,0 +1,232 @@
+#![forbid(unsafe_code)]
+
+use super::object_store::{object_path, objects_dir, put_object, read_object};
+use crate::library::error::VcsError;
+use crate::library::ignore::IgnoreRules;
+use crate::library::tree::{EntryMode, FlatTree, Tree, TreeEntry};
+use std::collections::BTreeMap;
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+/// Returns the mode of the file along the given path.
+pub fn file_mode(path: &Path) -> Result<EntryMode, VcsError> {
+    let metadata = fs::metadata(path).map_err(|e| VcsError::io(path, e))?;
+    if metadata.is_dir() {
+        return Ok(EntryMode::Directory);
+    }
+
+    #[cfg(unix)]
+    if metadata.permissions().mode() & 0o111 != 0 {
+        return Ok(EntryMode::Executable);
+    }
+
+    Ok(EntryMode::File)
+}
+
+/// Sets the permissions of the file along the given path according to the mode.
+pub fn set_file_mode(path: &Path, mode: EntryMode) -> Result<(), VcsError> {
+    #[cfg(unix)]
+    if mode == EntryMode::Executable {
+        let mut permissions = fs::metadata(path)
+            .map_err(|e| VcsError::io(path, e))?
+            .permissions();
+        permissions.set_mode(permissions.mode() | 0o111);
+        fs::set_permissions(path, permissions).map_err(|e| VcsError::io(path, e))?;
+    }
+
+    #[cfg(not(unix))]
+    let _ = (path, mode);
+
+    Ok(())
+}
+
+/// Puts the given tree to the object store and returns its hash.
+pub fn put_tree(root_dir: &Path, tree: &Tree) -> Result<String, VcsError> {
+    let data = serde_json::to_vec(tree).map_err(|e| VcsError::json(&objects_dir(root_dir), e))?;
+    put_object(root_dir, &data)
+}
+
+/// Reads the tree with the given hash from the object store.
+pub fn read_tree(root_dir: &Path, hash: &str) -> Result<Tree, VcsError> {
+    let data = read_object(root_dir, hash)?;
+    serde_json::from_slice(&data).map_err(|e| VcsError::json(&object_path(root_dir, hash), e))
+}
+
+/// Puts the files and subdirectories of the given directory to the object store
+/// and returns the hash of its tree. Directories ".vcs" and files ignored by ".vcsignore" are skipped.
+pub fn write_tree_from_dir(root_dir: &Path, dir: &Path) -> Result<String, VcsError> {
+    write_tree_with_rules(root_dir, dir, &mut IgnoreRules::for_repo(root_dir)?)
+}
+
+fn write_tree_with_rules(
+    root_dir: &Path,
+    dir: &Path,
+    rules: &mut IgnoreRules,
+) -> Result<String, VcsError> {
+    if !dir.is_dir() {
+        return Err(VcsError::NotADirectory(dir.to_path_buf()));
+    }
+    rules.add_file(root_dir, dir.strip_prefix(root_dir).unwrap_or(dir))?;
+
+    let mut tree = Tree::init();
+    for entry in fs::read_dir(dir).map_err(|e| VcsError::io(dir, e))? {
+        let entry = entry.map_err(|e| VcsError::io(dir, e))?;
+        let path = entry.path();
+        if path.ends_with(".vcs") {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let is_dir = path.is_dir();
+        if rules.is_ignored(path.strip_prefix(root_dir).unwrap_or(&path), is_dir) {
+            continue;
+        }
+
+        if is_dir {
+            let hash = write_tree_with_rules(root_dir, &path, rules)?;
+            tree.add_entry(TreeEntry::init(&name, EntryMode::Directory, &hash));
+        } else {
+            let data = fs::read(&path).map_err(|e| VcsError::io(&path, e))?;
+            let hash = put_object(root_dir, &data)?;
+            tree.add_entry(TreeEntry::init(&name, file_mode(&path)?, &hash));
+        }
+    }
+
+    put_tree(root_dir, &tree)
+}
+
+/// Puts the file or the directory along the given path relative to the repository root
+/// to the object store as the entries of the flat tree.
+fn write_flat_entry(
+    root_dir: &Path,
+    path: &Path,
+    is_dir: bool,
+    rules: &mut IgnoreRules,
+    ans: &mut FlatTree,
+) -> Result<(), VcsError> {
+    let full_path = root_dir.join(path);
+    if !is_dir {
+        let data = fs::read(&full_path).map_err(|e| VcsError::io(&full_path, e))?;
+        let hash = put_object(root_dir, &data)?;
+        ans.insert(path.to_path_buf(), (file_mode(&full_path)?, hash));
+    } else if write_flat_subtree(root_dir, path, rules, ans)? {
+        let hash = put_tree(root_dir, &Tree::init())?;
+        ans.insert(path.to_path_buf(), (EntryMode::Directory, hash));
+    }
+    Ok(())
+}
+
+/// Puts the content of the directory along the given path relative to the repository root
+/// to the object store as the entries of the flat tree, returns whether the directory has no entries.
+fn write_flat_subtree(
+    root_dir: &Path,
+    dir: &Path,
+    rules: &mut IgnoreRules,
+    ans: &mut FlatTree,
+) -> Result<bool, VcsError> {
+    rules.add_file(root_dir, dir)?;
+    let full_dir = root_dir.join(dir);
+    let mut is_empty = true;
+    for entry in fs::read_dir(&full_dir).map_err(|e| VcsError::io(&full_dir, e))? {
+        let full_path = entry.map_err(|e| VcsError::io(&full_dir, e))?.path();
+        if full_path.ends_with(".vcs") {
+            continue;
+        }
+        let path = full_path.strip_prefix(root_dir).unwrap_or(&full_path);
+        let is_dir = full_path.is_dir();
+        if rules.is_ignored(path, is_dir) {
+            continue;
+        }
+        is_empty = false;
+        write_flat_entry(root_dir, path, is_dir, rules, ans)?;
+    }
+    Ok(is_empty)
+}
+
+/// Puts the files and empty directories along the given path relative to the repository root
+/// to the object store and returns their flat description, the rest of the repository is not read.
+/// Directories ".vcs" and files ignored by ".vcsignore" are skipped, as is the path inside an ignored directory.
+pub fn write_flat_tree_from_path(root_dir: &Path, path: &Path) -> Result<FlatTree, VcsError> {
+    let mut rules = IgnoreRules::for_repo(root_dir)?;
+    let mut ans = FlatTree::new();
+    let mut current = PathBuf::new();
+    for component in path.components() {
+        rules.add_file(root_dir, &current)?;
+        current.push(component);
+        let full_path = root_dir.join(&current);
+        if current.ends_with(".vcs")
+            || !full_path.exists()
+            || rules.is_ignored(&current, full_path.is_dir())
+        {
+            return Ok(ans);
+        }
+    }
+
+    if path.as_os_str().is_empty() {
+        write_flat_subtree(root_dir, path, &mut rules, &mut ans)?;
+    } else {
+        let is_dir = root_dir.join(path).is_dir();
+        write_flat_entry(root_dir, path, is_dir, &mut rules, &mut ans)?;
+    }
+    Ok(ans)
+}
+
+/// Puts the tree described by the given flat tree to the object store and returns its hash.
+pub fn write_tree(root_dir: &Path, flat_tree: &FlatTree) -> Result<String, VcsError> {
+    let mut tree = Tree::init();
+    let mut subdirs: BTreeMap<String, FlatTree> = BTreeMap::new();
+
+    for (path, (mode, hash)) in flat_tree.iter() {
+        let mut components = path.components();
+        let name = match components.next() {
+            Some(name) => name.as_os_str().to_string_lossy().to_string(),
+            None => continue,
+        };
+        let rest = components.as_path();
+
+        if rest.as_os_str().is_empty() {
+            tree.add_entry(TreeEntry::init(&name, *mode, hash));
+        } else {
+            subdirs
+                .entry(name)
+                .or_default()
+                .insert(rest.to_path_buf(), (*mode, hash.clone()));
+        }
+    }
+
+    for (name, subdir) in subdirs.iter() {
+        let hash = write_tree(root_dir, subdir)?;
+        tree.add_entry(TreeEntry::init(name, EntryMode::Directory, &hash));
+    }
+
+    put_tree(root_dir, &tree)
+}
+
+fn flatten_subtree(
+    root_dir: &Path,
+    hash: &str,
+    prefix: &Path,
+    ans: &mut FlatTree,
+) -> Result<(), VcsError> {
+    let tree = read_tree(root_dir, hash)?;
+    for entry in tree.get_entries() {
+        let path = prefix.join(entry.get_name());
+        if entry.get_mode() != EntryMode::Directory {
+            ans.insert(path, (entry.get_mode(), entry.get_hash().to_string()));
+        } else if read_tree(root_dir, entry.get_hash())?.is_empty() {
+            ans.insert(path, (EntryMode::Directory, entry.get_hash().to_string()));
+        } else {
+            flatten_subtree(root_dir, entry.get_hash(), &path, ans)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the flat description of the tree with the given hash:
+/// relative paths of all its files and empty directories.
+pub fn flatten_tree(root_dir: &Path, hash: &str) -> Result<FlatTree, VcsError> {
+    let mut ans = FlatTree::new();
+    flatten_subtree(root_dir, hash, &PathBuf::new(), &mut ans)?;
+    Ok(ans)
+}
@@ -1,189 +1,1427 @@
 #[cfg(test)]
-
 mod tests {
 
-use std::{path::Path, io::Write};
-use vcs::commands::*;
-use std::fs;
-use vcs::utils::operation_hash::calculate_hash;
-
-#[test]
-fn test_init() {
-    let path = Path::new("./tests/test_data_init");
-    let vcs = init::init(path);
-    assert!(vcs.is_ok());
-    let path_vcs = path.join(".vcs");
-    assert!(path_vcs.exists());
+    use clap::Parser;
+    use std::fs;
+    use std::{
+        io::Write,
+        path::{Path, PathBuf},
+    };
+    use vcs::commands::*;
+    use vcs::library::config::Config;
+    use vcs::library::error::VcsError;
+    use vcs::library::head::Head;
+    use vcs::library::log_options::{LogFormat, LogOptions};
+    use vcs::library::report::{ErrorReport, Report};
+    use vcs::library::revision::RevisionRange;
+    use vcs::library::tag::Annotation;
+    use vcs::utils::comand_parser::CommandParser;
+    use vcs::utils::extract_files::{files_from_commit, read_vcs, read_vcs_shared};
+    use vcs::utils::journal::{begin_operation, journal_path};
+    use vcs::utils::object_store::{hash_data, object_path};
+    use vcs::utils::operation_hash::calculate_hash;
+    use vcs::utils::pack_files::pack_vcs;
+    use vcs::utils::work_with_config::repo_config_path;
+    use vcs::utils::work_with_merge_state::read_merge_state;
 
-    let vcs = init::init(path);
-    assert!(vcs.is_err());
+    #[test]
+    fn test_init() {
+        let path = Path::new("./tests/test_data_init");
+        let vcs = init::init(path, None);
+        assert!(vcs.is_ok());
+        let path_vcs = path.join(".vcs");
+        assert!(path_vcs.exists());
 
-    fs::remove_dir_all(Path::new("./tests/test_data_init"));
-}
+        let vcs = init::init(path, None);
+        assert!(vcs.is_err());
 
-#[test]
-fn test_new_commit() {
-    let path = Path::new("./tests/test_data_commit");
-    let mut vcs = init::init(path).unwrap();
+        fs::remove_dir_all(Path::new("./tests/test_data_init")).unwrap();
+    }
 
-    assert!(new_commit::new_commit(&mut vcs, "-1").is_err());
+    #[test]
+    fn test_new_commit() {
+        let path = Path::new("./tests/test_data_commit");
+        let mut vcs = init::init(path, None).unwrap();
 
-    for ind in 0..100 {
-        fs::File::create(path.join(ind.to_string()));
-        assert!(new_commit::new_commit(&mut vcs, &ind.to_string()).is_ok());
-    }    
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        assert!(new_commit::new_commit(&mut vcs, "-1").is_err());
 
-    fs::remove_dir_all(Path::new("./tests/test_data_commit"));
-}
+        for ind in 0..100 {
+            fs::File::create(path.join(ind.to_string())).unwrap();
+            add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+            assert!(new_commit::new_commit(&mut vcs, &ind.to_string()).is_ok());
+        }
 
-#[test]
-fn test_new_branch() {
-    let path = Path::new("./tests/test_data_branch");
-    let mut vcs = init::init(path).unwrap();
+        fs::remove_dir_all(Path::new("./tests/test_data_commit")).unwrap();
+    }
 
-    assert!(new_branch::new_branch(&mut vcs, "master").is_err());
-    assert!(new_branch::new_branch(&mut vcs, "new-branch").is_ok());
-    assert!(new_branch::new_branch(&mut vcs, "not-new-branch").is_err());
+    #[test]
+    fn test_new_branch() {
+        let path = Path::new("./tests/test_data_branch");
+        let mut vcs = init::init(path, None).unwrap();
 
-    for ind in 0..100 {
-        let branch = vcs.get_branch_by_name("master").unwrap().clone();
-        vcs.change_current_branch(&branch).unwrap();
-        assert!(new_branch::new_branch(&mut vcs, &ind.to_string()).is_ok());
-    }    
+        assert!(new_branch::new_branch(&mut vcs, "master").is_err());
+        assert!(new_branch::new_branch(&mut vcs, "new-branch").is_ok());
+        assert!(new_branch::new_branch(&mut vcs, "not-new-branch").is_ok());
+        assert!(new_branch::new_branch(&mut vcs, "new-branch").is_err());
 
-    fs::remove_dir_all(Path::new("./tests/test_data_branch"));
-}
+        for ind in 0..100 {
+            let branch = vcs.get_branch_by_name("master").unwrap().clone();
+            vcs.change_head(Head::Branch(branch.get_name().to_string()));
+            assert!(new_branch::new_branch(&mut vcs, &ind.to_string()).is_ok());
+        }
 
-#[test]
-fn test_jump_to_commit() {
-    let path = Path::new("./tests/test_data_jump_commit");
-    let mut hashes: Vec<u64> = vec![];
-    let mut vcs = init::init(path).unwrap();
+        fs::remove_dir_all(Path::new("./tests/test_data_branch")).unwrap();
+    }
 
-    hashes.push(calculate_hash(vcs.get_current_commit()));
+    #[test]
+    fn test_jump_to_commit() {
+        let path = Path::new("./tests/test_data_jump_commit");
+        let mut hashes: Vec<String> = vec![];
+        let mut vcs = init::init(path, None).unwrap();
 
-    assert!(new_commit::new_commit(&mut vcs, "-1").is_err());
-    hashes.push(calculate_hash(vcs.get_current_commit()));
+        hashes.push(calculate_hash(vcs.get_current_commit()));
 
-    for ind in 0..20 {
-        fs::File::create(path.join(ind.to_string()));
-        assert!(new_commit::new_commit(&mut vcs, &ind.to_string()).is_ok());
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        assert!(new_commit::new_commit(&mut vcs, "-1").is_err());
         hashes.push(calculate_hash(vcs.get_current_commit()));
-    }    
 
-    new_branch::new_branch(&mut vcs, "new-branch");
+        for ind in 0..20 {
+            fs::File::create(path.join(ind.to_string())).unwrap();
+            add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+            assert!(new_commit::new_commit(&mut vcs, &ind.to_string()).is_ok());
+            hashes.push(calculate_hash(vcs.get_current_commit()));
+        }
 
-    for ind in 20..40 {
-        fs::File::create(path.join(ind.to_string()));
-        assert!(new_commit::new_commit(&mut vcs, &ind.to_string()).is_ok());
-        hashes.push(calculate_hash(vcs.get_current_commit()));
-    }   
-
-    let mut x = 239 % hashes.len();
-    for _ in 0..50 {
-        let x = (x * 41 + 65) %  hashes.len();
-        assert!(jump_to_commit::jump_to_commit(&mut vcs, hashes[x]).is_ok());
-    } 
-
-    assert!(jump_to_commit::jump_to_commit(&mut vcs, hashes.last().unwrap().clone()).is_ok());
-    fs::File::create(path.join("-2"));
-
-    assert!(jump_to_commit::jump_to_commit(&mut vcs, hashes[0]).is_err());
-    assert!(new_commit::new_commit(&mut vcs, &"-2".to_string()).is_ok());
-
-    for k in 0..hashes.len() + 1 {
-        let mut _flag = false;
-        for ind in  0..hashes.len() {
-            if hashes[ind] == k as u64 {
-                _flag = true;
-                break;
+        new_branch::new_branch(&mut vcs, "new-branch").unwrap();
+
+        for ind in 20..40 {
+            fs::File::create(path.join(ind.to_string())).unwrap();
+            add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+            assert!(new_commit::new_commit(&mut vcs, &ind.to_string()).is_ok());
+            hashes.push(calculate_hash(vcs.get_current_commit()));
+        }
+
+        let mut x = 239 % hashes.len();
+        for _ in 0..50 {
+            x = (x * 41 + 65) % hashes.len();
+            assert!(jump_to_commit::jump_to_commit(&mut vcs, &hashes[x], false).is_ok());
+            assert!(jump_to_commit::jump_to_commit(&mut vcs, &hashes[x][..8], false).is_ok());
+            assert_eq!(calculate_hash(vcs.get_current_commit()), hashes[x]);
+        }
+
+        assert!(jump_to_commit::jump_to_commit(&mut vcs, hashes.last().unwrap(), false).is_ok());
+        fs::File::create(path.join("-2")).unwrap();
+
+        assert!(jump_to_commit::jump_to_commit(&mut vcs, &hashes[0], false).is_err());
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        assert!(new_commit::new_commit(&mut vcs, "-2").is_ok());
+
+        for k in 0..hashes.len() + 1 {
+            let hash = format!("{:064x}", k);
+            if !hashes.contains(&hash) {
+                assert!(jump_to_commit::jump_to_commit(&mut vcs, &hash, false).is_err());
             }
         }
-        if !_flag {
-            assert!(jump_to_commit::jump_to_commit(&mut vcs, k as u64).is_err());
+        assert!(jump_to_commit::jump_to_commit(&mut vcs, &hashes[1][..3], false).is_err());
+
+        fs::remove_dir_all(Path::new("./tests/test_data_jump_commit")).unwrap();
+    }
+
+    #[test]
+    fn test_jump_to_branch() {
+        let path = Path::new("./tests/test_data_jump_branch");
+        let mut vcs = init::init(path, None).unwrap();
+
+        assert!(jump_to_branch::jump_to_branch(&mut vcs, "-1", false).is_err());
+
+        fs::remove_dir_all(Path::new("./tests/test_data_jump_branch")).unwrap();
+    }
+
+    #[test]
+    fn test_merge() {
+        let path = Path::new("./tests/test_data_merge");
+        fs::create_dir_all(path).unwrap();
+        fs::File::create(path.join("deleted-file.txt")).unwrap();
+        fs::File::create(path.join("modified-file.txt")).unwrap();
+        let mut vcs = init::init(path, None).unwrap();
+
+        assert!(merge::merge(&mut vcs, "just-branch").is_err());
+
+        assert!(new_branch::new_branch(&mut vcs, "new-branch").is_ok());
+        assert!(merge::merge(&mut vcs, "new-branch").is_err());
+
+        let mut output = fs::File::create(path.join("modified-file.txt")).unwrap();
+        let data: Vec<u8> = vec![97, 97, 97];
+        output.write_all(&data).unwrap();
+        fs::File::create(path.join("added-file.txt")).unwrap();
+        fs::remove_file(path.join("deleted-file.txt")).unwrap();
+
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        assert!(new_commit::new_commit(&mut vcs, "1").is_ok());
+        let master = vcs.get_branch_by_name("master").unwrap().clone();
+        vcs.change_head(Head::Branch(master.get_name().to_string()));
+
+        assert!(merge::merge(&mut vcs, "new-branch").is_err());
+
+        let branch = vcs.get_branch_by_name("new-branch").unwrap().clone();
+        vcs.change_head(Head::Branch(branch.get_name().to_string()));
+
+        let path_mod = path.join("modified-file.txt");
+        fs::remove_file(path_mod).unwrap();
+        fs::File::create(path.join("modified-file.txt")).unwrap();
+
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        assert!(new_commit::new_commit(&mut vcs, "2").is_ok());
+        let master = vcs.get_branch_by_name("new-branch").unwrap().clone();
+        vcs.change_head(Head::Branch(master.get_name().to_string()));
+
+        assert!(merge::merge(&mut vcs, "new-branch").is_err());
+
+        let branch = vcs.get_branch_by_name("new-branch").unwrap().clone();
+        vcs.change_head(Head::Branch(branch.get_name().to_string()));
+
+        let mut output = fs::File::create(path.join("modified-file.txt")).unwrap();
+        let data: Vec<u8> = vec![97, 97, 97];
+        output.write_all(&data).unwrap();
+        fs::File::create(path.join("deleted-file.txt")).unwrap();
+
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        assert!(new_commit::new_commit(&mut vcs, "3").is_ok());
+        let master = vcs.get_branch_by_name("master").unwrap().clone();
+        vcs.change_head(Head::Branch(master.get_name().to_string()));
+
+        assert!(merge::merge(&mut vcs, "new-branch").is_err());
+
+        let branch = vcs.get_branch_by_name("new-branch").unwrap().clone();
+        vcs.change_head(Head::Branch(branch.get_name().to_string()));
+
+        let path_mod = path.join("modified-file.txt");
+        fs::remove_file(path_mod).unwrap();
+        fs::File::create(path.join("modified-file.txt")).unwrap();
+
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        assert!(new_commit::new_commit(&mut vcs, "4").is_ok());
+        assert!(jump_to_branch::jump_to_branch(&mut vcs, "master", false).is_ok());
+
+        assert!(merge::merge(&mut vcs, "new-branch").is_ok());
+
+        assert!(vcs.get_branch_by_name("new-branch").is_ok());
+
+        fs::remove_dir_all(Path::new("./tests/test_data_merge")).unwrap();
+    }
+
+    #[test]
+    fn test_jump_restores_directories() {
+        let path = Path::new("./tests/test_data_directories");
+        let mut vcs = init::init(path, None).unwrap();
+
+        fs::create_dir_all(path.join("src")).unwrap();
+        fs::create_dir_all(path.join("tests")).unwrap();
+        fs::create_dir_all(path.join("empty").join("nested")).unwrap();
+        fs::write(path.join("src").join("mod.rs"), "src").unwrap();
+        fs::write(path.join("tests").join("mod.rs"), "tests").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        assert!(new_commit::new_commit(&mut vcs, "directories").is_ok());
+        let hash = calculate_hash(vcs.get_current_commit());
+
+        fs::write(path.join("src").join("mod.rs"), "changed").unwrap();
+        fs::remove_dir_all(path.join("tests")).unwrap();
+        fs::remove_dir_all(path.join("empty")).unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        assert!(new_commit::new_commit(&mut vcs, "changes").is_ok());
+
+        assert!(jump_to_commit::jump_to_commit(&mut vcs, &hash, false).is_ok());
+        assert_eq!(
+            fs::read_to_string(path.join("src").join("mod.rs")).unwrap(),
+            "src"
+        );
+        assert_eq!(
+            fs::read_to_string(path.join("tests").join("mod.rs")).unwrap(),
+            "tests"
+        );
+        assert!(path.join("empty").join("nested").is_dir());
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_moved_repository() {
+        let path = Path::new("./tests/test_data_moved");
+        let moved_path = Path::new("./tests/test_data_moved_to");
+        let mut vcs = init::init(path, None).unwrap();
+
+        fs::create_dir_all(path.join("dir")).unwrap();
+        fs::write(path.join("dir").join("file.txt"), "first").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        assert!(new_commit::new_commit(&mut vcs, "first").is_ok());
+        let hash = calculate_hash(vcs.get_current_commit());
+        fs::write(path.join("dir").join("file.txt"), "second").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        assert!(new_commit::new_commit(&mut vcs, "second").is_ok());
+        pack_vcs(path, &vcs).unwrap();
+
+        fs::rename(path, moved_path).unwrap();
+        let mut vcs = read_vcs(moved_path.to_path_buf()).unwrap();
+        assert_eq!(vcs.get_dir(), moved_path);
+        assert!(jump_to_commit::jump_to_commit(&mut vcs, &hash, false).is_ok());
+        assert_eq!(
+            fs::read_to_string(moved_path.join("dir").join("file.txt")).unwrap(),
+            "first"
+        );
+        assert!(!path.exists());
+
+        fs::remove_dir_all(moved_path).unwrap();
+    }
+
+    #[test]
+    fn test_error_variants() {
+        let path = Path::new("./tests/test_data_errors");
+        let mut vcs = init::init(path, None).unwrap();
+
+        assert!(matches!(
+            init::init(path, None),
+            Err(VcsError::AlreadyExists(_))
+        ));
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        assert!(matches!(
+            new_commit::new_commit(&mut vcs, "empty"),
+            Err(VcsError::NoChanges)
+        ));
+        assert!(matches!(
+            jump_to_branch::jump_to_branch(&mut vcs, "missing", false),
+            Err(VcsError::NoBranch(name)) if name == "missing"
+        ));
+        assert!(matches!(
+            jump_to_commit::jump_to_commit(&mut vcs, "abcdef", false),
+            Err(VcsError::NoCommit(hash)) if hash == "abcdef"
+        ));
+
+        new_branch::new_branch(&mut vcs, "branch").unwrap();
+        assert!(matches!(
+            new_branch::new_branch(&mut vcs, "branch"),
+            Err(VcsError::BranchExists(name)) if name == "branch"
+        ));
+        assert!(matches!(
+            merge::merge(&mut vcs, "master"),
+            Err(VcsError::AlreadyMerged(name)) if name == "master"
+        ));
+
+        fs::write(path.join("file.txt"), "data").unwrap();
+        assert!(matches!(
+            jump_to_branch::jump_to_branch(&mut vcs, "master", false),
+            Err(VcsError::UncommittedFiles)
+        ));
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_interrupted_operation_rolled_back() {
+        let path = Path::new("./tests/test_data_journal");
+        let mut vcs = init::init(path, None).unwrap();
+        fs::write(path.join("file.txt"), "first").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "first").unwrap();
+        let hash = calculate_hash(vcs.get_current_commit());
+        pack_vcs(path, &vcs).unwrap();
+
+        begin_operation(&vcs, "merge").unwrap();
+        let journal = fs::read(journal_path(path)).unwrap();
+        fs::write(path.join("file.txt"), "second").unwrap();
+        fs::write(path.join("other.txt"), "other").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "second").unwrap();
+        pack_vcs(path, &vcs).unwrap();
+        fs::write(journal_path(path), journal).unwrap();
+
+        assert!(matches!(
+            read_vcs_shared(path.to_path_buf()),
+            Err(VcsError::InterruptedOperation(operation)) if operation == "merge"
+        ));
+        assert!(journal_path(path).exists());
+
+        let vcs = read_vcs(path.to_path_buf()).unwrap();
+        assert_eq!(calculate_hash(vcs.get_current_commit()), hash);
+        assert_eq!(fs::read_to_string(path.join("file.txt")).unwrap(), "first");
+        assert!(!path.join("other.txt").exists());
+        assert!(!journal_path(path).exists());
+        assert!(!path.join(".vcs").join(".VCSRepository.json.tmp").exists());
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_ignore_rules() {
+        let path = Path::new("./tests/test_data_ignore");
+        let mut vcs = init::init(path, None).unwrap();
+        let initial = calculate_hash(vcs.get_current_commit());
+
+        fs::create_dir_all(path.join("build")).unwrap();
+        fs::create_dir_all(path.join("sub")).unwrap();
+        fs::create_dir_all(path.join("docs").join("a").join("b")).unwrap();
+        fs::write(
+            path.join(".vcsignore"),
+            "# comment\n*.log\n!keep.log\nbuild/\n/secret.txt\ndocs/**/*.tmp\n",
+        )
+        .unwrap();
+        fs::write(path.join("sub").join(".vcsignore"), "local.txt\n").unwrap();
+        for file in [
+            "a.log",
+            "keep.log",
+            "build/out.bin",
+            "secret.txt",
+            "sub/secret.txt",
+            "sub/local.txt",
+            "local.txt",
+            "docs/a/b/c.tmp",
+            "docs/c.txt",
+        ] {
+            fs::write(path.join(file), file).unwrap();
+        }
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        assert!(new_commit::new_commit(&mut vcs, "ignore").is_ok());
+
+        let mut files: Vec<String> = files_from_commit(path, vcs.get_current_commit())
+            .unwrap()
+            .iter()
+            .map(|file| file.get_path().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                ".vcsignore",
+                "docs/c.txt",
+                "keep.log",
+                "local.txt",
+                "sub/.vcsignore",
+                "sub/secret.txt"
+            ]
+        );
+
+        assert!(jump_to_commit::jump_to_commit(&mut vcs, &initial, false).is_ok());
+        for file in [
+            "a.log",
+            "build/out.bin",
+            "secret.txt",
+            "sub/local.txt",
+            "docs/a/b/c.tmp",
+        ] {
+            assert_eq!(fs::read_to_string(path.join(file)).unwrap(), file);
+        }
+        for file in [
+            "keep.log",
+            "local.txt",
+            "sub/secret.txt",
+            "docs/c.txt",
+            ".vcsignore",
+        ] {
+            assert!(!path.join(file).exists());
         }
+
+        fs::remove_dir_all(path).unwrap();
     }
 
-    fs::remove_dir_all(Path::new("./tests/test_data_jump_commit"));
-}
+    #[test]
+    fn test_staging_area() {
+        let path = Path::new("./tests/test_data_staging");
+        let mut vcs = init::init(path, None).unwrap();
+        let initial = calculate_hash(vcs.get_current_commit());
 
-#[test]
-fn test_jump_to_branch() {
-    let path = Path::new("./tests/test_data_jump_branch");
-    let mut vcs = init::init(path).unwrap();
+        fs::write(path.join("a.txt"), "a").unwrap();
+        fs::write(path.join("b.txt"), "b").unwrap();
+        fs::write(path.join(".vcsignore"), "*.log\n").unwrap();
+        fs::write(path.join("debug.log"), "log").unwrap();
+        assert!(matches!(
+            new_commit::new_commit(&mut vcs, "nothing"),
+            Err(VcsError::NoChanges)
+        ));
+        assert!(matches!(
+            add::add(&vcs, &[PathBuf::from("c.txt")]),
+            Err(VcsError::PathNotFound(_))
+        ));
+        assert!(matches!(
+            add::add(&vcs, &[PathBuf::from("debug.log")]),
+            Err(VcsError::IgnoredPath(_))
+        ));
 
-    assert!(jump_to_branch::jump_to_branch(&mut vcs, "-1").is_err());
+        add::add(&vcs, &[PathBuf::from("./a.txt")]).unwrap();
+        assert!(object_path(path, &hash_data(b"a")).exists());
+        assert!(!object_path(path, &hash_data(b"b")).exists());
+        assert!(!object_path(path, &hash_data(b"log")).exists());
+        assert!(matches!(
+            jump_to_commit::jump_to_commit(&mut vcs, &initial, false),
+            Err(VcsError::UncommittedFiles)
+        ));
+        assert!(new_commit::new_commit(&mut vcs, "only a").is_ok());
+        let files = files_from_commit(path, vcs.get_current_commit()).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].get_path(), Path::new("a.txt"));
 
-    fs::remove_dir_all(Path::new("./tests/test_data_jump_branch"));
-}
+        fs::write(path.join("a.txt"), "changed").unwrap();
+        add::add(&vcs, &[PathBuf::from("a.txt")]).unwrap();
+        reset::reset(&vcs, &[PathBuf::from("a.txt")]).unwrap();
+        assert!(matches!(
+            new_commit::new_commit(&mut vcs, "reset"),
+            Err(VcsError::NoChanges)
+        ));
 
-#[test]
-fn test_merge() {
-    let path = Path::new("./tests/test_data_merge");
-    fs::File::create(path.join("deleted-file.txt"));
-    fs::File::create(path.join("modified-file.txt"));
-    let mut vcs = init::init(path).unwrap();
-
-    assert!(merge::merge(&mut vcs, "just-branch").is_err());
-
-    assert!(new_branch::new_branch(&mut vcs, "new-branch").is_ok());
-    assert!(merge::merge(&mut vcs, "new-branch").is_err());
-    
-    let mut output = fs::File::create(path.join("modified-file.txt")).unwrap();
-    let data: Vec<u8> = vec![97, 97, 97];
-    output.write_all(&data).unwrap();
-    fs::File::create(path.join("added-file.txt"));
-    fs::remove_file(path.join("deleted-file.txt"));
-
-    assert!(new_commit::new_commit(&mut vcs, "1").is_ok());
-    let master = vcs.get_branch_by_name("master").unwrap().clone();
-    vcs.change_current_branch(&master);
-    
-    assert!(merge::merge(&mut vcs, "new-branch").is_err());
-
-    let branch = vcs.get_branch_by_name("new-branch").unwrap().clone();
-    vcs.change_current_branch(&branch);
- 
-    let path_mod = path.join("modified-file.txt");
-    fs::remove_file(path_mod);
-    fs::File::create(path.join("modified-file.txt"));
-
-    assert!(new_commit::new_commit(&mut vcs, "2").is_ok());
-    let master = vcs.get_branch_by_name("new-branch").unwrap().clone();
-    vcs.change_current_branch(&master);
-    
-    assert!(merge::merge(&mut vcs, "new-branch").is_err());
-
-    let branch = vcs.get_branch_by_name("new-branch").unwrap().clone();
-    vcs.change_current_branch(&branch);
-
-    let mut output = fs::File::create(path.join("modified-file.txt")).unwrap();
-    let data: Vec<u8> = vec![97, 97, 97];
-    output.write_all(&data).unwrap();
-    fs::File::create(path.join("deleted-file.txt"));
-
-    assert!(new_commit::new_commit(&mut vcs, "3").is_ok());
-    let master = vcs.get_branch_by_name("master").unwrap().clone();
-    vcs.change_current_branch(&master);
-    
-    assert!(merge::merge(&mut vcs, "new-branch").is_err());
-
-    let branch = vcs.get_branch_by_name("new-branch").unwrap().clone();
-    vcs.change_current_branch(&branch);
-
-    let path_mod = path.join("modified-file.txt");
-    fs::remove_file(path_mod);
-    fs::File::create(path.join("modified-file.txt"));
-
-    assert!(new_commit::new_commit(&mut vcs, "4").is_ok());
-    let master = vcs.get_branch_by_name("master").unwrap().clone();
-    vcs.change_current_branch(&master);
-    
-    assert!(merge::merge(&mut vcs, "new-branch").is_ok());
-
-    assert!(vcs.get_branch_by_name("new-branch").is_err());
-
-    fs::remove_dir_all(Path::new("./tests/test_data_merge"));
-}
+        fs::remove_file(path.join("a.txt")).unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        assert!(new_commit::new_commit(&mut vcs, "delete a, add b").is_ok());
+        let files = files_from_commit(path, vcs.get_current_commit()).unwrap();
+        let mut names: Vec<_> = files
+            .iter()
+            .map(|file| file.get_name().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec![".vcsignore", "b.txt"]);
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_diff() {
+        let path = Path::new("./tests/test_data_diff");
+        let mut vcs = init::init(path, None).unwrap();
+        let initial = calculate_hash(vcs.get_current_commit());
+
+        fs::write(path.join("file.txt"), "a\nb\n").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "first").unwrap();
+        let first = calculate_hash(vcs.get_current_commit());
+        assert_eq!(diff::diff_working_tree(&vcs, 3).unwrap(), "");
+
+        new_branch::new_branch(&mut vcs, "branch").unwrap();
+        fs::write(path.join("file.txt"), "a\nc\n").unwrap();
+        let expected = "diff a/file.txt b/file.txt\n--- a/file.txt\n+++ b/file.txt\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n";
+        assert_eq!(diff::diff_working_tree(&vcs, 3).unwrap(), expected);
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "second").unwrap();
+
+        assert_eq!(
+            diff::diff_branches(&vcs, "master", "branch", 3).unwrap(),
+            expected
+        );
+        let diff = diff::diff_commits(&vcs, &initial, &first[..8], 0).unwrap();
+        assert!(diff.contains("--- /dev/null\n+++ b/file.txt\n@@ -0,0 +1,2 @@\n+a\n+b\n"));
+        assert!(matches!(
+            diff::diff_branches(&vcs, "master", "missing", 3),
+            Err(VcsError::NoBranch(_))
+        ));
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_three_way_merge() {
+        let path = Path::new("./tests/test_data_three_way_merge");
+        let mut vcs = init::init(path, None).unwrap();
+        let file = path.join("file.txt");
+
+        fs::write(&file, "1\n2\n3\n4\n5\n6\n7\n").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "first").unwrap();
+
+        new_branch::new_branch(&mut vcs, "feature").unwrap();
+        fs::write(&file, "1\n2\n3\n4\n5\n6\nseven\n").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "feature").unwrap();
+        jump_to_branch::jump_to_branch(&mut vcs, "master", false).unwrap();
+        fs::write(&file, "one\n2\n3\n4\n5\n6\n7\n").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "master").unwrap();
+
+        merge::merge(&mut vcs, "feature").unwrap();
+        assert_eq!(
+            fs::read_to_string(&file).unwrap(),
+            "one\n2\n3\n4\n5\n6\nseven\n"
+        );
+        assert!(vcs.get_branch_by_name("feature").is_ok());
+
+        new_branch::new_branch(&mut vcs, "conflict").unwrap();
+        fs::write(&file, "uno\n2\n3\n4\n5\n6\nseven\n").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "conflict").unwrap();
+        let conflict = calculate_hash(vcs.get_current_commit());
+        jump_to_branch::jump_to_branch(&mut vcs, "master", false).unwrap();
+        fs::write(&file, "eins\n2\n3\n4\n5\n6\nseven\n").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "master").unwrap();
+        let master = calculate_hash(vcs.get_current_commit());
+
+        match merge::merge(&mut vcs, "conflict") {
+            Err(VcsError::MergeConflict(paths)) => {
+                assert_eq!(paths, vec![PathBuf::from("file.txt")])
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        let conflicted =
+            "<<<<<<< master\neins\n=======\nuno\n>>>>>>> conflict\n2\n3\n4\n5\n6\nseven\n";
+        assert_eq!(fs::read_to_string(&file).unwrap(), conflicted);
+        assert!(status::status(&vcs).is_ok());
+        assert!(matches!(
+            jump_to_commit::jump_to_commit(&mut vcs, &master, false),
+            Err(VcsError::MergeInProgress)
+        ));
+        assert!(matches!(
+            new_commit::new_commit(&mut vcs, "merge"),
+            Err(VcsError::UnresolvedConflicts(_))
+        ));
+
+        merge::abort_merge(&vcs).unwrap();
+        assert_eq!(
+            fs::read_to_string(&file).unwrap(),
+            "eins\n2\n3\n4\n5\n6\nseven\n"
+        );
+        assert!(matches!(
+            merge::abort_merge(&vcs),
+            Err(VcsError::NoMergeInProgress)
+        ));
+
+        assert!(merge::merge(&mut vcs, "conflict").is_err());
+        let state = read_merge_state(path).unwrap().unwrap();
+        assert_eq!(state.get_commit(), conflict);
+        fs::write(&file, "eins uno\n2\n3\n4\n5\n6\nseven\n").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "merge").unwrap();
+        assert!(vcs.get_branch_by_name("conflict").is_ok());
+        assert_eq!(vcs.get_current_commit().get_parent_hash().unwrap(), master);
+        assert_eq!(
+            vcs.get_current_commit().get_parent_hashes(),
+            [master.clone(), conflict]
+        );
+        assert!(merge::abort_merge(&vcs).is_err());
+
+        let vcs = read_vcs(vcs.get_dir().to_path_buf()).unwrap();
+        let files = files_from_commit(vcs.get_dir(), vcs.get_current_commit()).unwrap();
+        assert_eq!(files[0].get_data(), b"eins uno\n2\n3\n4\n5\n6\nseven\n");
+
+        fs::remove_dir_all(path).unwrap();
+    }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_merge_unresolved_conflicts() {
+        let path = Path::new("./tests/test_data_merge_unresolved_conflicts");
+        let mut vcs = init::init(path, None).unwrap();
+        let data = path.join("data.bin");
+
+        fs::write(&data, b"\0base").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "first").unwrap();
+
+        new_branch::new_branch(&mut vcs, "feature").unwrap();
+        fs::write(&data, b"\0theirs").unwrap();
+        fs::create_dir(path.join("a")).unwrap();
+        fs::write(path.join("a").join("b"), "dir\n").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "feature").unwrap();
+        jump_to_branch::jump_to_branch(&mut vcs, "master", false).unwrap();
+        fs::write(&data, b"\0ours").unwrap();
+        fs::write(path.join("a"), "file\n").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "master").unwrap();
+
+        let expected = vec![
+            PathBuf::from("a"),
+            PathBuf::from("a/b"),
+            PathBuf::from("data.bin"),
+        ];
+        match merge::merge(&mut vcs, "feature") {
+            Err(VcsError::MergeConflict(paths)) => assert_eq!(paths, expected),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert_eq!(fs::read(&data).unwrap(), b"\0ours");
+        assert_eq!(fs::read_to_string(path.join("a")).unwrap(), "file\n");
+        let state = read_merge_state(path).unwrap().unwrap();
+        assert_eq!(state.get_unresolved(), expected);
+        match new_commit::new_commit(&mut vcs, "merge") {
+            Err(VcsError::UnresolvedConflicts(paths)) => assert_eq!(paths, expected),
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        add::add(&vcs, &[PathBuf::from("data.bin")]).unwrap();
+        match new_commit::new_commit(&mut vcs, "merge") {
+            Err(VcsError::UnresolvedConflicts(paths)) => assert_eq!(paths, expected[..2]),
+            other => panic!("unexpected result {:?}", other),
+        }
+        add::add(&vcs, &[PathBuf::from("a")]).unwrap();
+        new_commit::new_commit(&mut vcs, "merge").unwrap();
+        assert_eq!(vcs.get_current_commit().get_parent_hashes().len(), 2);
+        let files = files_from_commit(vcs.get_dir(), vcs.get_current_commit()).unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(read_merge_state(path).unwrap().is_none());
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_merge_keeps_history() {
+        let path = Path::new("./tests/test_data_merge_history");
+        let mut vcs = init::init(path, None).unwrap();
+
+        new_branch::new_branch(&mut vcs, "feature").unwrap();
+        fs::write(path.join("feature.txt"), "feature").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "feature").unwrap();
+        let feature = vcs.get_current_commit().clone();
+        jump_to_branch::jump_to_branch(&mut vcs, "master", false).unwrap();
+        fs::write(path.join("master.txt"), "master").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "master").unwrap();
+        let master = vcs.get_current_commit().clone();
+
+        merge::merge(&mut vcs, "feature").unwrap();
+        let merge_commit = vcs.get_current_commit().clone();
+        assert!(merge_commit.is_merge());
+        assert_eq!(
+            merge_commit.get_parent_hashes(),
+            [calculate_hash(&master), calculate_hash(&feature)]
+        );
+
+        delete_branch::delete_branch(&mut vcs, "feature", false).unwrap();
+        pack_vcs(path, &vcs).unwrap();
+        let vcs = read_vcs(vcs.get_dir().to_path_buf()).unwrap();
+        let history = vcs.get_history(vcs.get_current_commit()).unwrap();
+        assert_eq!(history.len(), 5);
+        assert!(history.contains(&&feature));
+        assert_eq!(
+            vcs.get_branch_by_commit(&feature).unwrap().get_name(),
+            "master"
+        );
+        assert_eq!(
+            vcs.get_merge_base(&feature, &master).unwrap().get_msg(),
+            "Initial commit"
+        );
+        assert!(files_from_commit(vcs.get_dir(), &feature).is_ok());
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_merge_between_branches() {
+        let path = Path::new("./tests/test_data_merge_branches");
+        let mut vcs = init::init(path, None).unwrap();
+        let commit =
+            |vcs: &mut vcs::library::vcs_repository::VcsRepository, file: &str, data: &str| {
+                fs::write(path.join(file), data).unwrap();
+                add::add(vcs, &[PathBuf::from(".")]).unwrap();
+                new_commit::new_commit(vcs, data).unwrap();
+            };
+
+        new_branch::new_branch(&mut vcs, "develop").unwrap();
+        commit(&mut vcs, "develop.txt", "develop");
+        new_branch::new_branch(&mut vcs, "feature").unwrap();
+        commit(&mut vcs, "feature.txt", "feature");
+        jump_to_branch::jump_to_branch(&mut vcs, "develop", false).unwrap();
+        commit(&mut vcs, "develop.txt", "develop 2");
+        let develop = calculate_hash(vcs.get_current_commit());
+
+        merge::merge(&mut vcs, "feature").unwrap();
+        assert_eq!(vcs.get_current_branch().unwrap().get_name(), "develop");
+        assert_eq!(vcs.get_current_commit().get_parent_hash().unwrap(), develop);
+        assert_eq!(
+            fs::read_to_string(path.join("feature.txt")).unwrap(),
+            "feature"
+        );
+        assert!(vcs.get_branch_by_name("feature").is_ok());
+        assert_eq!(
+            vcs.get_branch_commit(vcs.get_branch_by_name("master").unwrap())
+                .unwrap()
+                .get_msg(),
+            "Initial commit"
+        );
+
+        jump_to_branch::jump_to_branch(&mut vcs, "master", false).unwrap();
+        commit(&mut vcs, "master.txt", "master");
+        jump_to_branch::jump_to_branch(&mut vcs, "develop", false).unwrap();
+        merge::merge(&mut vcs, "master").unwrap();
+        assert!(vcs.get_branch_by_name("master").is_ok());
+        assert!(path.join("master.txt").exists());
+        assert!(matches!(
+            merge::merge(&mut vcs, "master"),
+            Err(VcsError::AlreadyMerged(_))
+        ));
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_delete_branch_and_gc() {
+        let path = Path::new("./tests/test_data_delete_branch");
+        let mut vcs = init::init(path, None).unwrap();
+
+        new_branch::new_branch(&mut vcs, "merged").unwrap();
+        fs::write(path.join("merged.txt"), "merged").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "merged").unwrap();
+        let merged = calculate_hash(vcs.get_current_commit());
+        assert!(matches!(
+            delete_branch::delete_branch(&mut vcs, "merged", false),
+            Err(VcsError::CurrentBranch(_))
+        ));
+        jump_to_branch::jump_to_branch(&mut vcs, "master", false).unwrap();
+        merge::merge(&mut vcs, "merged").unwrap();
+
+        new_branch::new_branch(&mut vcs, "unmerged").unwrap();
+        fs::write(path.join("unmerged.txt"), "unmerged").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "unmerged").unwrap();
+        let unmerged = vcs.get_current_commit().clone();
+        jump_to_branch::jump_to_branch(&mut vcs, "master", false).unwrap();
+
+        assert_eq!(
+            delete_branch::delete_branch(&mut vcs, "merged", false).unwrap(),
+            merged
+        );
+        assert!(matches!(
+            delete_branch::delete_branch(&mut vcs, "unmerged", false),
+            Err(VcsError::UnmergedBranch(_))
+        ));
+        delete_branch::delete_branch(&mut vcs, "unmerged", true).unwrap();
+        assert!(vcs.get_branch_by_name("unmerged").is_err());
+        assert!(vcs.get_commit_by_hash(&calculate_hash(&unmerged)).is_ok());
+
+        assert_eq!(gc::gc(&mut vcs).unwrap(), 2);
+        assert!(vcs.get_commit_by_hash(&merged).is_ok());
+        assert!(vcs.get_commit_by_hash(&calculate_hash(&unmerged)).is_err());
+        let vcs = read_vcs(vcs.get_dir().to_path_buf()).unwrap();
+        assert!(vcs.get_commit_by_hash(&calculate_hash(&unmerged)).is_err());
+        assert!(!vcs::utils::object_store::object_path(path, unmerged.get_tree_hash()).exists());
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_branch_refs() {
+        let path = Path::new("./tests/test_data_refs");
+        let mut vcs = init::init(path, None).unwrap();
+        let initial = calculate_hash(vcs.get_current_commit());
+        let heads = path.join(".vcs").join("refs").join("heads");
+        let head = path.join(".vcs").join("HEAD");
+        pack_vcs(path, &vcs).unwrap();
+        assert_eq!(
+            fs::read_to_string(heads.join("master")).unwrap(),
+            format!("{}\n", initial)
+        );
+        assert_eq!(
+            fs::read_to_string(&head).unwrap(),
+            "ref: refs/heads/master\n"
+        );
+
+        new_branch::new_branch(&mut vcs, "feature").unwrap();
+        fs::write(path.join("file.txt"), "data").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "feature").unwrap();
+        let feature = calculate_hash(vcs.get_current_commit());
+        pack_vcs(path, &vcs).unwrap();
+        assert_eq!(
+            fs::read_to_string(heads.join("feature")).unwrap(),
+            format!("{}\n", feature)
+        );
+        assert_eq!(
+            fs::read_to_string(&head).unwrap(),
+            "ref: refs/heads/feature\n"
+        );
+
+        let first = vcs
+            .get_current_commit()
+            .get_parent_hash()
+            .unwrap()
+            .to_string();
+        jump_to_commit::jump_to_commit(&mut vcs, &first, false).unwrap();
+        assert_eq!(vcs.get_head(), &Head::Detached(first.clone()));
+        assert!(matches!(
+            vcs.get_current_branch(),
+            Err(VcsError::DetachedHead(_))
+        ));
+        pack_vcs(path, &vcs).unwrap();
+        assert_eq!(fs::read_to_string(&head).unwrap(), format!("{}\n", first));
+
+        let vcs = read_vcs(path.to_path_buf()).unwrap();
+        assert_eq!(calculate_hash(vcs.get_current_commit()), first);
+        assert_eq!(
+            vcs.get_branch_by_name("feature").unwrap().get_commit_hash(),
+            feature
+        );
+        assert!(vcs
+            .get_branches()
+            .iter()
+            .map(|branch| branch.get_name())
+            .eq(["feature", "master"]));
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_detached_head() {
+        let path = Path::new("./tests/test_data_detached");
+        let mut vcs = init::init(path, None).unwrap();
+        let initial = calculate_hash(vcs.get_current_commit());
+        fs::write(path.join("file.txt"), "master").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "master").unwrap();
+        let master = calculate_hash(vcs.get_current_commit());
+
+        jump_to_commit::jump_to_commit(&mut vcs, &initial, false).unwrap();
+        fs::write(path.join("file.txt"), "detached").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "detached").unwrap();
+        let detached = calculate_hash(vcs.get_current_commit());
+        assert_eq!(vcs.get_head(), &Head::Detached(detached.clone()));
+        assert_eq!(vcs.get_current_commit().get_parent_hash().unwrap(), initial);
+        assert_eq!(
+            vcs.get_branch_by_name("master").unwrap().get_commit_hash(),
+            master
+        );
+        assert_eq!(vcs.get_detached_commits().unwrap(), vec![detached.clone()]);
+
+        match jump_to_branch::jump_to_branch(&mut vcs, "master", false) {
+            Err(VcsError::DetachedCommits(hashes)) => assert_eq!(hashes, vec![detached.clone()]),
+            _ => panic!("detached commits should not be left silently"),
+        }
+        assert_eq!(calculate_hash(vcs.get_current_commit()), detached);
+
+        new_branch::new_branch(&mut vcs, "rescue").unwrap();
+        assert!(vcs.get_detached_commits().unwrap().is_empty());
+        jump_to_branch::jump_to_branch(&mut vcs, "master", false).unwrap();
+        let rescue = vcs
+            .get_branch_commit(vcs.get_branch_by_name("rescue").unwrap())
+            .unwrap();
+        assert!(vcs.get_ancestors(rescue).unwrap().contains(&detached));
+
+        jump_to_commit::jump_to_commit(&mut vcs, &initial, false).unwrap();
+        fs::write(path.join("file.txt"), "lost").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "lost").unwrap();
+        let lost = calculate_hash(vcs.get_current_commit());
+        assert!(jump_to_commit::jump_to_commit(&mut vcs, &master, false).is_err());
+        jump_to_commit::jump_to_commit(&mut vcs, &master, true).unwrap();
+        assert_eq!(vcs.get_head(), &Head::Branch("master".to_string()));
+        assert_eq!(gc::gc(&mut vcs).unwrap(), 1);
+        assert!(vcs.get_commit_by_hash(&lost).is_err());
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_branch_management() {
+        let path = Path::new("./tests/test_data_branch_management");
+        let mut vcs = init::init(path, None).unwrap();
+        let heads = path.join(".vcs").join("refs").join("heads");
+
+        assert!(matches!(
+            new_branch::new_branch(&mut vcs, "HEAD"),
+            Err(VcsError::InvalidBranchName { .. })
+        ));
+        assert!(matches!(
+            new_branch::new_branch(&mut vcs, "bad name"),
+            Err(VcsError::InvalidBranchName { .. })
+        ));
+        new_branch::new_branch(&mut vcs, "fix/one").unwrap();
+        let tip = vcs
+            .get_current_branch()
+            .unwrap()
+            .get_commit_hash()
+            .to_string();
+        assert!(matches!(
+            new_branch::new_branch(&mut vcs, "fix"),
+            Err(VcsError::InvalidBranchName { .. })
+        ));
+        assert!(matches!(
+            new_branch::new_branch(&mut vcs, "fix/one/two"),
+            Err(VcsError::InvalidBranchName { .. })
+        ));
+        pack_vcs(path, &vcs).unwrap();
+        assert!(heads.join("fix").join("one").exists());
+
+        assert!(matches!(
+            rename_branch::rename_branch(&mut vcs, "missing", "other"),
+            Err(VcsError::NoBranch(_))
+        ));
+        assert!(matches!(
+            rename_branch::rename_branch(&mut vcs, "fix/one", "master"),
+            Err(VcsError::BranchExists(_))
+        ));
+        assert!(matches!(
+            rename_branch::rename_branch(&mut vcs, "fix/one", "fix/"),
+            Err(VcsError::InvalidBranchName { .. })
+        ));
+        rename_branch::rename_branch(&mut vcs, "fix/one", "fix").unwrap();
+        assert_eq!(vcs.get_head(), &Head::Branch("fix".to_string()));
+        assert_eq!(vcs.get_current_branch().unwrap().get_commit_hash(), tip);
+        assert!(vcs
+            .get_branches()
+            .iter()
+            .map(|branch| branch.get_name())
+            .eq(["fix", "master"]));
+        assert!(list_branches::list_branches(&vcs).is_ok());
+        assert!(show_branch::show_branch(&vcs, "fix").is_ok());
+        assert!(show_branch::show_branch(&vcs, "fix/one").is_err());
+
+        pack_vcs(path, &vcs).unwrap();
+        assert_eq!(
+            fs::read_to_string(heads.join("fix")).unwrap(),
+            format!("{}\n", tip)
+        );
+        assert_eq!(
+            fs::read_to_string(path.join(".vcs").join("HEAD")).unwrap(),
+            "ref: refs/heads/fix\n"
+        );
+        let vcs = read_vcs(path.to_path_buf()).unwrap();
+        assert!(vcs
+            .get_branches()
+            .iter()
+            .map(|branch| branch.get_name())
+            .eq(["fix", "master"]));
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_tags() {
+        let path = Path::new("./tests/test_data_tags");
+        let mut vcs = init::init(path, None).unwrap();
+        let initial = calculate_hash(vcs.get_current_commit());
+        fs::write(path.join("file.txt"), "one").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "one").unwrap();
+        let one = calculate_hash(vcs.get_current_commit());
+
+        assert_eq!(
+            create_tag::create_tag(&mut vcs, "v0", Some(&initial[..8]), None).unwrap(),
+            initial
+        );
+        let annotation = Annotation::init("tester", "First release");
+        assert_eq!(
+            create_tag::create_tag(&mut vcs, "v1", None, Some(annotation.clone())).unwrap(),
+            one
+        );
+        assert!(matches!(
+            create_tag::create_tag(&mut vcs, "v1", None, None),
+            Err(VcsError::TagExists(_))
+        ));
+        assert!(matches!(
+            create_tag::create_tag(&mut vcs, "v1/rc", None, None),
+            Err(VcsError::InvalidTagName { .. })
+        ));
+        assert!(matches!(
+            create_tag::create_tag(&mut vcs, "HEAD", None, None),
+            Err(VcsError::InvalidTagName { .. })
+        ));
+        assert!(matches!(
+            create_tag::create_tag(&mut vcs, "v2", Some("v3"), None),
+            Err(VcsError::NoCommit(_))
+        ));
+        assert!(create_tag::create_tag(&mut vcs, "v0-copy", Some("v0"), None).is_ok());
+        assert_eq!(
+            delete_tag::delete_tag(&mut vcs, "v0-copy").unwrap(),
+            initial
+        );
+        assert!(matches!(
+            delete_tag::delete_tag(&mut vcs, "v0-copy"),
+            Err(VcsError::NoTag(_))
+        ));
+
+        assert!(diff::diff_commits(&vcs, "v0", "v1", 3)
+            .unwrap()
+            .contains("+one"));
+        jump_to_commit::jump_to_commit(&mut vcs, "v0", false).unwrap();
+        assert_eq!(calculate_hash(vcs.get_current_commit()), initial);
+        fs::write(path.join("file.txt"), "wip").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "wip").unwrap();
+        let wip = create_tag::create_tag(&mut vcs, "wip", None, None).unwrap();
+        jump_to_commit::jump_to_commit(&mut vcs, "v1", false).unwrap();
+        assert_eq!(vcs.get_head(), &Head::Branch("master".to_string()));
+
+        pack_vcs(path, &vcs).unwrap();
+        let tags = path.join(".vcs").join("refs").join("tags");
+        assert_eq!(
+            fs::read_to_string(tags.join("v0")).unwrap(),
+            format!("{}\n", initial)
+        );
+        assert_ne!(
+            fs::read_to_string(tags.join("v1")).unwrap(),
+            format!("{}\n", one)
+        );
+
+        let mut vcs = read_vcs(path.to_path_buf()).unwrap();
+        let tag = vcs.get_tag_by_name("v1").unwrap();
+        assert_eq!(tag.get_commit_hash(), one);
+        assert_eq!(tag.get_annotation(), Some(&annotation));
+        assert!(vcs
+            .get_tag_by_name("v0")
+            .unwrap()
+            .get_annotation()
+            .is_none());
+
+        assert_eq!(gc::gc(&mut vcs).unwrap(), 0);
+        assert!(vcs.get_commit_by_hash(&wip).is_ok());
+        let vcs = read_vcs(path.to_path_buf()).unwrap();
+        assert_eq!(
+            vcs.get_tag_by_name("v1").unwrap().get_annotation(),
+            Some(&annotation)
+        );
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_revisions() {
+        let path = Path::new("./tests/test_data_revisions");
+        let mut vcs = init::init(path, None).unwrap();
+        let mut hashes = vec![calculate_hash(vcs.get_current_commit())];
+        for ind in 0..3 {
+            fs::write(path.join("file.txt"), ind.to_string()).unwrap();
+            add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+            new_commit::new_commit(&mut vcs, &ind.to_string()).unwrap();
+            hashes.push(calculate_hash(vcs.get_current_commit()));
+        }
+        create_tag::create_tag(&mut vcs, "v1", Some("HEAD~2"), None).unwrap();
+        new_branch::new_branch(&mut vcs, "feature").unwrap();
+        fs::write(path.join("other.txt"), "feature").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "feature").unwrap();
+        let feature = calculate_hash(vcs.get_current_commit());
+        jump_to_branch::jump_to_branch(&mut vcs, "master", false).unwrap();
+        merge::merge(&mut vcs, "feature").unwrap();
+        let merge_hash = calculate_hash(vcs.get_current_commit());
+
+        let resolve = |expr: &str| calculate_hash(vcs.resolve_commit(expr).unwrap());
+        assert_eq!(resolve("HEAD"), merge_hash);
+        assert_eq!(resolve("HEAD^0"), merge_hash);
+        assert_eq!(resolve("master^"), hashes[3]);
+        assert_eq!(resolve("HEAD~3"), hashes[1]);
+        assert_eq!(resolve("HEAD^2"), feature);
+        assert_eq!(resolve("master^2~2"), hashes[3]);
+        assert_eq!(resolve("feature"), feature);
+        assert_eq!(resolve("v1"), hashes[1]);
+        assert_eq!(resolve("v1~1"), hashes[0]);
+        assert_eq!(resolve(&hashes[2][..6]), hashes[2]);
+        assert!(matches!(
+            vcs.resolve_commit("HEAD~4^2"),
+            Err(VcsError::NoParent(_))
+        ));
+        assert!(matches!(
+            vcs.resolve_commit("HEAD~9"),
+            Err(VcsError::NoParent(_))
+        ));
+        assert!(matches!(
+            vcs.resolve_commit("missing~1"),
+            Err(VcsError::NoCommit(_))
+        ));
+
+        let history = |expr: &str| -> Vec<String> {
+            let range = RevisionRange::parse(expr).unwrap();
+            vcs.get_range_history(&range)
+                .unwrap()
+                .into_iter()
+                .map(calculate_hash)
+                .collect()
+        };
+        assert_eq!(
+            history("v1..HEAD~1"),
+            vec![hashes[3].clone(), hashes[2].clone()]
+        );
+        assert_eq!(history("master..feature"), Vec::<String>::new());
+        assert_eq!(history("master~1..").len(), 3);
+        assert_eq!(history("v1").len(), 2);
+        assert!(log::log(
+            &vcs,
+            &RevisionRange::parse("v1..").unwrap(),
+            &LogOptions::init()
+        )
+        .is_ok());
+
+        jump_to_commit::jump_to_commit(&mut vcs, "HEAD~2", false).unwrap();
+        assert_eq!(calculate_hash(vcs.get_current_commit()), hashes[2]);
+        assert!(diff::diff_commits(&vcs, "v1", "master", 3)
+            .unwrap()
+            .contains("+feature"));
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_config_command() {
+        let path = Path::new("./tests/test_data_config_command");
+        let vcs = init::init(path, None).unwrap();
+        let config_path = repo_config_path(path);
+
+        config::set_config(&config_path, "Diff.Context", "0").unwrap();
+        assert!(config::set_config(&config_path, "diff.context", "all").is_err());
+        assert!(config::set_config(&config_path, "context", "1").is_err());
+        for value in ["a\nb", "a\rb", "a\r\nb"] {
+            assert!(matches!(
+                config::set_config(&config_path, "user.name", value),
+                Err(VcsError::InvalidConfigValue { .. })
+            ));
+        }
+        assert_eq!(
+            fs::read_to_string(&config_path).unwrap(),
+            "diff.context = 0\n"
+        );
+        let settings = Config::read(path).unwrap();
+        assert_eq!(config::get_config(&settings, "diff.context").unwrap(), "0");
+        assert_eq!(settings.get_diff_context().unwrap(), 0);
+
+        fs::write(path.join("notes.txt"), "a\nb\nc\n").unwrap();
+        fs::write(path.join("build.log"), "log").unwrap();
+        fs::write(path.join("excludes"), "*.log\n").unwrap();
+        config::set_config(&config_path, "core.excludesfile", "excludes").unwrap();
+        assert!(matches!(
+            add::add(&vcs, &[PathBuf::from("build.log")]),
+            Err(VcsError::IgnoredPath(_))
+        ));
+        let diff = diff::diff_working_tree(&vcs, settings.get_diff_context().unwrap()).unwrap();
+        assert!(diff.contains("@@ -0,0 +1,3 @@"));
+        assert!(!diff.contains("build.log"));
+
+        config::unset_config(&config_path, "core.excludesfile").unwrap();
+        assert!(matches!(
+            config::unset_config(&config_path, "core.excludesfile"),
+            Err(VcsError::NoConfigValue(_))
+        ));
+        assert!(diff::diff_working_tree(&vcs, 0)
+            .unwrap()
+            .contains("build.log"));
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_initial_branch() {
+        let path = Path::new("./tests/test_data_initial_branch");
+        assert!(matches!(
+            init::init(path, Some("bad..name")),
+            Err(VcsError::InvalidBranchName { .. })
+        ));
+        assert!(!path.join(".vcs").exists());
+
+        let mut vcs = init::init(path, Some("trunk")).unwrap();
+        assert_eq!(vcs.get_head(), &Head::Branch("trunk".to_string()));
+        assert_eq!(vcs.get_default_branch(), "trunk");
+        assert!(!vcs.exists_branch("master"));
+        pack_vcs(path, &vcs).unwrap();
+
+        new_branch::new_branch(&mut vcs, "feature").unwrap();
+        assert!(matches!(
+            delete_branch::delete_branch(&mut vcs, "trunk", true),
+            Err(VcsError::DefaultBranch(_))
+        ));
+        rename_branch::rename_branch(&mut vcs, "trunk", "main").unwrap();
+        assert_eq!(vcs.get_default_branch(), "main");
+        pack_vcs(path, &vcs).unwrap();
+
+        let vcs = read_vcs(path.to_path_buf()).unwrap();
+        assert_eq!(vcs.get_default_branch(), "main");
+        assert_eq!(vcs.get_head(), &Head::Branch("feature".to_string()));
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_log_options() {
+        let path = Path::new("./tests/test_data_log_options");
+        let mut vcs = init::init(path, None).unwrap();
+        fs::write(path.join("a.txt"), "a").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "add a").unwrap();
+        new_branch::new_branch(&mut vcs, "feature").unwrap();
+        fs::create_dir_all(path.join("docs")).unwrap();
+        fs::write(path.join("docs").join("b.txt"), "b").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "add docs").unwrap();
+        jump_to_branch::jump_to_branch(&mut vcs, "master", false).unwrap();
+        fs::write(path.join("c.txt"), "c").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "add c").unwrap();
+        merge::merge(&mut vcs, "feature").unwrap();
+        create_tag::create_tag(&mut vcs, "v1", Some("HEAD~1"), None).unwrap();
+        let head = RevisionRange::parse("HEAD").unwrap();
+
+        let mut options = LogOptions::init();
+        options.set_format(LogFormat::Oneline);
+        options.set_graph(true);
+        let graph = log::log(&vcs, &head, &options).unwrap();
+        let lines: Vec<&str> = graph.lines().collect();
+        assert_eq!(lines.len(), 8);
+        assert!(
+            lines[0].starts_with("* ")
+                && lines[0].ends_with("(HEAD -> master) Merge branch feature")
+        );
+        assert_eq!(lines[1], "|\\");
+        assert!(lines[2].starts_with("* | ") && lines[2].ends_with("(tag: v1) add c"));
+        assert!(lines[3].starts_with("| * ") && lines[3].ends_with("(feature) add docs"));
+        assert_eq!(lines[5], "|/");
+
+        let mut options = LogOptions::init();
+        options.set_format(LogFormat::Template("%s|%an|%p%n".to_string()));
+        options.set_paths(vec![PathBuf::from("docs")]);
+        let parent = calculate_hash(vcs.resolve_commit("feature~1").unwrap());
+        let author = vcs
+            .get_current_commit()
+            .get_author()
+            .unwrap()
+            .get_name()
+            .to_string();
+        let text = log::log(&vcs, &head, &options).unwrap();
+        assert!(text.starts_with("Merge branch feature|"));
+        assert!(text.ends_with(&format!("\n\nadd docs|{}|{}\n\n", author, &parent[..7])));
+
+        let mut options = LogOptions::init();
+        options.set_message(Some("add".to_string()));
+        options.set_max_count(Some(2));
+        let messages = |options: &LogOptions| -> Vec<String> {
+            log::log_commits(&vcs, &head, options)
+                .unwrap()
+                .iter()
+                .map(|c| c.get_msg().to_string())
+                .collect()
+        };
+        assert_eq!(messages(&options), vec!["add c", "add docs"]);
+        let date = *vcs.get_current_commit().get_date();
+        options.set_max_count(None);
+        options.set_dates(Some(date), None);
+        assert!(messages(&options).is_empty());
+        options.set_dates(None, Some(date));
+        assert_eq!(messages(&options).len(), 3);
+
+        jump_to_commit::jump_to_commit(&mut vcs, "HEAD~1", false).unwrap();
+        let mut options = LogOptions::init();
+        assert_eq!(log::log_commits(&vcs, &head, &options).unwrap().len(), 3);
+        options.set_all(true);
+        assert_eq!(log::log_commits(&vcs, &head, &options).unwrap().len(), 6);
+        assert!(matches!(
+            LogOptions::parse_date("01.02.2024"),
+            Err(VcsError::InvalidDate(_))
+        ));
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_json_reports() {
+        let path = Path::new("./tests/test_data_json_reports");
+        let mut vcs = init::init(path, None).unwrap();
+        let file = path.join("file.txt");
+
+        fs::write(&file, "1\n").unwrap();
+        let status = status::status(&vcs).unwrap();
+        assert_eq!(status.get_untracked(), [PathBuf::from("file.txt")]);
+        assert!(status.to_text().ends_with("Untracked files:\n  file.txt\n"));
+        let json = serde_json::to_value(&status).unwrap();
+        assert_eq!(json["head"]["branch"], "master");
+        assert_eq!(json["untracked"][0], "file.txt");
+        assert!(json["merge"].is_null());
+
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        let commit = new_commit::new_commit(&mut vcs, "first").unwrap();
+        assert_eq!(
+            commit.get_changes().get_added(),
+            [PathBuf::from("file.txt")]
+        );
+        assert!(commit.to_text().starts_with("[master "));
+        let json = serde_json::to_value(&commit).unwrap();
+        assert_eq!(
+            json["head"]["commit"],
+            calculate_hash(vcs.get_current_commit())
+        );
+        assert_eq!(json["message"], "first");
+        assert!(json["merged_branch"].is_null());
+
+        new_branch::new_branch(&mut vcs, "feature").unwrap();
+        fs::write(&file, "feature\n").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "feature").unwrap();
+        jump_to_branch::jump_to_branch(&mut vcs, "master", false).unwrap();
+        fs::write(&file, "master\n").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        new_commit::new_commit(&mut vcs, "master").unwrap();
+
+        let error = merge::merge(&mut vcs, "feature").unwrap_err();
+        let json = serde_json::to_value(ErrorReport::init(&error, vec![])).unwrap();
+        assert_eq!(json["kind"], "merge_conflict");
+        assert_eq!(json["paths"][0], "file.txt");
+        assert!(json.get("commits").is_none());
+
+        fs::write(&file, "merged\n").unwrap();
+        add::add(&vcs, &[PathBuf::from(".")]).unwrap();
+        let json =
+            serde_json::to_value(new_commit::new_commit(&mut vcs, "merge").unwrap()).unwrap();
+        assert_eq!(json["merged_branch"], "feature");
+        assert_eq!(json["parents"].as_array().unwrap().len(), 2);
+
+        let report = log::log_report(
+            &vcs,
+            &RevisionRange::parse("HEAD").unwrap(),
+            &LogOptions::init(),
+        )
+        .unwrap();
+        let entry = &report.get_commits()[0];
+        assert_eq!(entry.get_hash(), calculate_hash(vcs.get_current_commit()));
+        assert_eq!(entry.get_refs(), ["HEAD -> master"]);
+        assert_eq!(
+            entry.get_changes().unwrap().get_modified(),
+            [PathBuf::from("file.txt")]
+        );
+        assert_eq!(
+            report.to_text(),
+            log::log(
+                &vcs,
+                &RevisionRange::parse("HEAD").unwrap(),
+                &LogOptions::init()
+            )
+            .unwrap()
+        );
+
+        let branches = list_branches::list_branches(&vcs).unwrap();
+        assert!(branches
+            .get_branches()
+            .iter()
+            .any(|branch| branch.get_name() == "master" && branch.is_current()));
+        assert_eq!(VcsError::UncommittedFiles.get_kind(), "uncommitted_files");
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_exit_status() {
+        let path = Path::new("./tests/test_data_exit_status");
+        fs::create_dir_all(path).unwrap();
+        let run = |args: &[&str]| {
+            std::process::Command::new(env!("CARGO_BIN_EXE_vcs"))
+                .args(args)
+                .current_dir(path)
+                .output()
+                .unwrap()
+        };
+
+        let output = run(&["init", "--path", "."]);
+        assert!(output.status.success());
+        let output = run(&["jump", "--branch", "missing"]);
+        assert_eq!(output.status.code(), Some(1));
+        let output = run(&["--json", "jump", "--branch", "missing"]);
+        assert_eq!(output.status.code(), Some(1));
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(report["ok"], false);
+        assert_eq!(report["error"]["kind"], "no_branch");
+        let output = run(&["--json", "status"]);
+        assert!(output.status.success());
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_required_arguments() {
+        assert!(CommandParser::try_parse_from(["vcs", "jump"]).is_err());
+        assert!(CommandParser::try_parse_from(["vcs", "jump", "--force"]).is_err());
+        assert!(CommandParser::try_parse_from(["vcs", "jump", "--branch", "master"]).is_ok());
+        assert!(CommandParser::try_parse_from(["vcs", "jump", "--commit", "HEAD~1"]).is_ok());
+        assert!(CommandParser::try_parse_from(["vcs", "--json", "merge"]).is_err());
+        assert!(CommandParser::try_parse_from(["vcs", "merge", "--abort"]).is_ok());
+        assert!(CommandParser::try_parse_from(["vcs", "merge", "--branch", "feature"]).is_ok());
+    }
+}
@@ -1,37 +1,48 @@
-#[warn(unreachable_code)]
-#[warn(dead_code)]
-#[warn(unused_variables)]
-
-use std::path::Path;
-use vcs::library::files::File;
-use vcs::library::commit::Commit;
-use vcs::library::branch::Branch;
-use vcs::utils::operation_hash::calculate_hash;
+use std::collections::HashSet;
 use std::fs;
 use std::io::Write;
+use std::path::Path;
+use vcs::library::branch::Branch;
+use vcs::library::commit::Commit;
+use vcs::library::config::{ColorMode, Config};
+use vcs::library::error::VcsError;
+use vcs::library::files::File;
+use vcs::library::head::Head;
+use vcs::library::identity::Identity;
+use vcs::library::lock::{exclusive_lock_path, LockMode, RepositoryLock};
+use vcs::library::revision::{AncestryStep, Revision, RevisionRange};
 use vcs::library::vcs_repository::VcsRepository;
+use vcs::utils::diff::{diff_lines, unified_diff, DiffLine};
+use vcs::utils::extract_files::{files_from_commit, read_vcs, read_vcs_shared};
+use vcs::utils::object_store::{
+    hash_data, object_path, objects_dir, put_object, read_object, remove_unreferenced,
+};
+use vcs::utils::operation_hash::calculate_hash;
+use vcs::utils::pack_files::pack_vcs;
+use vcs::utils::three_way_merge::{has_conflict_markers, merge_texts};
+use vcs::utils::work_with_config::{read_config_file, write_config_value};
+use vcs::utils::work_with_trees::{flatten_tree, read_tree};
 
 #[test]
 fn test_file_init() {
     let name = "text_file.txt";
     let path = Path::new("./tests/test_data/text_file.txt");
     let data: Vec<u8> = vec![97, 98, 99];
-    let file = File::init(&path);
+    let file = File::init(path).unwrap();
     assert_eq!(file.get_name(), name);
     assert_eq!(file.get_path().to_str(), path.to_str());
     assert_eq!(file.get_data().clone(), data);
 }
 
 #[test]
-#[should_panic(expected = "Cannot read the file")]
 fn test_file_give_not_file() {
     let path = Path::new("./src/library");
-    File::init(&path);
+    assert!(matches!(File::init(path), Err(VcsError::Io { .. })));
 }
 
 #[test]
 fn test_commit_init() {
-    let path = Path::new(".\\tests\\test_data\\repo_with_vcs1");
+    let path = Path::new("./tests/test_data/repo_with_vcs1");
     let msg = "Hello";
 
     let commit = Commit::init(path, msg, None, true);
@@ -40,142 +51,772 @@ fn test_commit_init() {
 
     let hash = calculate_hash(&commit);
 
-    let path = path.join(".vcs").join("objects").join(hash.to_string());
-    assert!(path.exists());
-
-    let path = path.join("aaaa.txt");
-    assert!(path.exists());
-
-    let path = Path::new(".\\tests\\test_data\\repo_with_vcs1");
+    assert!(object_path(path, commit.get_tree_hash()).exists());
+    let tree = read_tree(path, commit.get_tree_hash()).unwrap();
+    let names: Vec<&str> = tree
+        .get_entries()
+        .iter()
+        .map(|entry| entry.get_name())
+        .collect();
+    assert_eq!(names, vec!["aaaa.txt", "aaaba.txt"]);
+
+    let blob = hash_data(&[98, 99, 100, 101]);
+    assert!(object_path(path, &blob).exists());
+    for entry in tree.get_entries() {
+        assert_eq!(entry.get_hash(), blob);
+    }
 
     let commit2 = Commit::init(path, msg, None, false);
     assert!(commit2.is_ok());
-    let commit = commit2.unwrap();
+    assert_ne!(calculate_hash(&commit2.unwrap()), hash);
 
-    fs::remove_dir_all(Path::new(".\\tests\\test_data\\repo_with_vcs1\\.vcs\\objects"));
+    fs::remove_dir_all(Path::new("./tests/test_data/repo_with_vcs1/.vcs")).unwrap();
+}
+
+#[test]
+fn test_commit_hash() {
+    let path = Path::new("./tests/test_data_hash");
+    fs::create_dir_all(path).unwrap();
+    let commit = Commit::init(path, "Hello", None, true).unwrap();
+
+    let hash = calculate_hash(&commit);
+    assert_eq!(hash.len(), 64);
+    assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+    assert_eq!(hash, hash_data(commit.get_canonical_form().as_bytes()));
+    assert!(commit.get_canonical_form().ends_with("\n\nHello"));
+
+    let json = serde_json::to_string(&commit).unwrap();
+    let restored: Commit = serde_json::from_str(&json).unwrap();
+    assert_eq!(calculate_hash(&restored), hash);
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    value["is_first_in_branch"] = serde_json::json!(false);
+    let not_first: Commit = serde_json::from_value(value).unwrap();
+    assert_ne!(calculate_hash(&not_first), hash);
+    assert!(commit.get_canonical_form().contains("\nfirst-in-branch\n"));
+
+    let child = Commit::init(path, "Hello", Some(hash.clone()), false).unwrap();
+    assert!(child.get_canonical_form().starts_with(&format!(
+        "tree {}\nparent {}\n",
+        child.get_tree_hash(),
+        hash
+    )));
+
+    fs::remove_dir_all(path).unwrap();
 }
 
 #[test]
 fn test_merge_init() {
-    let path = Path::new(".\\tests\\test_data\\repo_with_vcs2");
+    let path = Path::new("./tests/test_data/repo_with_vcs2");
 
     let mut output = fs::File::create(path.join("aaaba.txt")).unwrap();
     let data: Vec<u8> = vec![97, 97, 97, 97];
-    output.write_all(&data);
+    output.write_all(&data).unwrap();
     let commit1 = Commit::init(path, "first", None, true).unwrap();
 
-    fs::File::create(path.join("add_file.txt"));
-    let mut output = fs::File::create(path.join ("aaaba.txt")).unwrap();
+    fs::File::create(path.join("add_file.txt")).unwrap();
+    let mut output = fs::File::create(path.join("aaaba.txt")).unwrap();
     let data: Vec<u8> = vec![98, 99, 100, 101];
-    output.write_all(&data);
+    output.write_all(&data).unwrap();
     let commit2 = Commit::init(path, "first", None, true).unwrap();
 
-    let commit3 = Commit::merge_init(&commit1, &commit2, "no-branch").unwrap();
+    let merger = Identity::init("Merger", "merger@example.com");
+    let commit3 = Commit::merge_init(
+        &commit1,
+        commit2.get_tree_hash(),
+        &commit2,
+        "no-branch",
+        &merger,
+    );
 
     assert!(commit3.get_parent_hash().is_ok());
     assert_eq!(commit3.get_parent_hash().unwrap(), calculate_hash(&commit1));
     assert_eq!(commit3.get_msg(), "Merge branch no-branch");
+    assert_eq!(commit3.get_parent_hashes()[1], calculate_hash(&commit2));
+    assert_eq!(commit3.get_author(), Some(&merger));
+    assert_eq!(commit3.get_committer(), Some(&merger));
 
-    let hash = calculate_hash(&commit3);
-
-    let path = path.join(".vcs").join("objects").join(hash.to_string());
-    assert!(path.exists());
-    let add_file = path.join("add_file.txt");
-    let mod_file = path.join("aaaba.txt");
-    assert!(add_file.exists());
-    assert!(mod_file.exists());
+    let files = flatten_tree(path, commit3.get_tree_hash()).unwrap();
+    assert!(files.contains_key(Path::new("add_file.txt")));
+    assert_eq!(files[Path::new("aaaba.txt")].1, hash_data(&data));
+    assert!(object_path(path, &hash_data(&data)).exists());
 
-    fs::remove_dir_all(Path::new(".\\tests\\test_data\\repo_with_vcs2\\.vcs\\objects"));
-    fs::remove_file(Path::new(".\\tests\\test_data\\repo_with_vcs2\\add_file.txt"));
+    fs::remove_dir_all(Path::new("./tests/test_data/repo_with_vcs2/.vcs")).unwrap();
+    fs::remove_file(Path::new("./tests/test_data/repo_with_vcs2/add_file.txt")).unwrap();
 }
 
-
 #[test]
 pub fn test_add_branch() {
     let path = Path::new("./tests/test_data/repo");
 
-    let vcs = VcsRepository::init(path);
+    let vcs = VcsRepository::init(path, "master");
     assert!(vcs.is_ok());
     let mut vcs = vcs.unwrap();
-    
-    fs::File::create(path.join("add_file.txt"));
+
+    fs::File::create(path.join("add_file.txt")).unwrap();
     let mut output = fs::File::create(path.join("aaaa.txt")).unwrap();
     let data: Vec<u8> = vec![98, 99, 100, 101];
-    output.write_all(&data);
-
-    let commit = Commit::init(path, "my_commit", 
-                                Some(calculate_hash(vcs.get_current_commit())), 
-                                true);
+    output.write_all(&data).unwrap();
+
+    let commit = Commit::init(
+        path,
+        "my_commit",
+        Some(calculate_hash(vcs.get_current_commit())),
+        true,
+    );
     assert!(commit.is_ok());
     let commit = commit.unwrap();
     let hash = calculate_hash(&commit);
 
-    let branch = Branch::init(&commit, "branch_name");
-    assert_eq!(branch.get_name(), "branch_name");
+    vcs.add_branch("branch_name", &commit);
 
-    vcs.add_branch(&branch);
-
-    assert_eq!(vcs.get_current_branch().clone(), branch);
-    assert!(vcs.get_commit_by_hash(hash).is_ok());
-    assert_eq!(vcs.get_commit_by_hash(hash).unwrap().clone(), commit);
+    let branch = vcs.get_current_branch().unwrap().clone();
+    assert_eq!(branch, Branch::init("branch_name", &hash));
+    assert_eq!(vcs.get_head(), &Head::Branch("branch_name".to_string()));
+    assert!(vcs.get_commit_by_hash(&hash).is_ok());
+    assert_eq!(vcs.get_commit_by_hash(&hash).unwrap().clone(), commit);
     assert_eq!(vcs.get_current_commit().clone(), commit);
 
-    fs::remove_dir_all(Path::new("./tests/test_data/repo/.vcs"));
-    fs::remove_file(Path::new("./tests/test_data/repo/add_file.txt"));    
-    fs::remove_file(Path::new("./tests/test_data/repo/aaaa.txt"));    
+    fs::remove_dir_all(Path::new("./tests/test_data/repo/.vcs")).unwrap();
+    fs::remove_file(Path::new("./tests/test_data/repo/add_file.txt")).unwrap();
+    fs::remove_file(Path::new("./tests/test_data/repo/aaaa.txt")).unwrap();
 }
 
 #[test]
 pub fn test_delete_branch() {
     let path = Path::new("./tests/test_data/repo1");
 
-    let vcs = VcsRepository::init(path);
+    let vcs = VcsRepository::init(path, "master");
     assert!(vcs.is_ok());
     let mut vcs = vcs.unwrap();
-    
-    fs::File::create(path.join("add_file.txt"));
+
+    fs::File::create(path.join("add_file.txt")).unwrap();
     let mut output = fs::File::create(path.join("aaaa.txt")).unwrap();
     let data: Vec<u8> = vec![98, 99, 100, 101];
-    output.write_all(&data);
-
-    let commit = Commit::init(path, "commit", 
-                                Some(calculate_hash(vcs.get_current_commit())), 
-                                true);
+    output.write_all(&data).unwrap();
+
+    let commit = Commit::init(
+        path,
+        "commit",
+        Some(calculate_hash(vcs.get_current_commit())),
+        true,
+    );
     assert!(commit.is_ok());
     let commit = commit.unwrap();
     let hash1 = calculate_hash(&commit);
 
-    let mut branch = Branch::init(&commit, "branch_name");
-    vcs.add_branch(&branch);
+    vcs.add_branch("branch_name", &commit);
 
-    fs::File::create(path.join("new_file.txt"));
-    let commit1 = Commit::init(path, "my_commit", 
-                                Some(hash1), 
-                                 false);
+    fs::File::create(path.join("new_file.txt")).unwrap();
+    let commit1 = Commit::init(path, "my_commit", Some(hash1.clone()), false);
     assert!(commit1.is_ok());
     let commit1 = commit1.unwrap();
     let hash2 = calculate_hash(&commit1);
- 
-    vcs.get_mut_current_branch().add_commit(&commit1);
-    assert_eq!(vcs.get_current_branch().get_first_commit().clone(), commit);
-    vcs.add_commit(&commit);
-    assert_eq!(vcs.get_current_branch().get_first_commit().clone(), commit);
-    assert_eq!(vcs.get_current_branch().get_last_commit().clone(), commit1);
 
-    vcs.delete_branch("branch_name");
+    vcs.update_head(&commit1);
+    let branch = vcs.get_current_branch().unwrap().clone();
+    assert_eq!(vcs.get_first_commit(&branch).unwrap().clone(), commit);
+    assert_eq!(vcs.get_last_branch_commit().unwrap().clone(), commit1);
+
+    vcs.change_head(Head::Branch("master".to_string()));
+    vcs.delete_branch("branch_name").unwrap();
+    assert!(vcs.get_commit_by_hash(&hash1).is_ok());
+    assert_eq!(vcs.prune_commits().unwrap(), 2);
+    vcs.collect_garbage().unwrap();
 
     let path = Path::new("./tests/test_data/repo1");
-    let path = path.join(".vcs").join("objects").join(hash1.to_string());
 
-    assert!(!path.exists());
+    assert!(vcs.get_commit_by_hash(&hash1).is_err());
+    assert!(vcs.get_commit_by_hash(&hash2).is_err());
+    assert!(!object_path(path, commit.get_tree_hash()).exists());
+    assert!(!object_path(path, commit1.get_tree_hash()).exists());
+    assert!(object_path(path, vcs.get_current_commit().get_tree_hash()).exists());
 
-    let path = Path::new("./tests/test_data/repo1");
-    let path = path.join(".vcs").join("objects").join(hash2.to_string());
+    assert!(object_path(path, &hash_data(&data)).exists());
+    assert!(!object_path(path, &hash_data(&[])).exists());
 
-    assert!(!path.exists());
+    fs::remove_dir_all(Path::new("./tests/test_data/repo1/.vcs")).unwrap();
+    fs::remove_file(Path::new("./tests/test_data/repo1/add_file.txt")).unwrap();
+    fs::remove_file(Path::new("./tests/test_data/repo1/new_file.txt")).unwrap();
+}
 
-    fs::remove_dir_all(Path::new("./tests/test_data/repo1/.vcs"));
-    fs::remove_file(Path::new("./tests/test_data/repo1/add_file.txt"));    
-    fs::remove_file(Path::new("./tests/test_data/repo1/new_file.txt"));    
+#[test]
+pub fn test_object_store() {
+    let path = Path::new("./tests/test_data_object_store");
+    fs::create_dir_all(path).unwrap();
+
+    let hash = put_object(path, b"same").unwrap();
+    assert_eq!(hash, hash_data(b"same"));
+    assert_eq!(put_object(path, b"same").unwrap(), hash);
+    assert_eq!(fs::read_dir(objects_dir(path)).unwrap().count(), 1);
+    assert_eq!(read_object(path, &hash).unwrap(), b"same");
+
+    fs::write(path.join("a.txt"), "same").unwrap();
+    let first = Commit::init(path, "first", None, true).unwrap();
+    fs::write(path.join("b.txt"), "other").unwrap();
+    let second = Commit::init(path, "second", Some(calculate_hash(&first)), false).unwrap();
+    let first_tree = flatten_tree(path, first.get_tree_hash()).unwrap();
+    let second_tree = flatten_tree(path, second.get_tree_hash()).unwrap();
+    let a = Path::new("a.txt");
+    assert_eq!(first_tree[a].1, hash);
+    assert_eq!(second_tree[a].1, hash);
+
+    let other = hash_data(b"other");
+    remove_unreferenced(path, &HashSet::from([hash.clone()])).unwrap();
+    assert!(object_path(path, &hash).exists());
+    assert!(!object_path(path, &other).exists());
+    assert!(!object_path(path, first.get_tree_hash()).exists());
+    assert!(matches!(
+        read_object(path, &other),
+        Err(VcsError::NoObject(_))
+    ));
+    assert!(matches!(
+        read_object(path, "zz"),
+        Err(VcsError::NoObject(_))
+    ));
+
+    remove_unreferenced(path, &HashSet::new()).unwrap();
+    assert_eq!(fs::read_dir(objects_dir(path)).unwrap().count(), 0);
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+pub fn test_migrate_legacy_repository() {
+    let path = Path::new("./tests/test_data_migration");
+    let objects = path.join(".vcs").join("objects");
+    fs::create_dir_all(objects.join("12345678901234567890")).unwrap();
+    fs::create_dir_all(objects.join("9876543210987654321")).unwrap();
+
+    let file = |name: &str, data: &[u8], path: &str| {
+        serde_json::json!({"name": name, "data": data, "path": path}).to_string()
+    };
+    fs::write(
+        objects.join("12345678901234567890").join("b.txt"),
+        file("b.txt", b"old", "/old/place/a/b.txt"),
+    )
+    .unwrap();
+    fs::write(
+        objects.join("9876543210987654321").join("b.txt"),
+        file("b.txt", b"new", "/old/place/a/b.txt"),
+    )
+    .unwrap();
+    fs::write(
+        objects.join("9876543210987654321").join("c.txt"),
+        file("c.txt", b"c", "/old/place/c.txt"),
+    )
+    .unwrap();
+
+    let commit1 = serde_json::json!({"parent": null, "message": "Initial commit", "is_first_in_branch": true,
+                                     "root_path": "/old/place", "date": "2022-11-01T10:00:00+03:00"});
+    let commit2 = serde_json::json!({"parent": 12345678901234567890u64, "message": "second", "is_first_in_branch": false,
+                                     "root_path": "/old/place", "date": "2022-11-02T10:00:00+03:00"});
+    let metadata = serde_json::json!({
+        "branches": [{"first_commit": commit1, "last_commit": commit2, "name": "master"}],
+        "current_branch_id": 0,
+        "all_commits": {"12345678901234567890": commit1, "9876543210987654321": commit2},
+        "root_dir": "/old/place",
+        "current_commit": commit2,
+    });
+    let mut broken = metadata.clone();
+    broken["current_branch_id"] = serde_json::json!(5);
+    fs::write(
+        path.join(".vcs").join("VCSRepository.json"),
+        broken.to_string(),
+    )
+    .unwrap();
+    assert!(matches!(
+        read_vcs(path.to_path_buf()),
+        Err(VcsError::CorruptedRepository(_))
+    ));
+    assert!(objects.join("12345678901234567890").is_dir());
+    assert!(objects.join("9876543210987654321").is_dir());
+
+    fs::write(
+        path.join(".vcs").join("VCSRepository.json"),
+        metadata.to_string(),
+    )
+    .unwrap();
+
+    assert!(matches!(
+        read_vcs_shared(path.to_path_buf()),
+        Err(VcsError::OutdatedVersion(0))
+    ));
+    assert!(objects.join("12345678901234567890").is_dir());
+
+    let vcs = read_vcs(path.to_path_buf()).unwrap();
+    let current = vcs.get_current_commit().clone();
+    assert_eq!(current.get_msg(), "second");
+    assert!(read_vcs_shared(path.to_path_buf()).is_ok());
+    assert_eq!(vcs.get_last_branch_commit().unwrap().clone(), current);
+
+    let files = files_from_commit(path, &current).unwrap();
+    assert_eq!(files.len(), 2);
+    for file in files.iter() {
+        if file.get_path() == Path::new("a/b.txt") {
+            assert_eq!(file.get_data().clone(), b"new".to_vec());
+        } else {
+            assert_eq!(file.get_path(), Path::new("c.txt"));
+        }
+    }
+
+    let parent = vcs
+        .get_commit_by_hash(current.get_parent_hash().unwrap())
+        .unwrap();
+    assert_eq!(parent.get_msg(), "Initial commit");
+    let branch = vcs.get_current_branch().unwrap();
+    assert_eq!(
+        vcs.get_first_commit(branch).unwrap().clone(),
+        parent.clone()
+    );
+    assert!(!objects.join("12345678901234567890").exists());
+    assert!(!objects.join("9876543210987654321").exists());
+
+    let buffer = fs::read_to_string(path.join(".vcs").join("VCSRepository.json")).unwrap();
+    let buffer: serde_json::Value = serde_json::from_str(&buffer).unwrap();
+    assert_eq!(buffer["version"], 5);
+    assert_eq!(buffer["default_branch"], "master");
+    assert!(buffer.get("branches").is_none());
+    let master =
+        fs::read_to_string(path.join(".vcs").join("refs").join("heads").join("master")).unwrap();
+    assert_eq!(master.trim(), calculate_hash(&current));
+    assert_eq!(
+        fs::read_to_string(path.join(".vcs").join("HEAD")).unwrap(),
+        "ref: refs/heads/master\n"
+    );
+    assert_eq!(
+        buffer["all_commits"][current.get_parent_hash().unwrap()]["parents"],
+        serde_json::json!([])
+    );
+    assert!(buffer.get("root_dir").is_none());
+    let vcs = read_vcs(path.to_path_buf()).unwrap();
+    assert_eq!(vcs.get_current_commit().clone(), current);
+
+    fs::remove_dir_all(path).unwrap();
+}
 
-}
\ No newline at end of file
+#[test]
+#[cfg(unix)]
+pub fn test_unreadable_file() {
+    let path = Path::new("./tests/test_data_unreadable");
+    fs::create_dir_all(path).unwrap();
+    std::os::unix::fs::symlink("missing.txt", path.join("link.txt")).unwrap();
+
+    let commit = Commit::init(path, "unreadable", None, true);
+    assert!(matches!(commit, Err(VcsError::Io { .. })));
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+pub fn test_missing_objects() {
+    let path = Path::new("./tests/test_data_missing_objects");
+    fs::create_dir_all(path.join("dir")).unwrap();
+    fs::write(path.join("dir").join("file.txt"), "data").unwrap();
+    let commit = Commit::init(path, "commit", None, true).unwrap();
+
+    fs::remove_dir_all(path.join(".vcs").join("objects")).unwrap();
+    assert!(matches!(
+        files_from_commit(path, &commit),
+        Err(VcsError::NoObject(_))
+    ));
+    assert!(matches!(
+        flatten_tree(path, commit.get_tree_hash()),
+        Err(VcsError::NoObject(_))
+    ));
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+pub fn test_truncated_metadata() {
+    let path = Path::new("./tests/test_data_truncated");
+    fs::create_dir_all(path.join(".vcs")).unwrap();
+    let vcs = VcsRepository::init(path, "master").unwrap();
+    pack_vcs(path, &vcs).unwrap();
+
+    let metadata = path.join(".vcs").join("VCSRepository.json");
+    let data = fs::read(&metadata).unwrap();
+    fs::write(&metadata, &data[..data.len() / 2]).unwrap();
+    assert!(matches!(
+        read_vcs(path.to_path_buf()),
+        Err(VcsError::Json { .. })
+    ));
+
+    fs::remove_file(&metadata).unwrap();
+    assert!(matches!(
+        read_vcs(path.to_path_buf()),
+        Err(VcsError::Io { .. })
+    ));
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+pub fn test_repository_lock() {
+    let path = Path::new("./tests/test_data_lock");
+    fs::create_dir_all(path.join(".vcs")).unwrap();
+
+    let lock = RepositoryLock::acquire(path, LockMode::Exclusive).unwrap();
+    assert!(exclusive_lock_path(path).exists());
+    assert!(matches!(
+        RepositoryLock::acquire(path, LockMode::Exclusive),
+        Err(VcsError::RepositoryBusy(_))
+    ));
+    assert!(matches!(
+        RepositoryLock::acquire(path, LockMode::Shared),
+        Err(VcsError::RepositoryBusy(_))
+    ));
+    drop(lock);
+    assert!(!exclusive_lock_path(path).exists());
+
+    let reader1 = RepositoryLock::acquire(path, LockMode::Shared).unwrap();
+    let reader2 = RepositoryLock::acquire(path, LockMode::Shared).unwrap();
+    assert!(matches!(
+        RepositoryLock::acquire(path, LockMode::Exclusive),
+        Err(VcsError::RepositoryBusy(_))
+    ));
+    drop(reader1);
+    drop(reader2);
+    assert!(RepositoryLock::acquire(path, LockMode::Exclusive).is_ok());
+
+    let stale = serde_json::json!({"pid": u32::MAX, "date": "2022-11-01T10:00:00+03:00"});
+    fs::write(exclusive_lock_path(path), stale.to_string()).unwrap();
+    let lock = RepositoryLock::acquire(path, LockMode::Exclusive).unwrap();
+    assert_ne!(
+        fs::read_to_string(exclusive_lock_path(path)).unwrap(),
+        stale.to_string()
+    );
+    drop(lock);
+
+    let old = serde_json::json!({"pid": std::process::id(), "date": "2022-11-01T10:00:00+03:00"});
+    fs::write(exclusive_lock_path(path), old.to_string()).unwrap();
+    assert!(matches!(
+        RepositoryLock::acquire(path, LockMode::Exclusive),
+        Err(VcsError::RepositoryBusy(_))
+    ));
+    fs::remove_file(exclusive_lock_path(path)).unwrap();
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+pub fn test_unified_diff() {
+    let old = ["a", "b", "c"];
+    let new = ["a", "c", "d"];
+    assert_eq!(
+        diff_lines(&old, &new),
+        vec![
+            DiffLine::Equal("a"),
+            DiffLine::Delete("b"),
+            DiffLine::Equal("c"),
+            DiffLine::Insert("d"),
+        ]
+    );
+    assert!(diff_lines(&[], &[]).is_empty());
+
+    let old: String = (1..=20).map(|line| format!("{}\n", line)).collect();
+    let new: String = (1..=20)
+        .map(|line| match line {
+            2 => "two\n".to_string(),
+            18 => "eighteen\n".to_string(),
+            _ => format!("{}\n", line),
+        })
+        .collect();
+    let diff = unified_diff(
+        Path::new("f.txt"),
+        Some(old.as_bytes()),
+        Some(new.as_bytes()),
+        1,
+    );
+    assert_eq!(
+        diff,
+        "diff a/f.txt b/f.txt\n--- a/f.txt\n+++ b/f.txt\n\
+                      @@ -1,3 +1,3 @@\n 1\n-2\n+two\n 3\n\
+                      @@ -17,3 +17,3 @@\n 17\n-18\n+eighteen\n 19\n"
+    );
+
+    let diff = unified_diff(Path::new("f.txt"), None, Some(b"x"), 3);
+    assert!(diff.contains(
+        "--- /dev/null\n+++ b/f.txt\n@@ -0,0 +1,1 @@\n+x\n\\ No newline at end of file\n"
+    ));
+
+    let diff = unified_diff(Path::new("f.bin"), Some(&[0, 1]), Some(&[0, 2]), 3);
+    assert!(diff.ends_with("Binary files a/f.bin and b/f.bin differ\n"));
+}
+
+#[test]
+fn test_diff_lines_is_shortest() {
+    let mut seed = 7u64;
+    let mut next = move || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) as usize
+    };
+    let alphabet = ["a", "b", "c"];
+    for _ in 0..500 {
+        let old: Vec<&str> = (0..next() % 12).map(|_| alphabet[next() % 3]).collect();
+        let new: Vec<&str> = (0..next() % 12).map(|_| alphabet[next() % 3]).collect();
+
+        let mut lcs = vec![vec![0; new.len() + 1]; old.len() + 1];
+        for i in (0..old.len()).rev() {
+            for j in (0..new.len()).rev() {
+                lcs[i][j] = match old[i] == new[j] {
+                    true => lcs[i + 1][j + 1] + 1,
+                    false => lcs[i + 1][j].max(lcs[i][j + 1]),
+                };
+            }
+        }
+
+        let diff = diff_lines(&old, &new);
+        let (mut from, mut to) = (vec![], vec![]);
+        for line in diff.iter() {
+            match *line {
+                DiffLine::Equal(line) => {
+                    from.push(line);
+                    to.push(line);
+                }
+                DiffLine::Delete(line) => from.push(line),
+                DiffLine::Insert(line) => to.push(line),
+            }
+        }
+        assert_eq!((&from, &to), (&old, &new));
+        let edits = diff
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Equal(_)))
+            .count();
+        assert_eq!(edits, old.len() + new.len() - 2 * lcs[0][0]);
+    }
+
+    let old: Vec<String> = (0..100_000).map(|line| line.to_string()).collect();
+    let new: Vec<String> = (0..100_000)
+        .map(|line| match line % 1000 {
+            0 => "changed".to_string(),
+            _ => line.to_string(),
+        })
+        .collect();
+    let old: Vec<&str> = old.iter().map(String::as_str).collect();
+    let new: Vec<&str> = new.iter().map(String::as_str).collect();
+    let edits = diff_lines(&old, &new)
+        .iter()
+        .filter(|line| !matches!(line, DiffLine::Equal(_)))
+        .count();
+    assert_eq!(edits, 200);
+}
+
+#[test]
+fn test_merge_texts() {
+    let ancestor = "1\n2\n3\n4\n5\n6\n7\n";
+    let ours = "one\n2\n3\n4\n5\n6\n7\n";
+    let theirs = "1\n2\n3\n4\n5\n6\nseven\n";
+    let (merged, conflict) = merge_texts(ancestor, ours, theirs, "master", "branch");
+    assert!(!conflict);
+    assert_eq!(merged, "one\n2\n3\n4\n5\n6\nseven\n");
+
+    let (merged, conflict) = merge_texts(ancestor, ours, ours, "master", "branch");
+    assert!(!conflict);
+    assert_eq!(merged, ours);
+
+    let theirs = "uno\n2\n3\n4\n5\n6\n";
+    let (merged, conflict) = merge_texts(ancestor, ours, theirs, "master", "branch");
+    assert!(conflict);
+    assert_eq!(
+        merged,
+        "<<<<<<< master\none\n=======\nuno\n>>>>>>> branch\n2\n3\n4\n5\n6\n"
+    );
+
+    let (merged, conflict) = merge_texts("a", "b", "c", "master", "branch");
+    assert!(conflict);
+    assert_eq!(merged, "<<<<<<< master\nb\n=======\nc\n>>>>>>> branch\n");
+    assert!(has_conflict_markers(merged.as_bytes()));
+
+    assert!(!has_conflict_markers(b"Title\n=======\ntext\n"));
+    assert!(!has_conflict_markers(b"<<<<<<< master\nb\n=======\nc\n"));
+    assert!(!has_conflict_markers(
+        b">>>>>>> quote\n=======\n<<<<<<< end\n"
+    ));
+}
+
+#[test]
+fn test_branch_names() {
+    for name in ["feature", "fix/issue-12", "v1.0", "release/2023/q1"] {
+        assert!(Branch::check_name(name).is_ok(), "{} should be valid", name);
+    }
+    for name in [
+        "",
+        "HEAD",
+        "my branch",
+        "tab\tname",
+        "feature/",
+        "/feature",
+        "a//b",
+        "-f",
+        ".hidden",
+        "a/..",
+        "a~1",
+        "a^",
+        "a:b",
+        "a?",
+        "a*",
+        "a[",
+        "a\\b",
+    ] {
+        assert!(
+            matches!(
+                Branch::check_name(name),
+                Err(VcsError::InvalidBranchName { .. })
+            ),
+            "{} should be invalid",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_parse_revisions() {
+    let revision = Revision::parse("master~2^2^").unwrap();
+    assert_eq!(revision.get_base(), "master");
+    assert_eq!(
+        revision.get_steps(),
+        &[
+            AncestryStep::Ancestor(2),
+            AncestryStep::Parent(2),
+            AncestryStep::Parent(1)
+        ]
+    );
+    assert!(Revision::parse("v1.0").unwrap().get_steps().is_empty());
+    for expr in [
+        "",
+        "~1",
+        "HEAD~x",
+        "HEAD^1a",
+        "HEAD~99999999999999999999999",
+    ] {
+        assert!(
+            matches!(Revision::parse(expr), Err(VcsError::InvalidRevision { .. })),
+            "{}",
+            expr
+        );
+    }
+
+    let range = RevisionRange::parse("v1..master~1").unwrap();
+    assert_eq!(range.get_from(), Some(&Revision::parse("v1").unwrap()));
+    assert_eq!(range.get_to(), &Revision::parse("master~1").unwrap());
+    let range = RevisionRange::parse("feature..").unwrap();
+    assert_eq!(range.get_to(), &Revision::head());
+    let range = RevisionRange::parse("abcd").unwrap();
+    assert!(range.get_from().is_none());
+    assert!(RevisionRange::parse("a...b").is_err());
+}
+
+#[test]
+fn test_commit_identity() {
+    let path = Path::new("./tests/test_data_identity");
+    fs::create_dir_all(path.join(".vcs")).unwrap();
+    fs::write(
+        path.join(".vcs").join("config"),
+        "# identity\nuser.name = Ada Lovelace\n\nuser.email = ada@example.com\n",
+    )
+    .unwrap();
+
+    let commit = Commit::init(path, "with identity", None, true).unwrap();
+    let ada = Identity::init("Ada Lovelace", "ada@example.com");
+    assert_eq!(commit.get_author(), Some(&ada));
+    assert_eq!(commit.get_committer(), Some(&ada));
+    assert!(commit
+        .get_canonical_form()
+        .contains("author Ada Lovelace <ada@example.com>\n"));
+    assert!(commit
+        .get_canonical_form()
+        .contains("committer Ada Lovelace <ada@example.com>\n"));
+
+    let other = Identity::init("Charles Babbage", "charles@example.com");
+    let changed = Commit::from_tree(
+        commit.get_tree_hash(),
+        "with identity",
+        vec![],
+        true,
+        &other,
+        &ada,
+    );
+    assert_ne!(
+        calculate_hash(&changed),
+        calculate_hash(&Commit::from_tree(
+            commit.get_tree_hash(),
+            "with identity",
+            vec![],
+            true,
+            &ada,
+            &ada
+        ))
+    );
+
+    let mut legacy: serde_json::Value = serde_json::to_value(&commit).unwrap();
+    legacy.as_object_mut().unwrap().remove("author");
+    legacy.as_object_mut().unwrap().remove("committer");
+    let legacy: Commit = serde_json::from_value(legacy).unwrap();
+    assert!(legacy.get_author().is_none());
+    assert!(!legacy.get_canonical_form().contains("author"));
+
+    fs::write(path.join(".vcs").join("config"), "user.name\n").unwrap();
+    assert!(matches!(
+        Commit::init(path, "bad config", None, true),
+        Err(VcsError::InvalidConfig { line: 1, .. })
+    ));
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn test_config() {
+    let settings = |pairs: &[(&str, &str)]| {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    };
+    let config = Config::init(
+        settings(&[("diff.context", "5"), ("user.name", "Repo")]),
+        settings(&[
+            ("user.name", "User"),
+            ("user.email", "u@example.com"),
+            ("color.ui", "never"),
+        ]),
+    );
+    assert_eq!(config.get_user_name(), Some("Repo"));
+    assert_eq!(config.get("USER.Email"), Some("u@example.com"));
+    assert_eq!(config.get_diff_context().unwrap(), 5);
+    assert_eq!(config.get_color().unwrap(), ColorMode::Never);
+    assert_eq!(config.get_default_branch().unwrap(), "master");
+    assert_eq!(config.get_entries().len(), 4);
+
+    let bad = Config::init(
+        settings(&[("diff.context", "many"), ("init.defaultbranch", "a..b")]),
+        settings(&[]),
+    );
+    assert!(matches!(
+        bad.get_diff_context(),
+        Err(VcsError::InvalidConfigValue { .. })
+    ));
+    assert!(bad.get_default_branch().is_err());
+    assert_eq!(Config::default().get_color().unwrap(), ColorMode::Auto);
+    assert!(Config::check_key("core.excludesfile").is_ok());
+    assert!(Config::check_key("nosection").is_err());
+    assert!(Config::check_value("color.ui", "sometimes").is_err());
+
+    let path = Path::new("./tests/test_data_config/config");
+    assert!(!write_config_value(path, "user.name", None).unwrap());
+    assert!(!path.exists());
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, "# mine\nUser.Name = Old\ncolor.ui = auto\n").unwrap();
+    assert!(write_config_value(path, "user.name", Some("New")).unwrap());
+    assert!(!write_config_value(path, "diff.context", Some("1")).unwrap());
+    assert!(write_config_value(path, "color.ui", None).unwrap());
+    assert_eq!(
+        fs::read_to_string(path).unwrap(),
+        "# mine\nuser.name = New\ndiff.context = 1\n"
+    );
+    assert_eq!(read_config_file(path).unwrap().len(), 2);
+
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}